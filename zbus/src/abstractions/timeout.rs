@@ -1,35 +1,43 @@
-use crate::{Error, Result};
-use std::{future::Future, io::ErrorKind, time::Duration};
+use std::{future::Future, time::Duration};
 
 /// Awaits a future with a provided timeout.
+///
+/// Returns `None` if the future didn't complete in time.
 #[cfg(feature = "tokio")]
-pub(crate) async fn timeout<F, T>(fut: F, timeout: Duration) -> Result<T>
+pub(crate) async fn timeout<F, T>(fut: F, timeout: Duration) -> Option<T>
 where
-    F: Future<Output = Result<T>>,
+    F: Future<Output = T>,
 {
-    tokio::time::timeout(timeout, fut).await.map_err(|_| {
-        Error::from(std::io::Error::new(
-            ErrorKind::TimedOut,
-            "timed out".to_string(),
-        ))
-    })?
+    tokio::time::timeout(timeout, fut).await.ok()
 }
 
 /// Awaits a future with a provided timeout.
+///
+/// Returns `None` if the future didn't complete in time.
 #[cfg(not(feature = "tokio"))]
-pub(crate) async fn timeout<F, T>(fut: F, timeout: Duration) -> Result<T>
+pub(crate) async fn timeout<F, T>(fut: F, timeout: Duration) -> Option<T>
 where
-    F: Future<Output = Result<T>>,
+    F: Future<Output = T>,
 {
     use futures_lite::FutureExt;
 
-    fut.or(async {
-        async_io::Timer::after(timeout).await;
+    async { Some(fut.await) }
+        .or(async {
+            async_io::Timer::after(timeout).await;
 
-        Err(Error::from(std::io::Error::new(
-            ErrorKind::TimedOut,
-            "timed out",
-        )))
-    })
-    .await
+            None
+        })
+        .await
+}
+
+/// Sleep for the given duration.
+#[cfg(feature = "tokio")]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Sleep for the given duration.
+#[cfg(not(feature = "tokio"))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_io::Timer::after(duration).await;
 }
@@ -116,6 +116,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::default_constructed_unit_structs)]
     fn test_ibus_default() {
         let ibus = Ibus::default();
         assert_eq!(ibus.to_string(), "ibus:");
@@ -35,6 +35,9 @@ use std::{
 mod unix;
 pub use unix::{Unix, UnixSocket};
 mod tcp;
+pub(crate) use tcp::NONCE_LEN;
+#[cfg(feature = "p2p")]
+pub use tcp::NonceFile;
 pub use tcp::{Tcp, TcpTransportFamily};
 #[cfg(windows)]
 mod autolaunch;
@@ -171,25 +174,25 @@ impl Transport {
                     .map_err(Into::into)
             }
 
-            Transport::Tcp(mut addr) => match addr.take_nonce_file() {
+            Transport::Tcp(addr) => match addr.nonce_file_path()?.map(ToOwned::to_owned) {
                 Some(nonce_file) => {
                     #[allow(unused_mut)]
                     let mut stream = addr.connect().await?;
 
-                    #[cfg(unix)]
-                    let nonce_file = {
-                        use std::os::unix::ffi::OsStrExt;
-                        std::ffi::OsStr::from_bytes(&nonce_file)
-                    };
-
-                    #[cfg(windows)]
-                    let nonce_file = std::str::from_utf8(&nonce_file).map_err(|_| {
-                        Error::Address("nonce file path is invalid UTF-8".to_owned())
-                    })?;
+                    #[cfg(not(feature = "tokio"))]
+                    let nonce = std::fs::read(&nonce_file)?;
+                    #[cfg(feature = "tokio")]
+                    let nonce = tokio::fs::read(&nonce_file).await?;
+                    if nonce.len() != NONCE_LEN {
+                        return Err(Error::Address(format!(
+                            "nonce file `{}` must contain exactly {} bytes",
+                            nonce_file.display(),
+                            NONCE_LEN,
+                        )));
+                    }
 
                     #[cfg(not(feature = "tokio"))]
                     {
-                        let nonce = std::fs::read(nonce_file)?;
                         let mut nonce = &nonce[..];
 
                         while !nonce.is_empty() {
@@ -201,10 +204,7 @@ impl Transport {
                     }
 
                     #[cfg(feature = "tokio")]
-                    {
-                        let nonce = tokio::fs::read(nonce_file).await?;
-                        tokio::io::AsyncWriteExt::write_all(&mut stream, &nonce).await?;
-                    }
+                    tokio::io::AsyncWriteExt::write_all(&mut stream, &nonce).await?;
 
                     Ok(Stream::Tcp(stream))
                 }
@@ -4,9 +4,12 @@ use crate::{Error, Result};
 use async_io::Async;
 #[cfg(not(feature = "tokio"))]
 use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(feature = "p2p")]
+use std::path::PathBuf;
 use std::{
     collections::HashMap,
     fmt::{Display, Formatter},
+    path::Path,
     str::FromStr,
 };
 #[cfg(feature = "tokio")]
@@ -85,15 +88,30 @@ impl Tcp {
         self.nonce_file.take()
     }
 
+    /// The nonce file path, if any, as a `Path`.
+    pub(crate) fn nonce_file_path(&self) -> Result<Option<&Path>> {
+        let Some(nonce_file) = self.nonce_file() else {
+            return Ok(None);
+        };
+
+        #[cfg(unix)]
+        let nonce_file = {
+            use std::os::unix::ffi::OsStrExt;
+            std::ffi::OsStr::from_bytes(nonce_file)
+        };
+
+        #[cfg(windows)]
+        let nonce_file = std::str::from_utf8(nonce_file)
+            .map_err(|_| Error::Address("nonce file path is invalid UTF-8".to_owned()))?;
+
+        Ok(Some(Path::new(nonce_file)))
+    }
+
     pub(super) fn from_options(
         opts: HashMap<&str, &str>,
         nonce_tcp_required: bool,
     ) -> Result<Self> {
-        let bind = None;
-        if opts.contains_key("bind") {
-            return Err(Error::Address("`bind` isn't yet supported".into()));
-        }
-
+        let bind = opts.get("bind").map(|bind| bind.to_string());
         let host = opts
             .get("host")
             .ok_or_else(|| Error::Address("tcp address is missing `host`".into()))?
@@ -198,6 +216,87 @@ impl Display for Tcp {
     }
 }
 
+/// The length of the nonce sent by clients of a `nonce-tcp:` server.
+pub(crate) const NONCE_LEN: usize = 16;
+
+/// A nonce file, for the server side of the `nonce-tcp:` transport.
+///
+/// Right after connecting to a `nonce-tcp:` address, clients must send the nonce contained in the
+/// file referred to by the `noncefile` key. Since the file is only readable by the user who
+/// created it, this restricts who can connect to the server. Pass this to
+/// [`connection::Builder::nonce_file`] for each accepted connection, to have the nonce checked.
+///
+/// The file is removed when this is dropped.
+///
+/// This type is only available when the `p2p` feature is enabled.
+///
+/// [`connection::Builder::nonce_file`]: crate::connection::Builder::nonce_file
+#[cfg(feature = "p2p")]
+#[derive(Debug)]
+pub struct NonceFile {
+    path: PathBuf,
+    nonce: [u8; NONCE_LEN],
+}
+
+#[cfg(feature = "p2p")]
+impl NonceFile {
+    /// Generate a random nonce and write it to a file at `path`.
+    ///
+    /// The file is only readable by the current user. If `path` already exists, it's replaced.
+    pub fn create<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        use std::io::Write;
+
+        let path = path.into();
+        let mut nonce = [0; NONCE_LEN];
+        getrandom::fill(&mut nonce).map_err(std::io::Error::from)?;
+
+        // Write to a new file first, so we don't have to trust the permissions of an existing one.
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(format!(".{}", uuid::Uuid::new_v4().simple()));
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            options.mode(0o600);
+        }
+        let write = || {
+            options.open(&tmp_path)?.write_all(&nonce)?;
+
+            std::fs::rename(&tmp_path, &path)
+        };
+        if let Err(e) = write() {
+            let _ = std::fs::remove_file(&tmp_path);
+
+            return Err(e.into());
+        }
+
+        Ok(Self { path, nonce })
+    }
+
+    /// The path of the nonce file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn nonce(&self) -> [u8; NONCE_LEN] {
+        self.nonce
+    }
+}
+
+#[cfg(feature = "p2p")]
+impl Drop for NonceFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::debug!("Failed to remove nonce file `{}`: {e}", self.path.display());
+        }
+    }
+}
+
 /// A `tcp:` address family.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TcpTransportFamily {
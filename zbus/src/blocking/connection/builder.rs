@@ -11,13 +11,13 @@ use uds_windows::UnixStream;
 
 use zvariant::ObjectPath;
 
-#[cfg(feature = "p2p")]
-use crate::Guid;
 use crate::{
     Error, Result, address::Address, blocking::Connection, conn::AuthMechanism,
     connection::socket::BoxedSplit, names::WellKnownName, object_server::Interface,
     utils::block_on,
 };
+#[cfg(feature = "p2p")]
+use crate::{Guid, address::transport::NonceFile};
 
 /// A builder for [`zbus::blocking::Connection`].
 #[derive(Debug)]
@@ -163,6 +163,18 @@ impl<'a> Builder<'a> {
         self.0.server(guid).map(Self)
     }
 
+    /// Require the client to send the nonce from `nonce_file` before authenticating.
+    ///
+    /// This implements the server side of the `nonce-tcp:` transport and hence is only relevant
+    /// for server connections (see [`Builder::server`]). Building the connection fails if the
+    /// client sends a different nonce.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    #[cfg(feature = "p2p")]
+    pub fn nonce_file(self, nonce_file: &NonceFile) -> Self {
+        Self(self.0.nonce_file(nonce_file))
+    }
+
     /// Set the capacity of the main (unfiltered) queue.
     ///
     /// Since typically you'd want to set this at instantiation time, you can set it through the
@@ -248,13 +260,43 @@ impl<'a> Builder<'a> {
 
     /// Set a timeout for method calls.
     ///
-    /// Method calls will return
-    /// `zbus::Error::InputOutput(std::io::Error(kind: ErrorKind::TimedOut))` if a client does not
-    /// receive an answer from a service in time.
+    /// Method calls will return [`Error::MethodTimeout`] if a client does not receive an answer
+    /// from a service in time. The timeout can be overridden per proxy and per method call (see
+    /// [`crate::blocking::proxy::Builder::method_timeout`] and
+    /// [`crate::blocking::Proxy::call_with_timeout`]).
     pub fn method_timeout(self, timeout: std::time::Duration) -> Self {
         Self(self.0.method_timeout(timeout))
     }
 
+    /// Automatically reconnect to the bus when the connection is lost.
+    ///
+    /// See [`crate::connection::Builder::auto_reconnect`] for details.
+    pub fn auto_reconnect(self, policy: crate::connection::ReconnectPolicy) -> Self {
+        Self(self.0.auto_reconnect(policy))
+    }
+
+    /// Register an interceptor for the messages sent and received on the connection.
+    ///
+    /// See [`crate::connection::Builder::interceptor`] for details.
+    pub fn interceptor<I>(self, interceptor: I) -> Self
+    where
+        I: crate::connection::Interceptor,
+    {
+        Self(self.0.interceptor(interceptor))
+    }
+
+    /// Set the [`Authorizer`] for the method calls dispatched by the [`crate::ObjectServer`].
+    ///
+    /// See [`Authorizer`] for details.
+    ///
+    /// [`Authorizer`]: crate::object_server::Authorizer
+    pub fn authorizer<A>(self, authorizer: A) -> Self
+    where
+        A: crate::object_server::Authorizer,
+    {
+        Self(self.0.authorizer(authorizer))
+    }
+
     /// Build the connection, consuming the builder.
     ///
     /// # Errors
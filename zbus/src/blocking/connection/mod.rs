@@ -7,7 +7,7 @@ use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName,
 use zvariant::ObjectPath;
 
 use crate::{
-    DBusError, Error, Result,
+    DBusError, Error, OwnedGuid, Result,
     blocking::ObjectServer,
     fdo::{ConnectionCredentials, RequestNameFlags, RequestNameReply},
     message::Message,
@@ -52,11 +52,25 @@ impl Connection {
         self.inner.server_guid()
     }
 
+    /// The GUID of the current server.
+    ///
+    /// See [`zbus::Connection::current_server_guid`] for details.
+    pub fn current_server_guid(&self) -> OwnedGuid {
+        self.inner.current_server_guid()
+    }
+
     /// The unique name as assigned by the message bus or `None` if not a message bus connection.
     pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
         self.inner.unique_name()
     }
 
+    /// The current unique name of the connection.
+    ///
+    /// See [`zbus::Connection::current_unique_name`] for details.
+    pub fn current_unique_name(&self) -> Option<OwnedUniqueName> {
+        self.inner.current_unique_name()
+    }
+
     /// Send `msg` to the peer.
     pub fn send(&self, msg: &Message) -> Result<()> {
         block_on(self.inner.send(msg))
@@ -297,6 +311,13 @@ impl Connection {
     pub fn method_timeout(&self) -> Option<std::time::Duration> {
         self.inner.method_timeout()
     }
+
+    /// Statistics about the traffic on this connection.
+    ///
+    /// See [`crate::Connection::stats`] for details.
+    pub fn stats(&self) -> crate::connection::Stats {
+        self.inner.stats()
+    }
 }
 
 impl From<crate::Connection> for Connection {
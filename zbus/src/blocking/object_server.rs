@@ -1,10 +1,15 @@
 //! The object server API.
 
+use std::sync::Arc;
+use zbus_names::{InterfaceName, UniqueName};
 use zvariant::ObjectPath;
 
 use crate::{
     Error, Result,
-    object_server::{Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter},
+    object_server::{
+        BusyGuard, DynamicInterface, Interface, InterfaceDeref, InterfaceDerefMut, NodeEnumerator,
+        PropertiesBatch, SignalEmitter,
+    },
     utils::block_on,
 };
 
@@ -77,6 +82,20 @@ where
     pub fn signal_emitter(&self) -> &SignalEmitter<'static> {
         self.azync.signal_emitter()
     }
+
+    /// Coalesce property changes into a single `PropertiesChanged` signal per interface.
+    ///
+    /// See [`crate::object_server::InterfaceRef::batch`] for details.
+    pub fn batch<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&SignalEmitter<'static>) -> R,
+    {
+        let batch = Arc::new(PropertiesBatch::default());
+        let res = f(&self.signal_emitter().clone().with_batch(batch.clone()));
+        block_on(batch.emit(self.signal_emitter()))?;
+
+        Ok(res)
+    }
 }
 
 /// A blocking wrapper of [`crate::ObjectServer`].
@@ -158,6 +177,17 @@ impl ObjectServer {
         block_on(self.azync.at(path, iface))
     }
 
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// If the interface already exists at this path, returns false.
+    pub fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.at_dynamic(path, iface))
+    }
+
     /// Unregister a D-Bus [`crate::object_server::Interface`] at a given path.
     ///
     /// If there are no more interfaces left at that path, destroys the object as well.
@@ -171,6 +201,103 @@ impl ObjectServer {
         block_on(self.azync.remove::<I, P>(path))
     }
 
+    /// Unregister the interface named `name`, typically a [`DynamicInterface`], at a given path.
+    ///
+    /// If there are no more interfaces left at that path, destroys the object as well.
+    /// Returns whether the object was destroyed.
+    pub fn remove_dynamic<'p, P, N>(&self, path: P, name: N) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        N: TryInto<InterfaceName<'static>>,
+        N::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_dynamic(path, name))
+    }
+
+    /// Register an object with multiple interfaces at once.
+    ///
+    /// See [`crate::ObjectServer::object`] for details.
+    pub fn object<'p, P>(&self, path: P) -> Result<ObjectBuilder<'_>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        self.azync.object(path).map(ObjectBuilder)
+    }
+
+    /// Unregister the object at the given path, with all its interfaces at once.
+    ///
+    /// See [`crate::ObjectServer::remove_object`] for details.
+    pub fn remove_object<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_object(path))
+    }
+
+    /// Register a fallback D-Bus [`Interface`] for all the objects under a path prefix.
+    ///
+    /// See [`crate::ObjectServer::at_fallback`] for details.
+    pub fn at_fallback<'p, P, I>(&self, prefix: P, iface: I) -> Result<bool>
+    where
+        I: Interface,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.at_fallback(prefix, iface))
+    }
+
+    /// Unregister a fallback D-Bus [`Interface`] at a given path prefix.
+    ///
+    /// See [`crate::ObjectServer::remove_fallback`] for details.
+    pub fn remove_fallback<'p, I, P>(&self, prefix: P) -> Result<bool>
+    where
+        I: Interface,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_fallback::<I, P>(prefix))
+    }
+
+    /// Set the [`NodeEnumerator`] listing the objects under a path prefix.
+    ///
+    /// See [`crate::ObjectServer::set_node_enumerator`] for details.
+    pub fn set_node_enumerator<'p, P, E>(&self, prefix: P, enumerator: E) -> Result<()>
+    where
+        E: NodeEnumerator,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.set_node_enumerator(prefix, enumerator))
+    }
+
+    /// Unset the [`NodeEnumerator`] at a given path prefix.
+    ///
+    /// See [`crate::ObjectServer::remove_node_enumerator`] for details.
+    pub fn remove_node_enumerator<'p, P>(&self, prefix: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_node_enumerator(prefix))
+    }
+
+    /// Keep the object server busy, as long as the returned guard is alive.
+    ///
+    /// See [`crate::ObjectServer::busy`] for details.
+    pub fn busy(&self) -> BusyGuard {
+        self.azync.busy()
+    }
+
+    /// Wait for the object server to be idle for the given duration.
+    ///
+    /// See [`crate::ObjectServer::idle`] for details.
+    pub fn idle(&self, timeout: std::time::Duration) -> Result<()> {
+        block_on(self.azync.idle(timeout))
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -232,6 +359,55 @@ impl ObjectServer {
     }
 }
 
+/// Builder for registering an object with all its interfaces at once.
+///
+/// This is the blocking version of [`crate::object_server::ObjectBuilder`]. Use
+/// [`ObjectServer::object`] to create an instance of this type.
+#[derive(Debug)]
+#[must_use = "the object is only registered by `ObjectBuilder::register`"]
+pub struct ObjectBuilder<'s>(crate::object_server::ObjectBuilder<'s>);
+
+impl ObjectBuilder<'_> {
+    /// Add an [`Interface`] to the object.
+    pub fn with<I>(self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        Self(self.0.with(iface))
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    pub fn with_dynamic(self, iface: DynamicInterface) -> Self {
+        Self(self.0.with_dynamic(iface))
+    }
+
+    /// Tie the lifetime of the object to the peer with the given unique name.
+    ///
+    /// See [`crate::object_server::ObjectBuilder::owned_by`] for details.
+    pub fn owned_by<'n, N>(self, owner: N) -> Result<Self>
+    where
+        N: TryInto<UniqueName<'n>>,
+        N::Error: Into<Error>,
+    {
+        self.0.owned_by(owner).map(Self)
+    }
+
+    /// Set a callback to call after the object is removed, as its owner disconnected.
+    pub fn on_owner_vanished<F>(self, cleanup: F) -> Self
+    where
+        F: FnOnce(ObjectPath<'static>) + Send + 'static,
+    {
+        Self(self.0.on_owner_vanished(cleanup))
+    }
+
+    /// Register the object with all its interfaces.
+    ///
+    /// See [`crate::object_server::ObjectBuilder::register`] for details.
+    pub fn register(self) -> Result<bool> {
+        block_on(self.0.register())
+    }
+}
+
 impl From<crate::ObjectServer> for ObjectServer {
     fn from(azync: crate::ObjectServer) -> Self {
         Self { azync }
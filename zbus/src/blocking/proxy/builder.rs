@@ -49,6 +49,16 @@ impl<'a, T> Builder<'a, T> {
         Self(self.0.uncached_properties(properties))
     }
 
+    /// Set a timeout for method calls through the proxy.
+    ///
+    /// This overrides the timeout of the connection (see
+    /// [`crate::blocking::connection::Builder::method_timeout`]). Method calls will return
+    /// [`crate::Error::MethodTimeout`] if no reply is received in time.
+    #[must_use]
+    pub fn method_timeout(self, timeout: std::time::Duration) -> Self {
+        Self(self.0.method_timeout(timeout))
+    }
+
     /// Build a proxy from the builder.
     ///
     /// # Panics
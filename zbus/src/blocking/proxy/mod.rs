@@ -153,6 +153,14 @@ impl<'a> Proxy<'a> {
         self.inner().interface()
     }
 
+    /// The timeout for method calls through this proxy (if any).
+    ///
+    /// This is the timeout set through [`Builder::method_timeout`] if any, or the one of the
+    /// connection otherwise.
+    pub fn method_timeout(&self) -> Option<std::time::Duration> {
+        self.inner().method_timeout()
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](https://docs.rs/zbus_xml) crate for parsing the result.
@@ -238,6 +246,26 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call(method_name, body))
     }
 
+    /// Call a method with the given timeout and return the reply body.
+    ///
+    /// This is the same as [`Proxy::call`], except that the given timeout overrides the one of the
+    /// proxy and the connection (see [`Proxy::method_timeout`]). [`Error::MethodTimeout`] is
+    /// returned if no reply is received in time.
+    pub fn call_with_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        timeout: std::time::Duration,
+        body: &B,
+    ) -> Result<R>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        block_on(self.inner().call_with_timeout(method_name, timeout, body))
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -262,6 +290,29 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call_with_flags(method_name, flags, body))
     }
 
+    /// Call a method with the given timeout and return the reply body, optionally supplying a set
+    /// of method flags to control the way the method call message is sent and handled.
+    ///
+    /// This combines [`Proxy::call_with_flags`] and [`Proxy::call_with_timeout`].
+    pub fn call_with_flags_and_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        flags: BitFlags<MethodFlags>,
+        timeout: std::time::Duration,
+        body: &B,
+    ) -> Result<Option<R>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        block_on(
+            self.inner()
+                .call_with_flags_and_timeout(method_name, flags, timeout, body),
+        )
+    }
+
     /// Call a method without expecting a reply.
     ///
     /// This sets the `NoReplyExpected` flag on the calling message and does not wait for a reply.
@@ -350,6 +401,23 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().receive_owner_changed()).map(OwnerChangedIterator)
     }
 
+    /// Wait for the destination to have an owner, and return its unique name.
+    ///
+    /// See [`crate::Proxy::wait_for_owner`] for details.
+    pub fn wait_for_owner(&self, timeout: std::time::Duration) -> Result<UniqueName<'static>> {
+        block_on(self.inner().wait_for_owner(timeout))
+    }
+
+    /// Request the bus to start the destination service, and wait for it to have an owner.
+    ///
+    /// See [`crate::Proxy::start_and_wait_for_owner`] for details.
+    pub fn start_and_wait_for_owner(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<UniqueName<'static>> {
+        block_on(self.inner().start_and_wait_for_owner(timeout))
+    }
+
     /// Get a reference to the underlying async Proxy.
     pub fn inner(&self) -> &crate::Proxy<'a> {
         self.azync.as_ref().expect("Inner proxy is `None`")
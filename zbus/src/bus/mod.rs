@@ -0,0 +1,588 @@
+//! A message bus implementation.
+//!
+//! This module provides [`Bus`], a minimal D-Bus message bus (broker) that can be embedded in
+//! applications and tests, removing the need for an external `dbus-daemon` or `dbus-broker`.
+//!
+//! This module is only available when the `bus-impl` feature is enabled.
+
+#[cfg(not(feature = "tokio"))]
+use async_io::Async;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+#[cfg(unix)]
+use crate::address::transport::{Unix, UnixSocket};
+use crate::{
+    Error, Executor, Guid, OwnedGuid, Result,
+    address::{
+        Address,
+        transport::{NonceFile, Tcp, Transport},
+    },
+    connection,
+};
+
+mod names;
+mod peers;
+use peers::Peers;
+
+/// A D-Bus message bus.
+///
+/// The bus listens for clients on a single address. Each client that connects gets authenticated
+/// through the usual server-side handshake and is assigned a unique name once it calls `Hello`.
+/// After that, the bus:
+///
+/// * implements the `org.freedesktop.DBus` interface, including well-known name ownership and
+///   queuing (`RequestName`, `ReleaseName` etc), match rules (`AddMatch` and `RemoveMatch`) and
+///   name lookups (`GetNameOwner`, `NameHasOwner`, `ListNames` etc).
+/// * emits the `NameOwnerChanged`, `NameAcquired` and `NameLost` signals.
+/// * routes messages with a destination to the owner of the destination name and broadcasts the
+///   ones without a destination to all clients with a matching match rule.
+///
+/// Unix domain sockets (`path`, `abstract`, `dir` and `tmpdir`), TCP and nonce-TCP addresses are
+/// supported. With TCP, clients are authenticated anonymously.
+///
+/// This type is only available when the `bus-impl` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::bus::Bus;
+///
+/// let bus = Bus::for_address("unix:tmpdir=/tmp").await?;
+/// // Clients connect to this address.
+/// println!("{}", bus.address());
+///
+/// bus.run().await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Bus {
+    guid: OwnedGuid,
+    address: Address,
+    listener: Listener,
+    peers: Arc<Peers>,
+    executor: Executor<'static>,
+    // The socket file we created and hence need to remove on drop.
+    #[cfg(unix)]
+    socket_path: Option<PathBuf>,
+}
+
+impl Bus {
+    /// Create a bus listening on the given address.
+    ///
+    /// For `unix:dir` and `unix:tmpdir` addresses, a socket file with a random name is created in
+    /// the given directory. For `tcp` addresses, port `0` picks any available port. In both cases,
+    /// use [`Bus::address`] to get the address clients can connect to. For `nonce-tcp` addresses, a
+    /// new nonce is written to the file referred to by `noncefile`, which is removed on drop.
+    pub async fn for_address<A>(address: A) -> Result<Self>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<Error>,
+    {
+        let address = address.try_into().map_err(Into::into)?;
+        let guid: OwnedGuid = Guid::generate().into();
+
+        let (listener, transport) = match address.transport() {
+            #[cfg(unix)]
+            Transport::Unix(unix) => {
+                let (listener, socket) = Listener::bind_unix(unix)?;
+                (listener, Transport::Unix(Unix::new(socket)))
+            }
+            Transport::Tcp(tcp) => {
+                let (listener, tcp) = Listener::bind_tcp(tcp)?;
+                (listener, Transport::Tcp(tcp))
+            }
+            transport => {
+                return Err(Error::Address(format!(
+                    "transport `{transport}` isn't supported by the bus"
+                )));
+            }
+        };
+        #[cfg(unix)]
+        let socket_path = match &transport {
+            Transport::Unix(unix) => match unix.path() {
+                UnixSocket::File(path) => Some(path.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let address = Address::new(transport).set_guid(guid.clone())?;
+        info!("Bus listening on `{address}`");
+
+        Ok(Self {
+            peers: Arc::new(Peers::new(guid.clone())),
+            guid,
+            address,
+            listener,
+            executor: Executor::new(),
+            #[cfg(unix)]
+            socket_path,
+        })
+    }
+
+    /// The address clients can connect to.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The GUID of the bus.
+    pub fn guid(&self) -> &OwnedGuid {
+        &self.guid
+    }
+
+    /// Run the bus.
+    ///
+    /// This accepts client connections and serves them until an error occurs while accepting new
+    /// clients. Errors specific to a client connection only result in that client getting
+    /// disconnected.
+    pub async fn run(&self) -> Result<()> {
+        self.executor
+            .run(async {
+                loop {
+                    let builder = self.listener.accept().await?;
+                    let unique_name = self.peers.next_unique_name().await;
+                    let guid = self.guid.clone();
+                    let peers = self.peers.clone();
+
+                    self.executor
+                        .spawn(
+                            async move {
+                                let stream = match builder
+                                    .server(guid)
+                                    .map(|b| b.p2p())
+                                    .and_then(|b| b.unique_name(unique_name.clone()))
+                                {
+                                    Ok(builder) => builder.build_message_stream().await,
+                                    Err(e) => Err(e),
+                                };
+                                match stream {
+                                    Ok(stream) => peers.serve(stream, unique_name).await,
+                                    Err(e) => debug!("Failed to establish client connection: {e}"),
+                                }
+                            },
+                            "bus peer",
+                        )
+                        .detach();
+                }
+            })
+            .await
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Bus {
+    fn drop(&mut self) {
+        if let Some(path) = self.socket_path.take() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                debug!("Failed to remove socket file `{}`: {e}", path.display());
+            }
+        }
+    }
+}
+
+#[cfg(all(unix, not(feature = "tokio")))]
+type UnixListener = Async<std::os::unix::net::UnixListener>;
+#[cfg(all(unix, feature = "tokio"))]
+type UnixListener = tokio::net::UnixListener;
+#[cfg(not(feature = "tokio"))]
+type TcpListener = Async<std::net::TcpListener>;
+#[cfg(feature = "tokio")]
+type TcpListener = tokio::net::TcpListener;
+
+#[derive(Debug)]
+enum Listener {
+    #[cfg(unix)]
+    Unix(UnixListener),
+    Tcp(TcpListener, Option<NonceFile>),
+}
+
+impl Listener {
+    /// Bind to the given unix socket address, returning the socket clients can connect to.
+    #[cfg(unix)]
+    fn bind_unix(unix: &Unix) -> Result<(Self, UnixSocket)> {
+        #[cfg(target_os = "linux")]
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::SocketAddr;
+
+        let socket = match unix.path() {
+            UnixSocket::Dir(dir) | UnixSocket::TmpDir(dir) => {
+                UnixSocket::File(dir.join(format!("dbus-{}", Guid::generate())))
+            }
+            socket => socket.clone(),
+        };
+        let addr = match &socket {
+            UnixSocket::File(path) => SocketAddr::from_pathname(path)?,
+            #[cfg(target_os = "linux")]
+            UnixSocket::Abstract(name) => SocketAddr::from_abstract_name(name.as_encoded_bytes())?,
+            UnixSocket::Dir(_) | UnixSocket::TmpDir(_) => unreachable!("resolved above"),
+        };
+        let listener = std::os::unix::net::UnixListener::bind_addr(&addr)?;
+        listener.set_nonblocking(true)?;
+        #[cfg(not(feature = "tokio"))]
+        let listener = Async::new(listener)?;
+        #[cfg(feature = "tokio")]
+        let listener = UnixListener::from_std(listener)?;
+
+        Ok((Self::Unix(listener), socket))
+    }
+
+    /// Bind to the given TCP address, returning the address clients can connect to.
+    fn bind_tcp(tcp: &Tcp) -> Result<(Self, Tcp)> {
+        let nonce_file = tcp.nonce_file_path()?.map(NonceFile::create).transpose()?;
+
+        let listener = std::net::TcpListener::bind((tcp.host(), tcp.port()))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        #[cfg(not(feature = "tokio"))]
+        let listener = Async::new(listener)?;
+        #[cfg(feature = "tokio")]
+        let listener = TcpListener::from_std(listener)?;
+        let tcp = Tcp::new(tcp.host(), port)
+            .set_family(tcp.family())
+            .set_nonce_file(tcp.nonce_file().map(ToOwned::to_owned));
+
+        Ok((Self::Tcp(listener, nonce_file), tcp))
+    }
+
+    /// Accept the next client connection.
+    async fn accept(&self) -> Result<connection::Builder<'static>> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                #[cfg(not(feature = "tokio"))]
+                let stream = stream.into_inner()?;
+
+                Ok(connection::Builder::unix_stream(stream))
+            }
+            Self::Tcp(listener, nonce_file) => {
+                let (stream, _) = listener.accept().await?;
+                #[cfg(not(feature = "tokio"))]
+                let stream = stream.into_inner()?;
+                let builder = connection::Builder::tcp_stream(stream);
+
+                Ok(match nonce_file {
+                    Some(nonce_file) => builder.nonce_file(nonce_file),
+                    None => builder,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{
+        StreamExt,
+        future::{Either, select},
+    };
+    use ntest::timeout;
+    use std::pin::pin;
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        fdo::{DBusProxy, ReleaseNameReply, RequestNameFlags, RequestNameReply},
+        interface,
+        object_server::SignalEmitter,
+        proxy,
+        utils::block_on,
+    };
+
+    struct Greeter;
+
+    #[interface(name = "org.zbus.BusTest")]
+    impl Greeter {
+        fn hello(&self, name: &str) -> String {
+            format!("Hello {name}!")
+        }
+
+        #[zbus(signal)]
+        async fn greeted(emitter: &SignalEmitter<'_>, name: &str) -> crate::Result<()>;
+    }
+
+    #[proxy(
+        interface = "org.zbus.BusTest",
+        default_service = "org.zbus.BusTest",
+        default_path = "/org/zbus/BusTest"
+    )]
+    trait BusTest {
+        fn hello(&self, name: &str) -> crate::Result<String>;
+
+        #[zbus(signal)]
+        fn greeted(&self, name: &str) -> crate::Result<()>;
+    }
+
+    // Run `bus` while `test` runs.
+    async fn with_bus<F>(bus: Bus, test: F)
+    where
+        F: std::future::Future<Output = Result<()>>,
+    {
+        match select(pin!(bus.run()), pin!(test)).await {
+            Either::Left((res, _)) => panic!("bus stopped unexpectedly: {res:?}"),
+            Either::Right((res, _)) => res.unwrap(),
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn unix_bus() {
+        block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let bus = Bus::for_address(format!("unix:dir={}", dir.path().display()).as_str())
+                .await
+                .unwrap();
+            let address = bus.address().clone();
+            let path = match address.transport() {
+                Transport::Unix(unix) => match unix.path() {
+                    UnixSocket::File(path) => path.clone(),
+                    _ => panic!("unexpected unix socket"),
+                },
+                _ => panic!("unexpected transport"),
+            };
+            assert!(path.starts_with(dir.path()));
+            assert_eq!(address.guid().unwrap(), bus.guid().inner());
+
+            with_bus(bus, test_unix_bus(address)).await;
+
+            // The socket file is removed along with the bus.
+            assert!(!path.exists());
+        });
+    }
+
+    async fn test_unix_bus(address: Address) -> Result<()> {
+        let service = connection::Builder::address(address.clone())?
+            .serve_at("/org/zbus/BusTest", Greeter)?
+            .name("org.zbus.BusTest")?
+            .build()
+            .await?;
+        let client = connection::Builder::address(address)?.build().await?;
+        assert_ne!(service.unique_name(), client.unique_name());
+
+        let dbus = DBusProxy::new(&client).await?;
+        let owner = dbus.get_name_owner("org.zbus.BusTest".try_into()?).await?;
+        assert_eq!(Some(&owner), service.unique_name());
+        let names = dbus.list_names().await?;
+        for name in [
+            "org.freedesktop.DBus",
+            "org.zbus.BusTest",
+            service.unique_name().unwrap(),
+            client.unique_name().unwrap(),
+        ] {
+            assert!(names.iter().any(|n| *n == name), "`{name}` not listed");
+        }
+
+        // Method calls.
+        let proxy = BusTestProxy::new(&client).await?;
+        assert_eq!(proxy.hello("zbus").await?, "Hello zbus!");
+
+        // Signals.
+        let mut greeted = proxy.receive_greeted().await?;
+        let iface = service
+            .object_server()
+            .interface::<_, Greeter>("/org/zbus/BusTest")
+            .await?;
+        Greeter::greeted(iface.signal_emitter(), "zbus").await?;
+        let signal = greeted.next().await.unwrap();
+        assert_eq!(signal.args()?.name, "zbus");
+        assert_eq!(
+            signal.message().header().sender(),
+            service.unique_name().map(|n| n.inner())
+        );
+
+        // Name ownership changes.
+        let mut owner_changed = dbus
+            .receive_name_owner_changed_with_args(&[(0, "org.zbus.BusTest")])
+            .await?;
+        assert!(service.release_name("org.zbus.BusTest").await?);
+        let signal = owner_changed.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(
+            args.old_owner.as_ref(),
+            service.unique_name().map(|n| n.inner())
+        );
+        assert!(args.new_owner.is_none());
+        assert!(!dbus.name_has_owner("org.zbus.BusTest".try_into()?).await?);
+
+        // Calls to names without owners get an error.
+        match proxy.hello("zbus").await {
+            Err(crate::Error::MethodError(name, _, _)) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.ServiceUnknown")
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+
+        // Peers disconnecting are announced.
+        let service_name = service.unique_name().unwrap().clone();
+        let mut owner_changed = dbus
+            .receive_name_owner_changed_with_args(&[(0, service_name.as_str())])
+            .await?;
+        drop(iface);
+        drop(service);
+        let signal = owner_changed.next().await.unwrap();
+        assert!(signal.args()?.new_owner.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn tcp_bus() {
+        block_on(async {
+            let bus = Bus::for_address("tcp:host=127.0.0.1,port=0").await.unwrap();
+            let address = bus.address().clone();
+            match address.transport() {
+                Transport::Tcp(tcp) => assert_ne!(tcp.port(), 0),
+                _ => panic!("unexpected transport"),
+            }
+            let guid = bus.guid().clone();
+
+            with_bus(bus, async move {
+                let conn = connection::Builder::address(address)?.build().await?;
+                let dbus = DBusProxy::new(&conn).await?;
+                assert_eq!(dbus.get_id().await?, guid);
+
+                Ok(())
+            })
+            .await;
+        });
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn nonce_tcp_bus() {
+        block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let nonce_path = dir.path().join("nonce");
+            let tcp = Tcp::new("127.0.0.1", 0)
+                .set_nonce_file(Some(nonce_path.as_os_str().as_encoded_bytes().to_vec()));
+            let bus = Bus::for_address(Address::new(Transport::Tcp(tcp)))
+                .await
+                .unwrap();
+            let address = bus.address().clone();
+            let port = match address.transport() {
+                Transport::Tcp(tcp) => {
+                    assert_eq!(tcp.nonce_file_path().unwrap(), Some(nonce_path.as_path()));
+                    tcp.port()
+                }
+                _ => panic!("unexpected transport"),
+            };
+            assert_eq!(std::fs::read(&nonce_path).unwrap().len(), 16);
+
+            // A client with a different nonce.
+            let wrong_nonce = NonceFile::create(dir.path().join("wrong-nonce")).unwrap();
+            let tcp = Tcp::new("127.0.0.1", port).set_nonce_file(Some(
+                wrong_nonce.path().as_os_str().as_encoded_bytes().to_vec(),
+            ));
+            let wrong_address = Address::new(Transport::Tcp(tcp));
+
+            with_bus(bus, async move {
+                let conn = connection::Builder::address(address)?.build().await?;
+                DBusProxy::new(&conn).await?.get_id().await?;
+
+                connection::Builder::address(wrong_address)?
+                    .build()
+                    .await
+                    .unwrap_err();
+
+                Ok(())
+            })
+            .await;
+            // The nonce file is removed along with the bus.
+            assert!(!nonce_path.exists());
+        });
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn name_queue() {
+        block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let bus = Bus::for_address(format!("unix:dir={}", dir.path().display()).as_str())
+                .await
+                .unwrap();
+            let address = bus.address().clone();
+
+            with_bus(bus, test_name_queue(address)).await;
+        });
+    }
+
+    async fn test_name_queue(address: Address) -> Result<()> {
+        let name = "org.zbus.QueuedName";
+        let conn1 = connection::Builder::address(address.clone())?
+            .build()
+            .await?;
+        let conn2 = connection::Builder::address(address)?.build().await?;
+        let dbus1 = DBusProxy::new(&conn1).await?;
+        let dbus2 = DBusProxy::new(&conn2).await?;
+        let mut lost = dbus1.receive_name_lost().await?;
+        let mut acquired = dbus1.receive_name_acquired_with_args(&[(0, name)]).await?;
+
+        let reply = dbus1
+            .request_name(name.try_into()?, RequestNameFlags::AllowReplacement.into())
+            .await?;
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        let reply = dbus1
+            .request_name(name.try_into()?, RequestNameFlags::AllowReplacement.into())
+            .await?;
+        assert_eq!(reply, RequestNameReply::AlreadyOwner);
+        acquired.next().await.unwrap();
+
+        // Replacing the owner puts it in the queue.
+        let reply = dbus2
+            .request_name(name.try_into()?, RequestNameFlags::ReplaceExisting.into())
+            .await?;
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        assert_eq!(lost.next().await.unwrap().args()?.name, name);
+        let owners = dbus2.list_queued_owners(name.try_into()?).await?;
+        let owners: Vec<_> = owners.iter().map(|o| o.as_str()).collect();
+        assert_eq!(
+            owners,
+            [
+                conn2.unique_name().unwrap().as_str(),
+                conn1.unique_name().unwrap().as_str()
+            ]
+        );
+
+        // Without `AllowReplacement`, the new owner can't be replaced.
+        let reply = dbus1
+            .request_name(
+                name.try_into()?,
+                RequestNameFlags::ReplaceExisting | RequestNameFlags::DoNotQueue,
+            )
+            .await?;
+        assert_eq!(reply, RequestNameReply::Exists);
+        assert_eq!(dbus1.list_queued_owners(name.try_into()?).await?.len(), 1);
+        let reply = dbus1
+            .request_name(name.try_into()?, RequestNameFlags::ReplaceExisting.into())
+            .await?;
+        assert_eq!(reply, RequestNameReply::InQueue);
+
+        // Releasing the name hands it over to the next in the queue.
+        assert_eq!(
+            dbus2.release_name(name.try_into()?).await?,
+            ReleaseNameReply::Released
+        );
+        acquired.next().await.unwrap();
+        assert_eq!(
+            dbus1.get_name_owner(name.try_into()?).await?.as_str(),
+            conn1.unique_name().unwrap().as_str()
+        );
+        assert_eq!(
+            dbus2.release_name(name.try_into()?).await?,
+            ReleaseNameReply::NotOwner
+        );
+        assert_eq!(
+            dbus2
+                .release_name("org.zbus.NoSuchName".try_into()?)
+                .await?,
+            ReleaseNameReply::NonExistent
+        );
+
+        Ok(())
+    }
+}
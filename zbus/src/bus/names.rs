@@ -0,0 +1,194 @@
+use std::collections::{HashMap, VecDeque};
+
+use enumflags2::BitFlags;
+
+use crate::{
+    fdo::{ReleaseNameReply, RequestNameFlags, RequestNameReply},
+    names::{OwnedUniqueName, OwnedWellKnownName, UniqueName, WellKnownName},
+};
+
+/// The registry of well-known names on the bus, along with their owners and queues.
+#[derive(Debug, Default)]
+pub(super) struct NameRegistry {
+    names: HashMap<OwnedWellKnownName, NameEntry>,
+}
+
+#[derive(Debug)]
+struct NameEntry {
+    owner: NameOwner,
+    waiting_list: VecDeque<NameOwner>,
+}
+
+#[derive(Debug, Clone)]
+struct NameOwner {
+    unique_name: OwnedUniqueName,
+    allow_replacement: bool,
+    do_not_queue: bool,
+}
+
+impl NameOwner {
+    fn new(unique_name: OwnedUniqueName, flags: BitFlags<RequestNameFlags>) -> Self {
+        Self {
+            unique_name,
+            allow_replacement: flags.contains(RequestNameFlags::AllowReplacement),
+            do_not_queue: flags.contains(RequestNameFlags::DoNotQueue),
+        }
+    }
+}
+
+/// A change of the primary owner of a well-known name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct NameOwnerChange {
+    pub name: OwnedWellKnownName,
+    pub old_owner: Option<OwnedUniqueName>,
+    pub new_owner: Option<OwnedUniqueName>,
+}
+
+impl NameRegistry {
+    /// Handle a `RequestName` call from `requester`.
+    pub fn request_name(
+        &mut self,
+        name: OwnedWellKnownName,
+        requester: OwnedUniqueName,
+        flags: BitFlags<RequestNameFlags>,
+    ) -> (RequestNameReply, Option<NameOwnerChange>) {
+        let new_owner = NameOwner::new(requester, flags);
+        let Some(entry) = self.names.get_mut(&name) else {
+            let change = NameOwnerChange {
+                name: name.clone(),
+                old_owner: None,
+                new_owner: Some(new_owner.unique_name.clone()),
+            };
+            self.names.insert(
+                name,
+                NameEntry {
+                    owner: new_owner,
+                    waiting_list: VecDeque::new(),
+                },
+            );
+
+            return (RequestNameReply::PrimaryOwner, Some(change));
+        };
+
+        if entry.owner.unique_name == new_owner.unique_name {
+            entry.owner = new_owner;
+
+            return (RequestNameReply::AlreadyOwner, None);
+        }
+
+        if flags.contains(RequestNameFlags::ReplaceExisting) && entry.owner.allow_replacement {
+            entry
+                .waiting_list
+                .retain(|o| o.unique_name != new_owner.unique_name);
+            let change = NameOwnerChange {
+                name,
+                old_owner: Some(entry.owner.unique_name.clone()),
+                new_owner: Some(new_owner.unique_name.clone()),
+            };
+            let old_owner = std::mem::replace(&mut entry.owner, new_owner);
+            if !old_owner.do_not_queue {
+                entry.waiting_list.push_front(old_owner);
+            }
+
+            return (RequestNameReply::PrimaryOwner, Some(change));
+        }
+
+        let queued = entry
+            .waiting_list
+            .iter_mut()
+            .find(|o| o.unique_name == new_owner.unique_name);
+        if new_owner.do_not_queue {
+            if queued.is_some() {
+                entry
+                    .waiting_list
+                    .retain(|o| o.unique_name != new_owner.unique_name);
+            }
+
+            return (RequestNameReply::Exists, None);
+        }
+        match queued {
+            Some(queued) => *queued = new_owner,
+            None => entry.waiting_list.push_back(new_owner),
+        }
+
+        (RequestNameReply::InQueue, None)
+    }
+
+    /// Handle a `ReleaseName` call from `owner`.
+    pub fn release_name(
+        &mut self,
+        name: &WellKnownName<'_>,
+        owner: &UniqueName<'_>,
+    ) -> (ReleaseNameReply, Option<NameOwnerChange>) {
+        let Some(entry) = self.names.get_mut(name) else {
+            return (ReleaseNameReply::NonExistent, None);
+        };
+
+        if entry.owner.unique_name == *owner {
+            let name = OwnedWellKnownName::from(name.to_owned());
+            let new_owner = entry.waiting_list.pop_front();
+            let change = NameOwnerChange {
+                name: name.clone(),
+                old_owner: Some(entry.owner.unique_name.clone()),
+                new_owner: new_owner.as_ref().map(|o| o.unique_name.clone()),
+            };
+            match new_owner {
+                Some(new_owner) => entry.owner = new_owner,
+                None => {
+                    self.names.remove(&name);
+                }
+            }
+
+            return (ReleaseNameReply::Released, Some(change));
+        }
+
+        let queue_len = entry.waiting_list.len();
+        entry.waiting_list.retain(|o| o.unique_name != *owner);
+        if entry.waiting_list.len() != queue_len {
+            (ReleaseNameReply::Released, None)
+        } else {
+            (ReleaseNameReply::NotOwner, None)
+        }
+    }
+
+    /// Release all names owned by, or queued for, `unique_name`.
+    pub fn release_all(&mut self, unique_name: &UniqueName<'_>) -> Vec<NameOwnerChange> {
+        let names: Vec<_> = self
+            .names
+            .iter()
+            .filter(|(_, entry)| {
+                entry.owner.unique_name == *unique_name
+                    || entry
+                        .waiting_list
+                        .iter()
+                        .any(|o| o.unique_name == *unique_name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        names
+            .into_iter()
+            .filter_map(|name| self.release_name(&name, unique_name).1)
+            .collect()
+    }
+
+    /// The primary owner of `name`, if any.
+    pub fn owner(&self, name: &WellKnownName<'_>) -> Option<&OwnedUniqueName> {
+        self.names.get(name).map(|entry| &entry.owner.unique_name)
+    }
+
+    /// The primary owner of `name`, followed by all the connections queued for it.
+    pub fn queued_owners(&self, name: &WellKnownName<'_>) -> Option<Vec<OwnedUniqueName>> {
+        self.names.get(name).map(|entry| {
+            std::iter::once(&entry.owner)
+                .chain(entry.waiting_list.iter())
+                .map(|o| o.unique_name.clone())
+                .collect()
+        })
+    }
+
+    /// All the currently owned well-known names.
+    pub fn names(&self) -> impl Iterator<Item = &OwnedWellKnownName> {
+        self.names.keys()
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+
+use enumflags2::BitFlags;
+use futures_lite::StreamExt;
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    Connection, DBusError, Message, MessageStream, OwnedGuid, OwnedMatchRule, Result,
+    async_lock::Mutex,
+    fdo::{self, ConnectionCredentials, RequestNameFlags},
+    message::{self, Flags, Type},
+    names::{BusName, OwnedUniqueName, UniqueName, WellKnownName},
+};
+
+use super::names::{NameOwnerChange, NameRegistry};
+
+pub(super) const BUS_NAME: &str = "org.freedesktop.DBus";
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+const BUS_INTERFACE: &str = "org.freedesktop.DBus";
+const PEER_INTERFACE: &str = "org.freedesktop.DBus.Peer";
+
+/// The peers connected to the bus and the names they own.
+#[derive(Debug)]
+pub(super) struct Peers {
+    guid: OwnedGuid,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    peers: HashMap<OwnedUniqueName, Peer>,
+    names: NameRegistry,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+struct Peer {
+    conn: Connection,
+    match_rules: Vec<OwnedMatchRule>,
+}
+
+/// Messages to be sent out, once the state lock has been released.
+type Outgoing = Vec<(Connection, Message)>;
+
+impl Peers {
+    pub fn new(guid: OwnedGuid) -> Self {
+        Self {
+            guid,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Reserve a new unique name for a connecting peer.
+    pub async fn next_unique_name(&self) -> OwnedUniqueName {
+        let mut state = self.state.lock().await;
+        state.next_id += 1;
+        let name = format!(":1.{}", state.next_id);
+
+        UniqueName::from_string_unchecked(name).into()
+    }
+
+    /// Serve the peer connection behind `stream` until it disconnects.
+    #[instrument(name = "bus peer", skip(self, stream))]
+    pub async fn serve(&self, mut stream: MessageStream, unique_name: OwnedUniqueName) {
+        let conn = Connection::from(&stream);
+
+        // The first message must be a `Hello` call.
+        match stream.next().await {
+            Some(Ok(msg)) if is_hello(&msg) => {
+                if let Err(e) = self.add_peer(conn, &unique_name, &msg).await {
+                    debug!("Failed to register peer: {e}");
+
+                    return;
+                }
+            }
+            Some(Ok(_)) => {
+                debug!("Peer sent a message other than `Hello` first, disconnecting");
+
+                return;
+            }
+            Some(Err(e)) => {
+                debug!("Error reading from peer: {e}");
+
+                return;
+            }
+            None => return,
+        }
+
+        while let Some(msg) = stream.next().await {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(e) => {
+                    trace!("Peer connection closed: {e}");
+
+                    break;
+                }
+            };
+            if let Err(e) = self.route(&msg, &unique_name).await {
+                debug!("Failed to route message: {e}");
+            }
+        }
+
+        self.remove_peer(&unique_name).await;
+    }
+
+    async fn add_peer(
+        &self,
+        conn: Connection,
+        unique_name: &OwnedUniqueName,
+        hello: &Message,
+    ) -> Result<()> {
+        // The reply to `Hello` must be the very first message the peer receives.
+        let reply = Message::method_return(&hello.header())?
+            .sender(BUS_NAME)?
+            .build(unique_name)?;
+        conn.send(&reply).await?;
+
+        let mut state = self.state.lock().await;
+        state.peers.insert(
+            unique_name.clone(),
+            Peer {
+                conn: conn.clone(),
+                match_rules: vec![],
+            },
+        );
+        let mut outgoing = vec![(conn, name_acquired(unique_name, unique_name)?)];
+        state.name_owner_changed(&mut outgoing, unique_name.as_str(), None, Some(unique_name))?;
+        drop(state);
+
+        send_all(outgoing).await;
+
+        Ok(())
+    }
+
+    async fn remove_peer(&self, unique_name: &OwnedUniqueName) {
+        let mut state = self.state.lock().await;
+        if state.peers.remove(unique_name).is_none() {
+            return;
+        }
+        let mut outgoing = vec![];
+        let changes = state.names.release_all(unique_name);
+        let res = state
+            .name_owner_changes(&mut outgoing, changes)
+            .and_then(|_| {
+                state.name_owner_changed(
+                    &mut outgoing,
+                    unique_name.as_str(),
+                    Some(unique_name),
+                    None,
+                )
+            });
+        drop(state);
+        if let Err(e) = res {
+            debug!("Failed to create name owner change signals: {e}");
+        }
+
+        send_all(outgoing).await;
+    }
+
+    /// Route a message from `sender` to its destination(s).
+    async fn route(&self, msg: &Message, sender: &OwnedUniqueName) -> Result<()> {
+        let hdr = msg.header();
+        // Never trust the sender field set by the peer.
+        let msg = if hdr.sender().is_some_and(|s| *s == *sender) {
+            msg.clone()
+        } else {
+            with_sender(msg, sender)?
+        };
+
+        let destination = hdr.destination();
+        let outgoing = match destination {
+            Some(dest) if *dest == BUS_NAME => {
+                if msg.message_type() == Type::MethodCall {
+                    self.call_bus_method(&msg, sender).await?
+                } else {
+                    vec![]
+                }
+            }
+            Some(dest) => {
+                let state = self.state.lock().await;
+                match state.resolve(dest).and_then(|name| state.peers.get(&name)) {
+                    Some(peer) => vec![(peer.conn.clone(), msg)],
+                    None if expects_reply(&msg) => {
+                        let err = fdo::Error::ServiceUnknown(format!(
+                            "The name {dest} was not provided by any .service files"
+                        ));
+                        match state.peers.get(sender) {
+                            Some(peer) => vec![(peer.conn.clone(), error_reply(&msg, &err)?)],
+                            None => vec![],
+                        }
+                    }
+                    None => vec![],
+                }
+            }
+            None => {
+                let state = self.state.lock().await;
+                state
+                    .matching_peers(&msg)
+                    .map(|conn| (conn, msg.clone()))
+                    .collect()
+            }
+        };
+
+        send_all(outgoing).await;
+
+        Ok(())
+    }
+
+    async fn call_bus_method(&self, msg: &Message, sender: &OwnedUniqueName) -> Result<Outgoing> {
+        let mut outgoing = vec![];
+        let reply = match self.handle_bus_method(msg, sender, &mut outgoing).await {
+            Ok(reply) => reply,
+            Err(e) => error_reply(msg, &e)?,
+        };
+        if expects_reply(msg) {
+            let state = self.state.lock().await;
+            if let Some(peer) = state.peers.get(sender) {
+                outgoing.push((peer.conn.clone(), reply));
+            }
+        }
+
+        Ok(outgoing)
+    }
+
+    /// Handle a method call to the bus itself, returning the reply.
+    ///
+    /// Any signals resulting from the call are added to `outgoing`.
+    async fn handle_bus_method(
+        &self,
+        msg: &Message,
+        sender: &OwnedUniqueName,
+        outgoing: &mut Outgoing,
+    ) -> fdo::Result<Message> {
+        let hdr = msg.header();
+        let body = msg.body();
+        let member = hdr.member().map(|m| m.as_str()).unwrap_or_default();
+        match hdr.interface().map(|i| i.as_str()) {
+            None | Some(BUS_INTERFACE) => (),
+            Some(PEER_INTERFACE) if member == "Ping" => return Ok(method_reply(msg, &())?),
+            Some(iface) => {
+                return Err(fdo::Error::UnknownInterface(format!(
+                    "Unknown interface `{iface}`"
+                )));
+            }
+        }
+
+        let reply = match member {
+            "Hello" => {
+                return Err(fdo::Error::Failed(
+                    "Already handled an Hello message".to_string(),
+                ));
+            }
+            "RequestName" => {
+                let (name, flags): (WellKnownName<'_>, BitFlags<RequestNameFlags>) =
+                    body.deserialize().map_err(invalid_args)?;
+                if name == BUS_NAME {
+                    return Err(fdo::Error::InvalidArgs(format!(
+                        "Connection is not allowed to own the service `{name}`"
+                    )));
+                }
+                let mut state = self.state.lock().await;
+                let (reply, change) =
+                    state
+                        .names
+                        .request_name(name.to_owned().into(), sender.clone(), flags);
+                state.name_owner_changes(outgoing, change)?;
+
+                method_reply(msg, &reply)?
+            }
+            "ReleaseName" => {
+                let name: WellKnownName<'_> = body.deserialize().map_err(invalid_args)?;
+                let mut state = self.state.lock().await;
+                let (reply, change) = state.names.release_name(&name, sender);
+                state.name_owner_changes(outgoing, change)?;
+
+                method_reply(msg, &reply)?
+            }
+            "ListQueuedOwners" => {
+                let name: WellKnownName<'_> = body.deserialize().map_err(invalid_args)?;
+                let state = self.state.lock().await;
+                let owners = state
+                    .names
+                    .queued_owners(&name)
+                    .ok_or_else(|| name_has_no_owner(&name))?;
+
+                method_reply(msg, &owners)?
+            }
+            "AddMatch" => {
+                let rule: &str = body.deserialize().map_err(invalid_args)?;
+                let rule = OwnedMatchRule::try_from(rule)
+                    .map_err(|e| fdo::Error::MatchRuleInvalid(e.to_string()))?;
+                let mut state = self.state.lock().await;
+                if let Some(peer) = state.peers.get_mut(sender) {
+                    peer.match_rules.push(rule);
+                }
+
+                method_reply(msg, &())?
+            }
+            "RemoveMatch" => {
+                let rule: &str = body.deserialize().map_err(invalid_args)?;
+                let rule = OwnedMatchRule::try_from(rule)
+                    .map_err(|e| fdo::Error::MatchRuleInvalid(e.to_string()))?;
+                let mut state = self.state.lock().await;
+                let rules = state
+                    .peers
+                    .get_mut(sender)
+                    .map(|peer| &mut peer.match_rules);
+                match rules.and_then(|rules| Some((rules.iter().position(|r| *r == rule)?, rules)))
+                {
+                    Some((pos, rules)) => {
+                        rules.remove(pos);
+                    }
+                    None => {
+                        return Err(fdo::Error::MatchRuleNotFound(
+                            "The given match rule wasn't found and can't be removed".to_string(),
+                        ));
+                    }
+                }
+
+                method_reply(msg, &())?
+            }
+            "GetNameOwner" => {
+                let name: BusName<'_> = body.deserialize().map_err(invalid_args)?;
+                if name == BUS_NAME {
+                    method_reply(msg, &BUS_NAME)?
+                } else {
+                    let state = self.state.lock().await;
+                    let owner = state
+                        .resolve(&name)
+                        .ok_or_else(|| name_has_no_owner(&name))?;
+
+                    method_reply(msg, &owner)?
+                }
+            }
+            "NameHasOwner" => {
+                let name: BusName<'_> = body.deserialize().map_err(invalid_args)?;
+                let has_owner =
+                    name == BUS_NAME || self.state.lock().await.resolve(&name).is_some();
+
+                method_reply(msg, &has_owner)?
+            }
+            "ListNames" => {
+                let state = self.state.lock().await;
+                let names: Vec<&str> = std::iter::once(BUS_NAME)
+                    .chain(state.peers.keys().map(|n| n.as_str()))
+                    .chain(state.names.names().map(|n| n.as_str()))
+                    .collect();
+
+                method_reply(msg, &names)?
+            }
+            "ListActivatableNames" => method_reply(msg, &[BUS_NAME])?,
+            "GetId" => method_reply(msg, &self.guid)?,
+            "GetConnectionUnixUser" => {
+                let creds = self.peer_credentials(msg).await?;
+                let uid = creds.unix_user_id().ok_or_else(|| {
+                    fdo::Error::Failed("Unable to determine the Unix user ID".to_string())
+                })?;
+
+                method_reply(msg, &uid)?
+            }
+            "GetConnectionUnixProcessID" => {
+                let creds = self.peer_credentials(msg).await?;
+                let pid = creds.process_id().ok_or_else(|| {
+                    fdo::Error::UnixProcessIdUnknown(
+                        "Unable to determine the process ID".to_string(),
+                    )
+                })?;
+
+                method_reply(msg, &pid)?
+            }
+            "GetConnectionCredentials" => {
+                let creds = self.peer_credentials(msg).await?;
+
+                method_reply(msg, &creds)?
+            }
+            _ => {
+                return Err(fdo::Error::UnknownMethod(format!(
+                    "Unknown method `{member}`"
+                )));
+            }
+        };
+
+        Ok(reply)
+    }
+
+    /// The credentials of the peer named in the body of `msg`.
+    async fn peer_credentials(&self, msg: &Message) -> fdo::Result<ConnectionCredentials> {
+        let body = msg.body();
+        let name: BusName<'_> = body.deserialize().map_err(invalid_args)?;
+        let conn = {
+            let state = self.state.lock().await;
+            state
+                .resolve(&name)
+                .and_then(|name| state.peers.get(&name))
+                .map(|peer| peer.conn.clone())
+                .ok_or_else(|| name_has_no_owner(&name))?
+        };
+        let creds = conn
+            .peer_creds()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        // Only pass on the credentials that don't involve passing file descriptors.
+        Ok(ConnectionCredentials {
+            unix_user_id: creds.unix_user_id(),
+            unix_group_ids: creds.unix_group_ids().cloned(),
+            process_id: creds.process_id(),
+            windows_sid: creds.windows_sid().cloned(),
+            linux_security_label: creds.linux_security_label().cloned(),
+            ..Default::default()
+        })
+    }
+}
+
+impl State {
+    /// Resolve `name` to the unique name of a connected peer.
+    fn resolve(&self, name: &BusName<'_>) -> Option<OwnedUniqueName> {
+        match name {
+            BusName::Unique(name) => self
+                .peers
+                .contains_key(name)
+                .then(|| name.to_owned().into()),
+            BusName::WellKnown(name) => self.names.owner(name).cloned(),
+        }
+    }
+
+    /// The connections to all peers with a match rule matching `msg`.
+    fn matching_peers<'s>(&'s self, msg: &'s Message) -> impl Iterator<Item = Connection> + 's {
+        self.peers
+            .values()
+            .filter(move |peer| peer.match_rules.iter().any(|rule| self.matches(rule, msg)))
+            .map(|peer| peer.conn.clone())
+    }
+
+    fn matches(&self, rule: &OwnedMatchRule, msg: &Message) -> bool {
+        // `MatchRule::matches` can't resolve well-known names but we can.
+        if let Some(BusName::WellKnown(name)) = rule.sender() {
+            let hdr = msg.header();
+            match (self.names.owner(name), hdr.sender()) {
+                (Some(owner), Some(sender)) if *owner == *sender => (),
+                _ => return false,
+            }
+        }
+
+        rule.matches(msg).unwrap_or_else(|e| {
+            debug!("Error matching message against rule: {e}");
+
+            false
+        })
+    }
+
+    fn name_owner_changes(
+        &self,
+        outgoing: &mut Outgoing,
+        changes: impl IntoIterator<Item = NameOwnerChange>,
+    ) -> Result<()> {
+        for change in changes {
+            if let Some(old_owner) = &change.old_owner {
+                if let Some(peer) = self.peers.get(old_owner) {
+                    outgoing.push((peer.conn.clone(), name_lost(old_owner, &change.name)?));
+                }
+            }
+            if let Some(new_owner) = &change.new_owner {
+                if let Some(peer) = self.peers.get(new_owner) {
+                    outgoing.push((peer.conn.clone(), name_acquired(new_owner, &change.name)?));
+                }
+            }
+            self.name_owner_changed(
+                outgoing,
+                change.name.as_str(),
+                change.old_owner.as_ref(),
+                change.new_owner.as_ref(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn name_owner_changed(
+        &self,
+        outgoing: &mut Outgoing,
+        name: &str,
+        old_owner: Option<&OwnedUniqueName>,
+        new_owner: Option<&OwnedUniqueName>,
+    ) -> Result<()> {
+        let old_owner = old_owner.map(|n| n.as_str()).unwrap_or_default();
+        let new_owner = new_owner.map(|n| n.as_str()).unwrap_or_default();
+        let signal = Message::signal(BUS_PATH, BUS_INTERFACE, "NameOwnerChanged")?
+            .sender(BUS_NAME)?
+            .build(&(name, old_owner, new_owner))?;
+        outgoing.extend(
+            self.matching_peers(&signal)
+                .map(|conn| (conn, signal.clone()))
+                .collect::<Vec<_>>(),
+        );
+
+        Ok(())
+    }
+}
+
+async fn send_all(outgoing: Outgoing) {
+    for (conn, msg) in outgoing {
+        if let Err(e) = conn.send(&msg).await {
+            debug!("Failed to send message to peer: {e}");
+        }
+    }
+}
+
+fn is_hello(msg: &Message) -> bool {
+    let hdr = msg.header();
+
+    msg.message_type() == Type::MethodCall
+        && hdr.destination().map(|d| *d == BUS_NAME).unwrap_or(false)
+        && hdr.interface().map(|i| *i == BUS_INTERFACE).unwrap_or(true)
+        && hdr.member().map(|m| *m == "Hello").unwrap_or(false)
+}
+
+fn expects_reply(msg: &Message) -> bool {
+    msg.message_type() == Type::MethodCall
+        && !msg
+            .primary_header()
+            .flags()
+            .contains(Flags::NoReplyExpected)
+}
+
+/// Create a copy of `msg` with the sender field set to `sender`.
+fn with_sender(msg: &Message, sender: &UniqueName<'_>) -> Result<Message> {
+    let body = msg.body();
+    let builder = message::Builder::from(msg.header()).sender(sender)?;
+    #[cfg(unix)]
+    let fds = msg
+        .data()
+        .fds()
+        .iter()
+        .map(|fd| fd.try_to_owned().map(Into::into))
+        .collect::<zvariant::Result<Vec<_>>>()?;
+
+    // SAFETY: The body bytes and signature come from an already validated message.
+    unsafe {
+        builder.build_raw_body(
+            body.data().bytes(),
+            body.signature().clone(),
+            #[cfg(unix)]
+            fds,
+        )
+    }
+}
+
+fn method_reply<B>(call: &Message, body: &B) -> Result<Message>
+where
+    B: serde::ser::Serialize + zvariant::DynamicType,
+{
+    Message::method_return(&call.header())?
+        .sender(BUS_NAME)?
+        .build(body)
+}
+
+fn error_reply(call: &Message, err: &fdo::Error) -> Result<Message> {
+    Message::error(&call.header(), err.name())?
+        .sender(BUS_NAME)?
+        .build(&err.description().unwrap_or_default())
+}
+
+fn name_acquired(destination: &OwnedUniqueName, name: &str) -> Result<Message> {
+    Message::signal(BUS_PATH, BUS_INTERFACE, "NameAcquired")?
+        .sender(BUS_NAME)?
+        .destination(destination)?
+        .build(&name)
+}
+
+fn name_lost(destination: &OwnedUniqueName, name: &str) -> Result<Message> {
+    Message::signal(BUS_PATH, BUS_INTERFACE, "NameLost")?
+        .sender(BUS_NAME)?
+        .destination(destination)?
+        .build(&name)
+}
+
+fn invalid_args(e: crate::Error) -> fdo::Error {
+    fdo::Error::InvalidArgs(e.to_string())
+}
+
+fn name_has_no_owner(name: &str) -> fdo::Error {
+    fdo::Error::NameHasNoOwner(format!(
+        "Could not get owner of name '{name}': no such name"
+    ))
+}
@@ -8,6 +8,7 @@ use std::net::TcpStream;
 use std::os::unix::net::UnixStream;
 use std::{
     collections::{HashMap, HashSet},
+    sync::Arc,
     vec,
 };
 #[cfg(feature = "tokio")]
@@ -24,15 +25,24 @@ use vsock::VsockStream;
 use zvariant::ObjectPath;
 
 use crate::{
-    Connection, Error, Executor, Guid, OwnedGuid, Result,
+    Connection, Error, Executor, Guid, MessageStream, OwnedGuid, Result,
     address::{self, Address},
     fdo::RequestNameFlags,
     names::{InterfaceName, WellKnownName},
-    object_server::{ArcInterface, Interface},
+    object_server::{ArcInterface, Authorizer, Interface},
+};
+
+#[cfg(feature = "p2p")]
+use crate::{
+    address::transport::{NONCE_LEN, NonceFile},
+    utils::constant_time_eq,
 };
 
 use super::{
+    Interceptor, ReconnectPolicy,
     handshake::{AuthMechanism, Authenticated},
+    interceptor::Interceptors,
+    reconnect::Reconnect,
     socket::{BoxedSplit, ReadHalf, Split, WriteHalf},
 };
 
@@ -76,6 +86,9 @@ pub struct Builder<'a> {
     guid: Option<Guid<'a>>,
     #[cfg(feature = "p2p")]
     p2p: bool,
+    // The nonce `nonce-tcp` clients must send in the p2p server case.
+    #[cfg(feature = "p2p")]
+    nonce: Option<[u8; NONCE_LEN]>,
     internal_executor: bool,
     interfaces: Interfaces<'a>,
     names: HashSet<WellKnownName<'a>>,
@@ -85,6 +98,9 @@ pub struct Builder<'a> {
     request_name_flags: BitFlags<RequestNameFlags>,
     method_timeout: Option<std::time::Duration>,
     user_id: Option<u32>,
+    reconnect: Option<ReconnectPolicy>,
+    interceptors: Vec<Box<dyn Interceptor>>,
+    authorizer: Option<Arc<dyn Authorizer>>,
 }
 
 impl<'a> Builder<'a> {
@@ -291,6 +307,21 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Require the client to send the nonce from `nonce_file` before authenticating.
+    ///
+    /// This implements the server side of the `nonce-tcp:` transport and hence is only relevant
+    /// for server connections (see [`Builder::server`]). Building the connection fails if the
+    /// client sends a different nonce. [`Builder::build`] returns [`Error::Unsupported`] if this
+    /// is set on any other kind of connection.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    #[cfg(feature = "p2p")]
+    pub fn nonce_file(mut self, nonce_file: &NonceFile) -> Self {
+        self.nonce = Some(nonce_file.nonce());
+
+        self
+    }
+
     /// Set the capacity of the main (unfiltered) queue.
     ///
     /// Since typically you'd want to set this at instantiation time, you can set it through the
@@ -416,15 +447,58 @@ impl<'a> Builder<'a> {
 
     /// Set a timeout for method calls.
     ///
-    /// Method calls will return
-    /// `zbus::Error::InputOutput(std::io::Error(kind: ErrorKind::TimedOut))` if a client does not
-    /// receive an answer from a service in time.
+    /// Method calls will return [`Error::MethodTimeout`] if a client does not receive an answer
+    /// from a service in time. The timeout can be overridden per proxy and per method call (see
+    /// [`crate::proxy::Builder::method_timeout`] and [`crate::Proxy::call_with_timeout`]).
     pub fn method_timeout(mut self, timeout: std::time::Duration) -> Self {
         self.method_timeout = Some(timeout);
 
         self
     }
 
+    /// Automatically reconnect to the bus when the connection is lost.
+    ///
+    /// Reconnection attempts are made according to the given `policy`. After reconnection, the
+    /// well-known names requested through [`Connection::request_name`] (and friends) are requested
+    /// again, the match rules of the active signal subscriptions are added again and the objects in
+    /// the [`crate::ObjectServer`] keep being served. Note that the connection gets a new unique
+    /// name from the bus and that messages might have been missed in the meantime, so existing
+    /// [`MessageStream`]s yield an [`Error::Reconnected`] error, instead of ending.
+    ///
+    /// This is only supported for bus connections created from an address (e.g. through
+    /// [`Builder::session`], [`Builder::system`] or [`Builder::address`]). [`Builder::build`] will
+    /// return [`Error::Unsupported`] otherwise.
+    pub fn auto_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+
+        self
+    }
+
+    /// Register an interceptor for the messages sent and received on the connection.
+    ///
+    /// This can be called multiple times, in which case the interceptors are called in the order
+    /// of registration. See [`Interceptor`] for details.
+    pub fn interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: Interceptor,
+    {
+        self.interceptors.push(Box::new(interceptor));
+
+        self
+    }
+
+    /// Set the [`Authorizer`] for the method calls dispatched by the [`crate::ObjectServer`].
+    ///
+    /// See [`Authorizer`] for details.
+    pub fn authorizer<A>(mut self, authorizer: A) -> Self
+    where
+        A: Authorizer,
+    {
+        self.authorizer = Some(Arc::new(authorizer));
+
+        self
+    }
+
     /// Build the connection, consuming the builder.
     ///
     /// # Errors
@@ -432,24 +506,64 @@ impl<'a> Builder<'a> {
     /// Until server-side bus connection is supported, attempting to build such a connection will
     /// result in a [`Error::Unsupported`] error.
     pub async fn build(self) -> Result<Connection> {
+        self.build_with(false).await.map(|(conn, _)| conn)
+    }
+
+    /// Build the connection, consuming the builder, and return a stream of all its messages.
+    ///
+    /// Unlike creating a [`MessageStream`] after [`Builder::build`], this ensures that no message
+    /// is missed, including the ones the peer sends immediately after the handshake (e.g `Hello`).
+    #[cfg(feature = "bus-impl")]
+    pub(crate) async fn build_message_stream(self) -> Result<MessageStream> {
+        self.build_with(true)
+            .await
+            .map(|(_, stream)| stream.expect("message stream requested"))
+    }
+
+    async fn build_with(self, msg_stream: bool) -> Result<(Connection, Option<MessageStream>)> {
         let executor = Executor::new();
         #[cfg(not(feature = "tokio"))]
         let internal_executor = self.internal_executor;
         // Box the future as it's large and can cause stack overflow.
-        let conn = Box::pin(executor.run(self.build_(executor.clone()))).await?;
+        let ret = Box::pin(executor.run(self.build_(executor.clone(), msg_stream))).await?;
 
         #[cfg(not(feature = "tokio"))]
         start_internal_executor(&executor, internal_executor)?;
 
-        Ok(conn)
+        Ok(ret)
     }
 
-    async fn build_(mut self, executor: Executor<'static>) -> Result<Connection> {
+    async fn build_(
+        mut self,
+        executor: Executor<'static>,
+        msg_stream: bool,
+    ) -> Result<(Connection, Option<MessageStream>)> {
         #[cfg(feature = "p2p")]
         let is_bus_conn = !self.p2p;
         #[cfg(not(feature = "p2p"))]
         let is_bus_conn = true;
 
+        let reconnect = match (self.reconnect.take(), &self.target) {
+            (None, _) => None,
+            (Some(policy), Some(Target::Address(address))) if is_bus_conn => Some(Reconnect {
+                policy,
+                address: address.clone(),
+                auth_mechanism: self.auth_mechanism,
+                user_id: self.user_id,
+            }),
+            (Some(_), _) => return Err(Error::Unsupported),
+        };
+
+        // The nonce is only checked by servers going through the handshake.
+        #[cfg(feature = "p2p")]
+        if self.nonce.is_some()
+            && (!self.p2p
+                || self.guid.is_none()
+                || matches!(self.target, Some(Target::AuthenticatedSocket(_))))
+        {
+            return Err(Error::Unsupported);
+        }
+
         let mut auth = self.connect(is_bus_conn).await?;
 
         // SAFETY: `Authenticated` is always built with these fields set to `Some`.
@@ -458,9 +572,23 @@ impl<'a> Builder<'a> {
         #[cfg(unix)]
         let already_received_fds = auth.already_received_fds.drain(..).collect();
 
-        let mut conn = Connection::new(auth, is_bus_conn, executor, self.method_timeout).await?;
+        let mut conn = Connection::new(
+            auth,
+            is_bus_conn,
+            executor,
+            self.method_timeout,
+            reconnect,
+            Interceptors::new(self.interceptors),
+        )
+        .await?;
         conn.set_max_queued(self.max_queued.unwrap_or(DEFAULT_MAX_QUEUED));
 
+        if let Some(authorizer) = self.authorizer {
+            // Don't start the object server before the interfaces are added.
+            conn.ensure_object_server(self.interfaces.is_empty())
+                .set_arc_authorizer(authorizer);
+        }
+
         if !self.interfaces.is_empty() {
             let object_server = conn.ensure_object_server(false);
             for (path, interfaces) in self.interfaces {
@@ -481,6 +609,9 @@ impl<'a> Builder<'a> {
             listener.await;
         }
 
+        // Must be created before the socket reader is started to not miss any messages.
+        let msg_stream = msg_stream.then(|| MessageStream::from(&conn));
+
         // Start the socket reader task.
         conn.init_socket_reader(
             socket_read,
@@ -494,7 +625,7 @@ impl<'a> Builder<'a> {
                 .await?;
         }
 
-        Ok(conn)
+        Ok((conn, msg_stream))
     }
 
     fn new(target: Target) -> Self {
@@ -502,6 +633,8 @@ impl<'a> Builder<'a> {
             target: Some(target),
             #[cfg(feature = "p2p")]
             p2p: false,
+            #[cfg(feature = "p2p")]
+            nonce: None,
             max_queued: None,
             guid: None,
             internal_executor: true,
@@ -513,6 +646,9 @@ impl<'a> Builder<'a> {
             request_name_flags: BitFlags::default(),
             method_timeout: None,
             user_id: None,
+            reconnect: None,
+            interceptors: vec![],
+            authorizer: None,
         }
     }
 
@@ -557,6 +693,10 @@ impl<'a> Builder<'a> {
                         return Err(Error::Unsupported);
                     }
 
+                    if let Some(nonce) = self.nonce.take() {
+                        receive_nonce(stream.read_mut(), &nonce).await?;
+                    }
+
                     let creds = stream.read_mut().peer_credentials().await?;
                     #[cfg(unix)]
                     let client_uid = self.user_id.or_else(|| creds.unix_user_id());
@@ -609,18 +749,7 @@ impl<'a> Builder<'a> {
             Target::VsockStream(stream) => stream.into(),
             Target::Address(address) => {
                 guid = address.guid().map(|g| g.to_owned().into());
-                match address.connect().await? {
-                    #[cfg(any(unix, not(feature = "tokio")))]
-                    address::transport::Stream::Unix(stream) => stream.into(),
-                    #[cfg(unix)]
-                    address::transport::Stream::Unixexec(stream) => stream.into(),
-                    address::transport::Stream::Tcp(stream) => stream.into(),
-                    #[cfg(any(
-                        all(feature = "vsock", not(feature = "tokio")),
-                        feature = "tokio-vsock"
-                    ))]
-                    address::transport::Stream::Vsock(stream) => stream.into(),
-                }
+                connect_address(address).await?
             }
             Target::Socket(stream) => stream,
             Target::AuthenticatedSocket(stream) => {
@@ -634,6 +763,50 @@ impl<'a> Builder<'a> {
     }
 }
 
+/// Connect to the given `address`.
+pub(super) async fn connect_address(address: Address) -> Result<BoxedSplit> {
+    let split = match address.connect().await? {
+        #[cfg(any(unix, not(feature = "tokio")))]
+        address::transport::Stream::Unix(stream) => stream.into(),
+        #[cfg(unix)]
+        address::transport::Stream::Unixexec(stream) => stream.into(),
+        address::transport::Stream::Tcp(stream) => stream.into(),
+        #[cfg(any(
+            all(feature = "vsock", not(feature = "tokio")),
+            feature = "tokio-vsock"
+        ))]
+        address::transport::Stream::Vsock(stream) => stream.into(),
+    };
+
+    Ok(split)
+}
+
+/// Receive the nonce from a `nonce-tcp` client and check it against the `expected` one.
+#[cfg(feature = "p2p")]
+async fn receive_nonce(read: &mut dyn ReadHalf, expected: &[u8; NONCE_LEN]) -> Result<()> {
+    let mut nonce = [0; NONCE_LEN];
+    let mut received = 0;
+    // Only read the nonce itself, the rest belongs to the handshake.
+    while received < NONCE_LEN {
+        #[cfg(unix)]
+        let (len, _) = read.recvmsg(&mut nonce[received..]).await?;
+        #[cfg(not(unix))]
+        let len = read.recvmsg(&mut nonce[received..]).await?;
+        if len == 0 {
+            return Err(Error::Handshake(
+                "Client disconnected before sending the nonce".into(),
+            ));
+        }
+        received += len;
+    }
+
+    if !constant_time_eq(&nonce, expected) {
+        return Err(Error::Handshake("Client sent an invalid nonce".into()));
+    }
+
+    Ok(())
+}
+
 /// Start the internal executor thread.
 ///
 /// Returns a dummy task that keep the executor ticking thread from exiting due to absence of any
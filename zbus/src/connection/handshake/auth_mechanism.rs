@@ -4,11 +4,12 @@ use crate::{Error, Result};
 
 /// Authentication mechanisms
 ///
-/// Note that the `DBUS_COOKIE_SHA1` mechanism is not supported by zbus since version 5.0. The
-/// reasons are:
+/// Note that the `DBUS_COOKIE_SHA1` mechanism is only supported if the `cookie-sha1` cargo feature
+/// is enabled, as a fallback for [`AuthMechanism::External`]. It's not enabled by default since
+/// version 5.0 because:
 ///
 /// * It drags the `sha1` crate as a dependency, which can be [problematic for some users].
-/// * It makes the handshake more complex, now allowing use to pipeline all the commands.
+/// * It makes the handshake more complex, not allowing us to pipeline all the commands.
 /// * It's not widely used. If `EXTERNAL` is not an option, you might as well just use `ANONYMOUS`.
 ///
 /// See <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-mechanisms>
@@ -19,6 +20,11 @@ pub enum AuthMechanism {
     /// This is the recommended authentication mechanism on platforms where credentials can be
     /// transferred out-of-band, in particular Unix platforms that can perform credentials-passing
     /// over the `unix:` transport.
+    ///
+    /// With the `cookie-sha1` cargo feature, `DBUS_COOKIE_SHA1` is used where credentials can't be
+    /// checked, e.g. over the `tcp:` transport. Clients try it once `EXTERNAL` is rejected, and
+    /// servers accept it as well. That mechanism establishes that the client can read the cookies
+    /// in `~/.dbus-keyrings`, so the client and the server must run as the same user.
     External,
 
     /// Does not perform any authentication at all, and should not be accepted by message buses.
@@ -53,3 +59,50 @@ impl FromStr for AuthMechanism {
         }
     }
 }
+
+/// The mechanisms exchanged during the handshake.
+///
+/// Unlike [`AuthMechanism`], this includes the mechanisms that can't be chosen directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Mechanism {
+    External,
+    Anonymous,
+    Cookie,
+}
+
+impl Mechanism {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mechanism::External => "EXTERNAL",
+            Mechanism::Anonymous => "ANONYMOUS",
+            Mechanism::Cookie => "DBUS_COOKIE_SHA1",
+        }
+    }
+}
+
+impl From<AuthMechanism> for Mechanism {
+    fn from(mechanism: AuthMechanism) -> Self {
+        match mechanism {
+            AuthMechanism::External => Mechanism::External,
+            AuthMechanism::Anonymous => Mechanism::Anonymous,
+        }
+    }
+}
+
+impl fmt::Display for Mechanism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mech = self.as_str();
+        write!(f, "{mech}")
+    }
+}
+
+impl FromStr for Mechanism {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "DBUS_COOKIE_SHA1" => Ok(Mechanism::Cookie),
+            _ => AuthMechanism::from_str(s).map(Into::into),
+        }
+    }
+}
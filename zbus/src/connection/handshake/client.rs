@@ -3,9 +3,11 @@ use tracing::{instrument, trace, warn};
 
 use crate::{Message, conn::socket::ReadHalf, is_flatpak, names::OwnedUniqueName};
 
+#[cfg(feature = "cookie-sha1")]
+use super::cookies::{self, Keyring};
 use super::{
-    AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake, OwnedGuid, Result,
-    sasl_auth_id,
+    AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake, Mechanism,
+    OwnedGuid, Result, sasl_auth_id,
 };
 
 /// A representation of an in-progress handshake, client-side
@@ -18,6 +20,8 @@ pub struct Client {
     server_guid: Option<OwnedGuid>,
     bus: bool,
     user_id: Result<String>,
+    #[cfg(feature = "cookie-sha1")]
+    keyring: Option<Keyring>,
 }
 
 impl Client {
@@ -39,9 +43,17 @@ impl Client {
                 Some(value) => Ok(value.to_string()),
                 None => sasl_auth_id(),
             },
+            #[cfg(feature = "cookie-sha1")]
+            keyring: None,
         }
     }
 
+    /// Use `keyring` instead of the keyring of the current user.
+    #[cfg(all(feature = "cookie-sha1", test))]
+    pub(super) fn set_keyring(&mut self, keyring: Keyring) {
+        self.keyring = Some(keyring);
+    }
+
     fn set_guid(&mut self, guid: OwnedGuid) -> Result<()> {
         match &self.server_guid {
             Some(server_guid) if *server_guid != guid => {
@@ -83,34 +95,104 @@ impl Client {
 
     /// Perform the authentication handshake with the server.
     #[instrument(skip(self), level = "trace")]
+    // Only `DBUS_COOKIE_SHA1` involves more than one round trip.
+    #[cfg_attr(not(feature = "cookie-sha1"), allow(clippy::never_loop))]
     async fn authenticate(&mut self) -> Result<()> {
-        let mechanism = self.common.mechanism();
+        #[cfg_attr(not(feature = "cookie-sha1"), allow(unused_mut))]
+        let mut mechanism = Mechanism::from(self.common.mechanism());
+        self.write_auth(mechanism).await?;
+
+        loop {
+            match self.common.read_command().await? {
+                Command::Ok(guid) => {
+                    trace!("Received OK from server");
+                    self.set_guid(guid)?;
+
+                    return Ok(());
+                }
+                #[cfg(feature = "cookie-sha1")]
+                Command::Data(Some(challenge)) if mechanism == Mechanism::Cookie => {
+                    trace!("Received cookie challenge from server");
+                    let response = self.cookie_response(&challenge).await?;
+                    self.common
+                        .write_command(Command::Data(Some(response)))
+                        .await?;
+                }
+                // Fall back to the cookie where the credentials can't be checked.
+                #[cfg(feature = "cookie-sha1")]
+                Command::Rejected(accepted)
+                    if mechanism == Mechanism::External
+                        && accepted
+                            .split_ascii_whitespace()
+                            .any(|m| m == Mechanism::Cookie.as_str()) =>
+                {
+                    mechanism = Mechanism::Cookie;
+                    self.write_auth(mechanism).await?;
+                }
+                Command::Rejected(accepted) => {
+                    let list = accepted.replace(" ", ", ");
+                    return Err(Error::Handshake(format!(
+                        "{mechanism} rejected by the server. Accepted mechanisms: [{list}]"
+                    )));
+                }
+                Command::Error(e) => {
+                    return Err(Error::Handshake(format!("Received error from server: {e}")));
+                }
+                cmd => {
+                    return Err(Error::Handshake(format!(
+                        "Unexpected command from server: {cmd}"
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Request authentication through `mechanism`.
+    async fn write_auth(&mut self, mechanism: Mechanism) -> Result<()> {
         trace!("Trying {mechanism} mechanism");
-        let user_id = self.user_id.clone();
         let auth_cmd = match mechanism {
-            AuthMechanism::Anonymous => Command::Auth(Some(mechanism), Some("zbus".into())),
-            AuthMechanism::External => Command::Auth(Some(mechanism), Some(user_id?.into_bytes())),
+            Mechanism::Anonymous => Command::Auth(Some(mechanism), Some("zbus".into())),
+            Mechanism::External | Mechanism::Cookie => {
+                Command::Auth(Some(mechanism), Some(self.user_id.clone()?.into_bytes()))
+            }
+        };
+
+        self.common.write_command(auth_cmd).await
+    }
+
+    /// Compute the response to a `DBUS_COOKIE_SHA1` challenge from the server.
+    ///
+    /// The challenge is of the form `<context> <cookie ID> <server challenge>` and the response is
+    /// `<client challenge> <SHA-1 of "<server challenge>:<client challenge>:<cookie>">`.
+    #[cfg(feature = "cookie-sha1")]
+    async fn cookie_response(&self, challenge: &[u8]) -> Result<Vec<u8>> {
+        let challenge = std::str::from_utf8(challenge)
+            .map_err(|e| Error::Handshake(format!("Invalid cookie challenge: {e}")))?;
+        let mut fields = challenge.split_ascii_whitespace();
+        let (Some(context), Some(id), Some(server_challenge), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(Error::Handshake(format!(
+                "Malformed cookie challenge: `{challenge}`"
+            )));
+        };
+        let id = id
+            .parse()
+            .map_err(|e| Error::Handshake(format!("Invalid cookie ID: {e}")))?;
+
+        let keyring = match &self.keyring {
+            Some(keyring) => keyring.clone(),
+            None => Keyring::user()?,
         };
-        self.common.write_command(auth_cmd).await?;
+        let context = context.to_string();
+        let cookie =
+            crate::Task::spawn_blocking(move || keyring.lookup(&context, id), "DBus cookie lookup")
+                .await??;
 
-        match self.common.read_command().await? {
-            Command::Ok(guid) => {
-                trace!("Received OK from server");
-                self.set_guid(guid)?;
+        let client_challenge = cookies::random_hex();
+        let hash = cookie.hash(server_challenge, &client_challenge);
 
-                Ok(())
-            }
-            Command::Rejected(accepted) => {
-                let list = accepted.replace(" ", ", ");
-                Err(Error::Handshake(format!(
-                    "{mechanism} rejected by the server. Accepted mechanisms: [{list}]"
-                )))
-            }
-            Command::Error(e) => Err(Error::Handshake(format!("Received error from server: {e}"))),
-            cmd => Err(Error::Handshake(format!(
-                "Unexpected command from server: {cmd}"
-            ))),
-        }
+        Ok(format!("{client_challenge} {hash}").into_bytes())
     }
 
     /// Sends out all commands after authentication.
@@ -1,6 +1,7 @@
 use std::{borrow::Cow, fmt, str::FromStr};
 
-use crate::{Error, Guid, OwnedGuid, Result, conn::AuthMechanism};
+use super::Mechanism;
+use crate::{Error, Guid, OwnedGuid, Result};
 
 // The plain-text SASL profile authentication protocol described here:
 // <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-protocol>
@@ -9,7 +10,7 @@ use crate::{Error, Guid, OwnedGuid, Result, conn::AuthMechanism};
 #[derive(Debug)]
 #[allow(clippy::upper_case_acronyms)]
 pub(super) enum Command {
-    Auth(Option<AuthMechanism>, Option<Vec<u8>>),
+    Auth(Option<Mechanism>, Option<Vec<u8>>),
     Cancel,
     Begin,
     Data(Option<Vec<u8>>),
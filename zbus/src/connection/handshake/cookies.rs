@@ -0,0 +1,377 @@
+//! Keyring handling for the `DBUS_COOKIE_SHA1` authentication mechanism.
+//!
+//! See <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-mechanisms-sha>
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use sha1::{Digest, Sha1};
+use tracing::{debug, trace};
+
+use crate::{Error, Result};
+
+/// The cookie context used by the server side.
+pub(super) const DEFAULT_CONTEXT: &str = "org_freedesktop_general";
+
+// The following timeouts (in seconds) are the ones used by the reference implementation.
+//
+// A new cookie is created if none is more recent than this.
+const NEW_COOKIE_TIMEOUT: u64 = 60 * 5;
+// Cookies older than this are removed from the keyring.
+const EXPIRE_COOKIE_TIMEOUT: u64 = NEW_COOKIE_TIMEOUT + 60 * 2;
+// Cookies created further than this in the future are removed from the keyring as well.
+const MAX_TIME_TRAVEL: u64 = 60 * 5;
+
+const LOCK_ATTEMPTS: u32 = 32;
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A cookie from a keyring context.
+#[derive(Clone, PartialEq, Eq)]
+pub(super) struct Cookie {
+    id: u32,
+    created: u64,
+    cookie: String,
+}
+
+impl Cookie {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The hex-encoded SHA-1 hash of the challenges and this cookie.
+    pub fn hash(&self, server_challenge: &str, client_challenge: &str) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(format!(
+            "{server_challenge}:{client_challenge}:{}",
+            self.cookie
+        ));
+
+        hex::encode(hasher.finalize())
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_ascii_whitespace();
+        let id = fields.next()?.parse().ok()?;
+        let created = fields.next()?.parse().ok()?;
+        let cookie = fields.next()?.to_string();
+        if fields.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            created,
+            cookie,
+        })
+    }
+}
+
+impl fmt::Debug for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Keep the secret out of the logs.
+        f.debug_struct("Cookie")
+            .field("id", &self.id)
+            .field("created", &self.created)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A directory of cookie contexts, typically `~/.dbus-keyrings`.
+#[derive(Debug, Clone)]
+pub(super) struct Keyring {
+    dir: PathBuf,
+}
+
+impl Keyring {
+    /// The keyring of the current user.
+    pub fn user() -> Result<Self> {
+        let home = xdg_home::home_dir()
+            .ok_or_else(|| Error::Handshake("Failed to determine home directory".into()))?;
+
+        Ok(Self::new(home.join(".dbus-keyrings")))
+    }
+
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Look up the cookie with ID `id` in `context`.
+    pub fn lookup(&self, context: &str, id: u32) -> Result<Cookie> {
+        let path = self.context_path(context)?;
+        self.check_permissions()?;
+
+        read_cookies(&path)?
+            .into_iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| Error::Handshake(format!("DBus cookie ID {id} not found")))
+    }
+
+    /// A recent cookie from `context`, to be used by the server side.
+    ///
+    /// Expired cookies are removed from the context and a new cookie is added to it, if none is
+    /// recent enough.
+    pub fn server_cookie(&self, context: &str) -> Result<Cookie> {
+        let path = self.context_path(context)?;
+        self.create_dir()?;
+        let _lock = LockFile::acquire(self.dir.join(format!("{context}.lock")))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::Handshake(format!("Invalid system time: {e}")))?
+            .as_secs();
+        let mut cookies = read_cookies(&path)?;
+        let n_cookies = cookies.len();
+        cookies.retain(|c| {
+            now.saturating_sub(c.created) <= EXPIRE_COOKIE_TIMEOUT
+                && c.created.saturating_sub(now) <= MAX_TIME_TRAVEL
+        });
+        let mut changed = cookies.len() != n_cookies;
+
+        let recent = cookies
+            .iter()
+            .filter(|c| now.saturating_sub(c.created) < NEW_COOKIE_TIMEOUT)
+            .max_by_key(|c| c.created)
+            .cloned();
+        let cookie = match recent {
+            Some(cookie) => cookie,
+            None => {
+                let id = cookies.iter().map(|c| c.id).max().unwrap_or(0) + 1;
+                let cookie = Cookie {
+                    id,
+                    created: now,
+                    cookie: format!("{}{}", random_hex(), random_hex()),
+                };
+                trace!("Created new DBus cookie {id} in context `{context}`");
+                cookies.push(cookie.clone());
+                changed = true;
+
+                cookie
+            }
+        };
+
+        if changed {
+            write_cookies(&path, &cookies)?;
+        }
+
+        Ok(cookie)
+    }
+
+    fn context_path(&self, context: &str) -> Result<PathBuf> {
+        if context.is_empty() {
+            return Err(Error::Handshake("Empty DBus cookie context".into()));
+        }
+        if !context.is_ascii()
+            || context.contains(['/', '\\', '.'])
+            || context.contains(|c: char| c.is_ascii_whitespace() || c.is_ascii_control())
+        {
+            return Err(Error::Handshake(format!(
+                "Invalid DBus cookie context `{context}`"
+            )));
+        }
+
+        Ok(self.dir.join(context))
+    }
+
+    fn create_dir(&self) -> Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+
+            builder.mode(0o700);
+        }
+        builder
+            .create(&self.dir)
+            .map_err(|e| keyring_error(&self.dir, e))?;
+
+        self.check_permissions()
+    }
+
+    fn check_permissions(&self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let metadata = fs::metadata(&self.dir).map_err(|e| keyring_error(&self.dir, e))?;
+            if metadata.permissions().mode() & 0o077 != 0 {
+                return Err(Error::Handshake(format!(
+                    "DBus keyring `{}` is accessible by other users",
+                    self.dir.display()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A random (v4) UUID, hex-encoded without hyphens.
+pub(super) fn random_hex() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+fn read_cookies(path: &Path) -> Result<Vec<Cookie>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(keyring_error(path, e)),
+    };
+
+    let cookies = content
+        .lines()
+        .filter_map(|line| {
+            let cookie = Cookie::parse(line);
+            if cookie.is_none() {
+                debug!(
+                    "Ignoring malformed line in DBus keyring `{}`",
+                    path.display()
+                );
+            }
+
+            cookie
+        })
+        .collect();
+    trace!("Loaded DBus keyring `{}`: {cookies:?}", path.display());
+
+    Ok(cookies)
+}
+
+fn write_cookies(path: &Path, cookies: &[Cookie]) -> Result<()> {
+    // Write to a temporary file first, so readers never see a partially written context.
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}", random_hex()));
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+    let write = || {
+        let mut file = options.open(&tmp_path)?;
+        for c in cookies {
+            writeln!(file, "{} {} {}", c.id, c.created, c.cookie)?;
+        }
+        file.sync_all()?;
+
+        fs::rename(&tmp_path, path)
+    };
+
+    write().map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+
+        keyring_error(path, e)
+    })
+}
+
+fn keyring_error(path: &Path, e: io::Error) -> Error {
+    Error::Handshake(format!(
+        "Failed to access DBus keyring `{}`: {e}",
+        path.display()
+    ))
+}
+
+/// The lock file protecting a keyring context from concurrent modifications.
+#[derive(Debug)]
+struct LockFile(PathBuf);
+
+impl LockFile {
+    fn acquire(path: PathBuf) -> Result<Self> {
+        for _ in 0..LOCK_ATTEMPTS {
+            if Self::try_create(&path)? {
+                return Ok(Self(path));
+            }
+            thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+
+        // Same as the reference implementation, assume the lock is stale and take it over.
+        debug!("Removing stale DBus keyring lock `{}`", path.display());
+        fs::remove_file(&path).map_err(|e| keyring_error(&path, e))?;
+        if Self::try_create(&path)? {
+            Ok(Self(path))
+        } else {
+            Err(Error::Handshake(format!(
+                "Failed to lock DBus keyring `{}`",
+                path.display()
+            )))
+        }
+    }
+
+    fn try_create(path: &Path) -> Result<bool> {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(keyring_error(path, e)),
+        }
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    #[timeout(15000)]
+    fn server_cookie() {
+        let dir = tempfile::tempdir().unwrap();
+        let keyring = Keyring::new(dir.path().join("keyrings"));
+        let path = keyring.dir.join(DEFAULT_CONTEXT);
+
+        // A new cookie is created along with the keyring.
+        let cookie = keyring.server_cookie(DEFAULT_CONTEXT).unwrap();
+        assert_eq!(cookie.id(), 1);
+        assert_eq!(read_cookies(&path).unwrap(), vec![cookie.clone()]);
+        assert_eq!(keyring.lookup(DEFAULT_CONTEXT, 1).unwrap(), cookie);
+        assert!(keyring.lookup(DEFAULT_CONTEXT, 2).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0);
+        }
+
+        // ..and then reused.
+        assert_eq!(keyring.server_cookie(DEFAULT_CONTEXT).unwrap(), cookie);
+
+        // Expired cookies get replaced.
+        let expired = Cookie {
+            created: cookie.created - EXPIRE_COOKIE_TIMEOUT - 1,
+            ..cookie
+        };
+        write_cookies(&path, std::slice::from_ref(&expired)).unwrap();
+        let cookie = keyring.server_cookie(DEFAULT_CONTEXT).unwrap();
+        assert_ne!(cookie, expired);
+        assert_eq!(read_cookies(&path).unwrap(), vec![cookie]);
+        assert!(
+            !dir.path()
+                .join("keyrings")
+                .join("org_freedesktop_general.lock")
+                .exists()
+        );
+
+        assert!(keyring.server_cookie("../escape").is_err());
+    }
+}
@@ -2,6 +2,8 @@ mod auth_mechanism;
 mod client;
 mod command;
 mod common;
+#[cfg(feature = "cookie-sha1")]
+mod cookies;
 #[cfg(feature = "p2p")]
 mod server;
 
@@ -18,6 +20,7 @@ use crate::{Error, OwnedGuid, Result};
 use super::socket::{BoxedSplit, ReadHalf, WriteHalf};
 
 pub use auth_mechanism::AuthMechanism;
+use auth_mechanism::Mechanism;
 use client::Client;
 use command::Command;
 use common::Common;
@@ -276,4 +279,114 @@ mod tests {
             .unwrap();
         crate::utils::block_on(server.perform()).unwrap();
     }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_handshake() {
+        let dir = tempfile::tempdir().unwrap();
+        let keyring = cookies::Keyring::new(dir.path().join(".dbus-keyrings"));
+
+        for _ in 0..2 {
+            let (p0, p1) = create_async_socket_pair();
+            let guid = OwnedGuid::from(Guid::generate());
+            let mut client = Client::new(
+                p0.into(),
+                Some(AuthMechanism::External),
+                Some(guid.clone()),
+                false,
+                None,
+            );
+            client.set_keyring(keyring.clone());
+            let mut server = Server::new(
+                p1.into(),
+                guid,
+                // The credentials of the client aren't known, e.g. over TCP.
+                None,
+                Some(AuthMechanism::External),
+                None,
+            )
+            .unwrap();
+            server.set_keyring(keyring.clone());
+
+            let (client, server) = crate::utils::block_on(join(
+                async move { client.perform().await.unwrap() },
+                async move { server.perform().await.unwrap() },
+            ));
+            assert_eq!(client.server_guid, server.server_guid);
+        }
+
+        // The second handshake reused the cookie created by the first one.
+        let context = dir
+            .path()
+            .join(".dbus-keyrings")
+            .join(cookies::DEFAULT_CONTEXT);
+        assert_eq!(std::fs::read_to_string(context).unwrap().lines().count(), 1);
+    }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_handshake_wrong_cookie() {
+        let dir = tempfile::tempdir().unwrap();
+        let server_keyring = cookies::Keyring::new(dir.path().join("server"));
+        let client_keyring = cookies::Keyring::new(dir.path().join("client"));
+        // Same cookie ID but a different secret.
+        client_keyring
+            .server_cookie(cookies::DEFAULT_CONTEXT)
+            .unwrap();
+
+        let (p0, p1) = create_async_socket_pair();
+        let mut client = Client::new(p0.into(), Some(AuthMechanism::External), None, false, None);
+        client.set_keyring(client_keyring);
+        let mut server = Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            None,
+            Some(AuthMechanism::External),
+            None,
+        )
+        .unwrap();
+        server.set_keyring(server_keyring);
+
+        let (client, server) =
+            crate::utils::block_on(join(async move { client.perform().await }, async move {
+                server.perform().await
+            }));
+        let err = client.unwrap_err();
+        assert!(
+            matches!(&err, Error::Handshake(e) if e.contains("rejected")),
+            "unexpected error: {err}"
+        );
+        // The client hung up after the rejection.
+        server.unwrap_err();
+    }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_handshake_other_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut p0, p1) = create_async_socket_pair();
+        let mut server = Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            None,
+            Some(AuthMechanism::External),
+            None,
+        )
+        .unwrap();
+        server.set_keyring(cookies::Keyring::new(dir.path().to_path_buf()));
+
+        let other_uid = (geteuid().as_raw() + 1).to_string();
+        crate::utils::block_on(p0.write_all(
+            format!("\0AUTH DBUS_COOKIE_SHA1 {}\r\n", hex::encode(other_uid)).as_bytes(),
+        ))
+        .unwrap();
+        drop(p0);
+        // The server rejects the user and then gets an EOF.
+        crate::utils::block_on(server.perform()).unwrap_err();
+        // No cookie was created.
+        assert!(!dir.path().join(cookies::DEFAULT_CONTEXT).exists());
+    }
 }
@@ -3,9 +3,14 @@ use tracing::{instrument, trace};
 
 use crate::names::OwnedUniqueName;
 
+#[cfg(feature = "cookie-sha1")]
+use super::cookies::{self, Cookie, Keyring};
 use super::{
-    AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake, OwnedGuid, Result,
+    AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake, Mechanism,
+    OwnedGuid, Result,
 };
+#[cfg(feature = "cookie-sha1")]
+use crate::utils::constant_time_eq;
 
 /*
  * Server-side handshake logic
@@ -14,11 +19,21 @@ use super::{
 #[allow(clippy::upper_case_acronyms)]
 enum ServerHandshakeStep {
     WaitingForAuth,
-    WaitingForData(AuthMechanism),
+    WaitingForData(Mechanism),
+    #[cfg(feature = "cookie-sha1")]
+    WaitingForCookieResponse(CookieChallenge),
     WaitingForBegin,
     Done,
 }
 
+/// The `DBUS_COOKIE_SHA1` challenge sent to the client.
+#[cfg(feature = "cookie-sha1")]
+#[derive(Debug, PartialEq)]
+struct CookieChallenge {
+    cookie: Cookie,
+    server_challenge: String,
+}
+
 /// A representation of an in-progress handshake, server-side
 ///
 /// This would typically be used to implement a D-Bus broker, or in the context of a P2P connection.
@@ -32,6 +47,8 @@ pub struct Server {
     #[cfg(windows)]
     client_sid: Option<String>,
     unique_name: Option<OwnedUniqueName>,
+    #[cfg(feature = "cookie-sha1")]
+    keyring: Option<Keyring>,
 }
 
 impl Server {
@@ -54,9 +71,29 @@ impl Server {
             client_sid,
             guid,
             unique_name,
+            #[cfg(feature = "cookie-sha1")]
+            keyring: None,
         })
     }
 
+    /// Use `keyring` instead of the keyring of the current user.
+    #[cfg(all(feature = "cookie-sha1", test))]
+    pub(super) fn set_keyring(&mut self, keyring: Keyring) {
+        self.keyring = Some(keyring);
+    }
+
+    /// The mechanisms the client can authenticate with.
+    fn mechanisms(&self) -> &'static [Mechanism] {
+        match self.common.mechanism() {
+            // The cookie stands in for the credentials, where they can't be checked.
+            #[cfg(feature = "cookie-sha1")]
+            AuthMechanism::External => &[Mechanism::External, Mechanism::Cookie],
+            #[cfg(not(feature = "cookie-sha1"))]
+            AuthMechanism::External => &[Mechanism::External],
+            AuthMechanism::Anonymous => &[Mechanism::Anonymous],
+        }
+    }
+
     #[instrument(skip(self))]
     async fn auth_ok(&mut self) -> Result<()> {
         let guid = self.guid.clone();
@@ -92,6 +129,48 @@ impl Server {
         }
     }
 
+    /// Send a `DBUS_COOKIE_SHA1` challenge to the client identified by `sasl_id`.
+    #[cfg(feature = "cookie-sha1")]
+    async fn send_cookie_challenge(&mut self, sasl_id: &[u8]) -> Result<()> {
+        let id = std::str::from_utf8(sasl_id)
+            .map_err(|e| Error::Handshake(format!("Invalid ID: {e}")))?;
+        // We can only check the client against our own keyring so it must be the same user as us.
+        // If we know the credentials of the peer, they must also match.
+        #[cfg(unix)]
+        let peer_ok = self.client_uid.is_none_or(|uid| uid.to_string() == id);
+        #[cfg(windows)]
+        let peer_ok = self.client_sid.as_ref().is_none_or(|sid| sid == id);
+        if !peer_ok || id != super::sasl_auth_id()? {
+            return self.rejected_error().await;
+        }
+
+        let keyring = match &self.keyring {
+            Some(keyring) => keyring.clone(),
+            None => Keyring::user()?,
+        };
+        let cookie = crate::Task::spawn_blocking(
+            move || keyring.server_cookie(cookies::DEFAULT_CONTEXT),
+            "DBus cookie keyring",
+        )
+        .await??;
+        let server_challenge = cookies::random_hex();
+        let challenge = format!(
+            "{} {} {server_challenge}",
+            cookies::DEFAULT_CONTEXT,
+            cookie.id()
+        );
+        trace!("Sending cookie challenge");
+        self.common
+            .write_command(Command::Data(Some(challenge.into_bytes())))
+            .await?;
+        self.step = ServerHandshakeStep::WaitingForCookieResponse(CookieChallenge {
+            cookie,
+            server_challenge,
+        });
+
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn unsupported_command_error(&mut self) -> Result<()> {
         let cmd = Command::Error("Unsupported or misplaced command".to_string());
@@ -102,7 +181,8 @@ impl Server {
 
     #[instrument(skip(self))]
     async fn rejected_error(&mut self) -> Result<()> {
-        let cmd = Command::Rejected(self.common.mechanism().as_str().into());
+        let mechanisms: Vec<_> = self.mechanisms().iter().map(Mechanism::as_str).collect();
+        let cmd = Command::Rejected(mechanisms.join(" ").into());
         trace!("Sending authentication error");
         self.common.write_command(cmd).await?;
         self.step = ServerHandshakeStep::WaitingForAuth;
@@ -116,6 +196,10 @@ impl Server {
         match self.step {
             ServerHandshakeStep::WaitingForAuth => self.handle_auth().await?,
             ServerHandshakeStep::WaitingForData(mech) => self.handle_auth_data(mech).await?,
+            #[cfg(feature = "cookie-sha1")]
+            ServerHandshakeStep::WaitingForCookieResponse(_) => {
+                self.handle_cookie_response().await?
+            }
             ServerHandshakeStep::WaitingForBegin => self.finalize().await?,
             ServerHandshakeStep::Done => return Ok(true),
         }
@@ -132,12 +216,11 @@ impl Server {
         let reply = self.common.read_command().await?;
         match reply {
             Command::Auth(requested_mech, resp) => {
-                let mech = self.common.mechanism();
-                if requested_mech != Some(mech) {
+                let Some(mech) = requested_mech.filter(|m| self.mechanisms().contains(m)) else {
                     self.rejected_error().await?;
 
                     return Ok(());
-                }
+                };
 
                 match &resp {
                     None => {
@@ -146,8 +229,12 @@ impl Server {
                         self.step = ServerHandshakeStep::WaitingForData(mech);
                     }
                     Some(sasl_id) => match mech {
-                        AuthMechanism::Anonymous => self.auth_ok().await?,
-                        AuthMechanism::External => self.check_external_auth(sasl_id).await?,
+                        Mechanism::Anonymous => self.auth_ok().await?,
+                        Mechanism::External => self.check_external_auth(sasl_id).await?,
+                        #[cfg(feature = "cookie-sha1")]
+                        Mechanism::Cookie => self.send_cookie_challenge(sasl_id).await?,
+                        #[cfg(not(feature = "cookie-sha1"))]
+                        Mechanism::Cookie => self.rejected_error().await?,
                     },
                 }
             }
@@ -163,22 +250,66 @@ impl Server {
 
     /// Handle the authentication data receiving step of the handshake.
     #[instrument(skip(self))]
-    async fn handle_auth_data(&mut self, mech: AuthMechanism) -> Result<()> {
+    async fn handle_auth_data(&mut self, mech: Mechanism) -> Result<()> {
         assert!(matches!(self.step, ServerHandshakeStep::WaitingForData(_)));
 
         trace!("Waiting for authentication data");
         let reply = self.common.read_command().await?;
         match (mech, reply) {
-            (AuthMechanism::External, Command::Data(None)) => self.auth_ok().await?,
-            (AuthMechanism::External, Command::Data(Some(data))) => {
+            (Mechanism::External, Command::Data(None)) => self.auth_ok().await?,
+            (Mechanism::External, Command::Data(Some(data))) => {
                 self.check_external_auth(&data).await?;
             }
-            (AuthMechanism::Anonymous, Command::Data(_)) => self.auth_ok().await?,
+            (Mechanism::Anonymous, Command::Data(_)) => self.auth_ok().await?,
+            #[cfg(feature = "cookie-sha1")]
+            (Mechanism::Cookie, Command::Data(Some(data))) => {
+                self.send_cookie_challenge(&data).await?;
+            }
             (_, _) => self.unsupported_command_error().await?,
         }
         Ok(())
     }
 
+    /// Handle the client's response to the `DBUS_COOKIE_SHA1` challenge.
+    #[cfg(feature = "cookie-sha1")]
+    #[instrument(skip(self))]
+    async fn handle_cookie_response(&mut self) -> Result<()> {
+        trace!("Waiting for cookie challenge response");
+        let reply = self.common.read_command().await?;
+        let ServerHandshakeStep::WaitingForCookieResponse(challenge) = &self.step else {
+            unreachable!("Not waiting for a cookie response");
+        };
+        match reply {
+            Command::Data(Some(data)) => {
+                let response = std::str::from_utf8(&data).unwrap_or_default();
+                let mut fields = response.split_ascii_whitespace();
+                let auth_ok = match (fields.next(), fields.next(), fields.next()) {
+                    (Some(client_challenge), Some(hash), None) => {
+                        let expected = challenge
+                            .cookie
+                            .hash(&challenge.server_challenge, client_challenge);
+
+                        constant_time_eq(expected.as_bytes(), hash.as_bytes())
+                    }
+                    _ => false,
+                };
+
+                if auth_ok {
+                    self.auth_ok().await?;
+                } else {
+                    self.rejected_error().await?;
+                }
+            }
+            Command::Cancel | Command::Error(_) => {
+                trace!("Received CANCEL or ERROR command from the client");
+                self.rejected_error().await?;
+            }
+            _ => self.unsupported_command_error().await?,
+        }
+
+        Ok(())
+    }
+
     /// Finalize the handshake.
     #[instrument(skip(self))]
     async fn finalize(&mut self) -> Result<()> {
@@ -0,0 +1,219 @@
+//! Interception of the messages going through a connection.
+
+use std::sync::Arc;
+
+use crate::{Message, Result};
+
+/// An interceptor of the messages sent and received on a [`Connection`].
+///
+/// Interceptors are registered on the connection with [`Builder::interceptor`]. They get to see
+/// every message sent through [`Connection::send`] (and hence through all the higher-level API)
+/// before it's written to the socket, and every message received before it's dispatched to the
+/// [`MessageStream`]s, the [`ObjectServer`] and the proxies. Each message can then be passed on
+/// as-is, replaced by a modified one or dropped.
+///
+/// Use [`message::Builder::from`] on the [`Message::header`] to build a modified message. This
+/// keeps the serial number of the message intact, which is important for method calls, since
+/// replies are matched against it. Replacing a received message keeps its
+/// [`Message::recv_position`].
+///
+/// When multiple interceptors are registered, they're called in the order of registration, each
+/// seeing the message returned by the previous one.
+///
+/// # Example
+///
+/// Dropping all incoming signals of a particular interface:
+///
+/// ```
+/// # zbus::block_on(async {
+/// use zbus::{Message, connection::{Builder, Interceptor}, message::Type};
+///
+/// #[derive(Debug)]
+/// struct DropSignals;
+///
+/// impl Interceptor for DropSignals {
+///     fn incoming(&self, msg: Message) -> Option<Message> {
+///         let header = msg.header();
+///         let drop = header.message_type() == Type::Signal
+///             && header.interface().is_some_and(|i| i == "org.example.Noisy");
+///
+///         (!drop).then_some(msg)
+///     }
+/// }
+///
+/// let conn = Builder::session()?.interceptor(DropSignals).build().await?;
+/// # drop(conn);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`Connection`]: crate::Connection
+/// [`Connection::send`]: crate::Connection::send
+/// [`Builder::interceptor`]: crate::connection::Builder::interceptor
+/// [`MessageStream`]: crate::MessageStream
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`message::Builder::from`]: crate::message::Builder
+pub trait Interceptor: std::fmt::Debug + Send + Sync + 'static {
+    /// Intercept an outgoing message.
+    ///
+    /// Return the message to send, `Ok(None)` to drop it silently or an error to fail the sending.
+    /// Note that if a method call is dropped, no reply will be received for it.
+    ///
+    /// The default implementation passes the message on as-is.
+    fn outgoing(&self, msg: Message) -> Result<Option<Message>> {
+        Ok(Some(msg))
+    }
+
+    /// Intercept an incoming message.
+    ///
+    /// Return the message to dispatch or `None` to drop it.
+    ///
+    /// The default implementation passes the message on as-is.
+    fn incoming(&self, msg: Message) -> Option<Message> {
+        Some(msg)
+    }
+}
+
+/// The chain of interceptors of a connection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interceptors(Arc<Vec<Box<dyn Interceptor>>>);
+
+impl Interceptors {
+    pub(crate) fn new(interceptors: Vec<Box<dyn Interceptor>>) -> Self {
+        Self(Arc::new(interceptors))
+    }
+
+    /// Run `msg` through the chain, as an outgoing message.
+    pub(crate) fn outgoing(&self, msg: &Message) -> Result<Option<Message>> {
+        let mut msg = msg.clone();
+        for interceptor in self.0.iter() {
+            match interceptor.outgoing(msg)? {
+                Some(m) => msg = m,
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(msg))
+    }
+
+    /// Run `msg` through the chain, as an incoming message.
+    pub(crate) fn incoming(&self, mut msg: Message) -> Option<Message> {
+        if self.0.is_empty() {
+            return Some(msg);
+        }
+
+        let recv_position = msg.recv_position();
+        for interceptor in self.0.iter() {
+            msg = interceptor.incoming(msg)?;
+        }
+        msg.set_recv_position(recv_position);
+
+        Some(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::Interceptor;
+    use crate::{
+        Error, Message, MessageStream, Result, connection, interface, message,
+        object_server::SignalEmitter, proxy, utils::block_on,
+    };
+
+    #[derive(Debug)]
+    struct Rewriter;
+
+    impl Interceptor for Rewriter {
+        fn outgoing(&self, msg: Message) -> Result<Option<Message>> {
+            let header = msg.header();
+            match header.member().map(|m| m.as_str()) {
+                Some("Echo") => {
+                    let s: String = msg.body().deserialize()?;
+                    message::Builder::from(header.clone())
+                        .build(&(s.to_uppercase(),))
+                        .map(Some)
+                }
+                Some("Forbidden") => Err(Error::Failure("rejected".into())),
+                _ => Ok(Some(msg)),
+            }
+        }
+
+        fn incoming(&self, msg: Message) -> Option<Message> {
+            let drop = msg.header().member().is_some_and(|m| m == "Dropped");
+
+            (!drop).then_some(msg)
+        }
+    }
+
+    struct Echo;
+
+    #[interface(name = "org.zbus.InterceptorTest")]
+    impl Echo {
+        fn echo(&self, s: &str) -> String {
+            s.to_string()
+        }
+
+        fn forbidden(&self) {}
+
+        #[zbus(signal)]
+        async fn dropped(emitter: &SignalEmitter<'_>) -> Result<()>;
+
+        #[zbus(signal)]
+        async fn kept(emitter: &SignalEmitter<'_>) -> Result<()>;
+    }
+
+    #[proxy(
+        interface = "org.zbus.InterceptorTest",
+        default_service = "org.zbus.InterceptorTest",
+        default_path = "/org/zbus/InterceptorTest"
+    )]
+    trait Echo {
+        fn echo(&self, s: &str) -> Result<String>;
+
+        fn forbidden(&self) -> Result<()>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn interceptor() {
+        block_on(test_interceptor()).unwrap();
+    }
+
+    async fn test_interceptor() -> Result<()> {
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/InterceptorTest", Echo)?
+            .name("org.zbus.InterceptorTest")?
+            .build()
+            .await?;
+        let client = connection::Builder::session()?
+            .interceptor(Rewriter)
+            .build()
+            .await?;
+
+        // Outgoing messages can be rewritten or rejected.
+        let proxy = EchoProxy::new(&client).await?;
+        assert_eq!(proxy.echo("zbus").await?, "ZBUS");
+        assert_eq!(
+            proxy.forbidden().await,
+            Err(Error::Failure("rejected".into()))
+        );
+
+        // Incoming messages can be dropped.
+        let rule = crate::MatchRule::builder()
+            .msg_type(message::Type::Signal)
+            .interface("org.zbus.InterceptorTest")?
+            .build();
+        let mut stream = MessageStream::for_match_rule(rule, &client, None).await?;
+        let emitter = SignalEmitter::new(&service, "/org/zbus/InterceptorTest")?;
+        Echo::dropped(&emitter).await?;
+        Echo::kept(&emitter).await?;
+        let msg = stream.next().await.unwrap()?;
+        assert_eq!(msg.header().member().unwrap(), "Kept");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,650 @@
+//! Listening for peer-to-peer connections on a D-Bus address.
+
+#[cfg(not(feature = "tokio"))]
+use async_io::Async;
+use futures_core::{Future, Stream};
+use futures_util::stream::FuturesUnordered;
+#[cfg(unix)]
+use std::os::unix::net::{SocketAddr, UnixListener};
+#[cfg(any(unix, not(feature = "tokio")))]
+use std::path::PathBuf;
+use std::{
+    fmt,
+    net::{TcpListener, ToSocketAddrs},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tracing::{debug, trace};
+#[cfg(all(windows, not(feature = "tokio")))]
+use uds_windows::UnixListener;
+
+#[cfg(any(
+    all(feature = "vsock", not(feature = "tokio")),
+    feature = "tokio-vsock"
+))]
+use crate::address::transport::Vsock;
+#[cfg(any(unix, not(feature = "tokio")))]
+use crate::address::transport::{Unix, UnixSocket};
+use crate::{
+    Connection, Error, Guid, OwnedGuid, Result, Task,
+    address::{
+        Address, Transport,
+        transport::{NonceFile, Tcp, TcpTransportFamily},
+    },
+};
+
+use super::{AuthMechanism, Builder, socket::BoxedSplit};
+
+type Setup = dyn Fn(Builder<'static>) -> Result<Builder<'static>> + Send + Sync;
+
+/// A listener for peer-to-peer connections.
+///
+/// A `Listener` binds a server [`Address`] and accepts connections from clients. Each accepted
+/// connection goes through the server side of the authentication handshake (see
+/// [`Builder::server`]) before being returned as a ready [`Connection`].
+///
+/// The following transports can be listened on:
+///
+/// * `unix:path=...` and `unix:abstract=...` (the latter only on Linux). On Windows, `unix:`
+///   addresses are only supported without the `tokio` feature.
+/// * `unix:dir=...` and `unix:tmpdir=...`, in which case a socket with a random name is created in
+///   the given directory. On Linux, `tmpdir` results in an abstract socket.
+/// * `tcp:` and `nonce-tcp:`, listening on the `bind` address if specified and on `host`
+///   otherwise. `bind=*` listens on all interfaces and `port=0` on a random port. For `nonce-tcp:`,
+///   the nonce file is created and clients are required to send the nonce.
+/// * `vsock:`, if the `vsock` or `tokio-vsock` feature is enabled.
+///
+/// [`Listener::address`] gives the address clients can connect to, with the actual socket name
+/// or port and the GUID of the server.
+///
+/// This type is only available when the `p2p` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(unix)]
+/// # zbus::block_on(async {
+/// use futures_util::StreamExt;
+/// use zbus::{connection::{Builder, Listener}, interface};
+///
+/// struct Greeter;
+///
+/// #[interface(name = "org.zbus.Greeter1")]
+/// impl Greeter {
+///     fn say_hello(&self, name: &str) -> String {
+///         format!("Hello {name}!")
+///     }
+/// }
+///
+/// let dir = std::env::temp_dir();
+/// let listener = Listener::bind(format!("unix:dir={}", dir.display()).as_str())
+///     .await?
+///     .serve_with(|builder| builder.serve_at("/org/zbus/Greeter", Greeter));
+///
+/// let client = Builder::address(listener.address().clone())?.p2p().build();
+/// let mut incoming = listener.incoming();
+/// let (client, server) = futures_util::join!(client, incoming.next());
+/// let (client, _server) = (client?, server.unwrap()?);
+///
+/// let reply: String = client
+///     .call_method(
+///         None::<()>,
+///         "/org/zbus/Greeter",
+///         Some("org.zbus.Greeter1"),
+///         "SayHello",
+///         &"Maria",
+///     )
+///     .await?
+///     .body()
+///     .deserialize()?;
+/// assert_eq!(reply, "Hello Maria!");
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+pub struct Listener {
+    socket: Socket,
+    address: Address,
+    guid: OwnedGuid,
+    auth_mechanism: Option<AuthMechanism>,
+    setup: Option<Arc<Setup>>,
+    nonce_file: Option<NonceFile>,
+    // The socket file we created, to be removed on drop.
+    #[cfg(any(unix, not(feature = "tokio")))]
+    socket_path: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+enum Socket {
+    #[cfg(not(feature = "tokio"))]
+    Unix(Async<UnixListener>),
+    #[cfg(all(unix, feature = "tokio"))]
+    Unix(tokio::net::UnixListener),
+    #[cfg(not(feature = "tokio"))]
+    Tcp(Async<TcpListener>),
+    #[cfg(feature = "tokio")]
+    Tcp(tokio::net::TcpListener),
+    #[cfg(all(feature = "vsock", not(feature = "tokio")))]
+    Vsock(Async<vsock::VsockListener>),
+    #[cfg(feature = "tokio-vsock")]
+    Vsock(tokio_vsock::VsockListener),
+}
+
+impl Listener {
+    /// Bind the given server address and start listening for connections.
+    ///
+    /// A new GUID is generated for the server, unless the address specifies one.
+    pub async fn bind<A>(address: A) -> Result<Self>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<Error>,
+    {
+        let address = address.try_into().map_err(Into::into)?;
+        let guid: OwnedGuid = match address.guid() {
+            Some(guid) => guid.to_owned().into(),
+            None => Guid::generate().into(),
+        };
+
+        let bound = match address.transport().clone() {
+            #[cfg(any(unix, not(feature = "tokio")))]
+            Transport::Unix(unix) => Self::bind_unix(unix)?,
+            Transport::Tcp(tcp) => Self::bind_tcp(tcp).await?,
+            #[cfg(any(
+                all(feature = "vsock", not(feature = "tokio")),
+                feature = "tokio-vsock"
+            ))]
+            Transport::Vsock(vsock) => Self::bind_vsock(vsock)?,
+            transport => {
+                return Err(Error::Address(format!(
+                    "can't listen on a `{transport}` address"
+                )));
+            }
+        };
+        let address = Address::new(bound.transport).set_guid(guid.clone())?;
+        debug!("Listening on `{address}`");
+
+        Ok(Self {
+            socket: bound.socket,
+            address,
+            guid,
+            auth_mechanism: None,
+            setup: None,
+            nonce_file: bound.nonce_file,
+            #[cfg(any(unix, not(feature = "tokio")))]
+            socket_path: bound.socket_path,
+        })
+    }
+
+    /// The address clients can connect to.
+    ///
+    /// Unlike the address the listener was bound to, this is always a client address: it refers
+    /// to the actual socket and port, and it includes the GUID of the server.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The GUID of the server.
+    pub fn guid(&self) -> &Guid<'_> {
+        self.guid.inner()
+    }
+
+    /// Specify the mechanism to use for authenticating clients.
+    ///
+    /// See [`Builder::auth_mechanism`].
+    #[must_use]
+    pub fn auth_mechanism(mut self, auth_mechanism: AuthMechanism) -> Self {
+        self.auth_mechanism = Some(auth_mechanism);
+
+        self
+    }
+
+    /// Set up each accepted connection with the given function.
+    ///
+    /// `setup` is called with the builder of each accepted connection, before the handshake. This
+    /// is typically used to serve interfaces on all connections through [`Builder::serve_at`]. Any
+    /// state shared between the connections is to be captured by `setup`, e.g. in an [`Arc`].
+    #[must_use]
+    pub fn serve_with<F>(mut self, setup: F) -> Self
+    where
+        F: Fn(Builder<'static>) -> Result<Builder<'static>> + Send + Sync + 'static,
+    {
+        self.setup = Some(Arc::new(setup));
+
+        self
+    }
+
+    /// Accept the next connection.
+    ///
+    /// This waits for a client to connect and for the handshake to complete. Use
+    /// [`Listener::incoming`] to handle multiple clients concurrently.
+    pub async fn accept(&self) -> Result<Connection> {
+        let socket = self.accept_socket().await?;
+
+        self.connection_builder(socket)?.build().await
+    }
+
+    /// A stream of the accepted connections.
+    ///
+    /// The handshakes of all the clients are performed concurrently and connections are yielded as
+    /// soon as they're ready. Errors, whether from accepting a client or from its handshake, are
+    /// yielded as well but they don't end the stream.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming {
+            listener: self,
+            accept: None,
+            handshakes: FuturesUnordered::new(),
+        }
+    }
+
+    async fn accept_socket(&self) -> Result<BoxedSplit> {
+        let socket = match &self.socket {
+            #[cfg(unix)]
+            Socket::Unix(listener) => listener.accept().await?.0.into(),
+            #[cfg(all(windows, not(feature = "tokio")))]
+            Socket::Unix(listener) => {
+                let (stream, _) = listener.read_with(|l| l.accept()).await?;
+
+                Async::new(stream)?.into()
+            }
+            Socket::Tcp(listener) => listener.accept().await?.0.into(),
+            #[cfg(all(feature = "vsock", not(feature = "tokio")))]
+            Socket::Vsock(listener) => {
+                let (stream, _) = listener.read_with(|l| l.accept()).await?;
+
+                Async::new(stream)?.into()
+            }
+            #[cfg(feature = "tokio-vsock")]
+            Socket::Vsock(listener) => listener.accept().await?.0.into(),
+        };
+        trace!("Accepted a connection on `{}`", self.address);
+
+        Ok(socket)
+    }
+
+    fn connection_builder(&self, socket: BoxedSplit) -> Result<Builder<'static>> {
+        let mut builder = Builder::socket(socket).server(self.guid.clone())?.p2p();
+        if let Some(auth_mechanism) = self.auth_mechanism {
+            builder = builder.auth_mechanism(auth_mechanism);
+        }
+        if let Some(nonce_file) = &self.nonce_file {
+            builder = builder.nonce_file(nonce_file);
+        }
+
+        match &self.setup {
+            Some(setup) => setup(builder),
+            None => Ok(builder),
+        }
+    }
+
+    #[cfg(any(unix, not(feature = "tokio")))]
+    fn bind_unix(unix: Unix) -> Result<Bound> {
+        #[cfg(unix)]
+        let file = |path: PathBuf| -> Result<_> {
+            let addr = SocketAddr::from_pathname(&path)?;
+
+            Ok((addr, UnixSocket::File(path.clone()), Some(path)))
+        };
+        // `uds_windows` binds paths directly.
+        #[cfg(windows)]
+        let file = |path: PathBuf| -> Result<_> {
+            Ok((path.clone(), UnixSocket::File(path.clone()), Some(path)))
+        };
+        #[cfg(target_os = "linux")]
+        let abstract_name = |name: std::ffi::OsString| -> Result<_> {
+            use std::os::linux::net::SocketAddrExt;
+
+            let addr = SocketAddr::from_abstract_name(name.as_encoded_bytes())?;
+
+            Ok((addr, UnixSocket::Abstract(name), None))
+        };
+        let random_name = || format!("dbus-{}", uuid::Uuid::new_v4().simple());
+
+        let (addr, socket, socket_path) = match unix.take_path() {
+            UnixSocket::File(path) => file(path)?,
+            #[cfg(target_os = "linux")]
+            UnixSocket::Abstract(name) => abstract_name(name)?,
+            UnixSocket::Dir(dir) => file(dir.join(random_name()))?,
+            #[cfg(target_os = "linux")]
+            UnixSocket::TmpDir(dir) => abstract_name(dir.join(random_name()).into_os_string())?,
+            #[cfg(not(target_os = "linux"))]
+            UnixSocket::TmpDir(dir) => file(dir.join(random_name()))?,
+        };
+
+        #[cfg(unix)]
+        let listener = UnixListener::bind_addr(&addr)?;
+        #[cfg(windows)]
+        let listener = UnixListener::bind(&addr)?;
+        #[cfg(not(feature = "tokio"))]
+        let listener = Async::new(listener)?;
+        #[cfg(feature = "tokio")]
+        let listener = {
+            listener.set_nonblocking(true)?;
+
+            tokio::net::UnixListener::from_std(listener)?
+        };
+
+        Ok(Bound {
+            socket: Socket::Unix(listener),
+            transport: Transport::Unix(Unix::new(socket)),
+            nonce_file: None,
+            socket_path,
+        })
+    }
+
+    async fn bind_tcp(tcp: Tcp) -> Result<Bound> {
+        let nonce_file = tcp.nonce_file_path()?.map(NonceFile::create).transpose()?;
+
+        let host = match (tcp.bind().unwrap_or(tcp.host()), tcp.family()) {
+            ("*", Some(TcpTransportFamily::Ipv6)) => "::",
+            ("*", _) => "0.0.0.0",
+            (host, _) => host,
+        }
+        .to_owned();
+        let (port, family) = (tcp.port(), tcp.family());
+        let addrs = Task::spawn_blocking(
+            move || -> Result<Vec<_>> {
+                let addrs = (host.as_str(), port)
+                    .to_socket_addrs()?
+                    .filter(|a| match family {
+                        Some(TcpTransportFamily::Ipv4) => a.is_ipv4(),
+                        Some(TcpTransportFamily::Ipv6) => a.is_ipv6(),
+                        None => true,
+                    });
+
+                Ok(addrs.collect())
+            },
+            "resolve tcp bind address",
+        )
+        .await??;
+
+        let listener = TcpListener::bind(&addrs[..])?;
+        let port = listener.local_addr()?.port();
+        #[cfg(not(feature = "tokio"))]
+        let listener = Async::new(listener)?;
+        #[cfg(feature = "tokio")]
+        let listener = {
+            listener.set_nonblocking(true)?;
+
+            tokio::net::TcpListener::from_std(listener)?
+        };
+
+        let transport = Tcp::new(tcp.host(), port)
+            .set_family(tcp.family())
+            .set_nonce_file(tcp.nonce_file().map(ToOwned::to_owned));
+
+        Ok(Bound {
+            socket: Socket::Tcp(listener),
+            transport: Transport::Tcp(transport),
+            nonce_file,
+            #[cfg(any(unix, not(feature = "tokio")))]
+            socket_path: None,
+        })
+    }
+
+    #[cfg(any(
+        all(feature = "vsock", not(feature = "tokio")),
+        feature = "tokio-vsock"
+    ))]
+    fn bind_vsock(vsock: Vsock) -> Result<Bound> {
+        #[cfg(not(feature = "tokio"))]
+        let listener = Async::new(vsock::VsockListener::bind_with_cid_port(
+            vsock.cid(),
+            vsock.port(),
+        )?)?;
+        #[cfg(feature = "tokio")]
+        let listener = tokio_vsock::VsockListener::bind(tokio_vsock::VsockAddr::new(
+            vsock.cid(),
+            vsock.port(),
+        ))?;
+        #[cfg(not(feature = "tokio"))]
+        let port = listener.get_ref().local_addr()?.port();
+        #[cfg(feature = "tokio")]
+        let port = listener.local_addr()?.port();
+
+        Ok(Bound {
+            socket: Socket::Vsock(listener),
+            transport: Transport::Vsock(Vsock::new(vsock.cid(), port)),
+            nonce_file: None,
+            #[cfg(any(unix, not(feature = "tokio")))]
+            socket_path: None,
+        })
+    }
+}
+
+struct Bound {
+    socket: Socket,
+    // The transport for clients.
+    transport: Transport,
+    nonce_file: Option<NonceFile>,
+    #[cfg(any(unix, not(feature = "tokio")))]
+    socket_path: Option<PathBuf>,
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener")
+            .field("socket", &self.socket)
+            .field("address", &self.address)
+            .field("auth_mechanism", &self.auth_mechanism)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(any(unix, not(feature = "tokio")))]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(path) = &self.socket_path {
+            if let Err(e) = std::fs::remove_file(path) {
+                debug!("Failed to remove socket file `{}`: {e}", path.display());
+            }
+        }
+    }
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A [`Stream`] of the connections accepted by a [`Listener`].
+///
+/// Use [`Listener::incoming`] to create an instance of this type.
+#[must_use = "streams do nothing unless polled"]
+pub struct Incoming<'l> {
+    listener: &'l Listener,
+    accept: Option<BoxFuture<'l, Result<BoxedSplit>>>,
+    handshakes: FuturesUnordered<BoxFuture<'static, Result<Connection>>>,
+}
+
+impl Stream for Incoming<'_> {
+    type Item = Result<Connection>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // Start the handshakes of all the clients that are waiting.
+        loop {
+            let listener = this.listener;
+            let accept = this
+                .accept
+                .get_or_insert_with(|| Box::pin(listener.accept_socket()));
+            let socket = match accept.as_mut().poll(cx) {
+                Poll::Ready(socket) => socket,
+                Poll::Pending => break,
+            };
+            this.accept = None;
+
+            match socket.and_then(|socket| listener.connection_builder(socket)) {
+                Ok(builder) => this.handshakes.push(Box::pin(builder.build())),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+
+        // Only the handshakes that were woken up are polled.
+        match Pin::new(&mut this.handshakes).poll_next(cx) {
+            Poll::Ready(Some(conn)) => Poll::Ready(Some(conn)),
+            // No handshakes in progress, but more clients are to come.
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl fmt::Debug for Incoming<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Incoming")
+            .field("listener", &self.listener)
+            .field("pending_handshakes", &self.handshakes.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::Listener;
+    use crate::{
+        Connection, Result,
+        address::{Address, Transport, transport::Tcp},
+        connection::Builder,
+        interface,
+    };
+
+    struct Counter(std::sync::Arc<std::sync::atomic::AtomicU32>);
+
+    #[interface(name = "org.zbus.Counter1")]
+    impl Counter {
+        fn next(&self) -> u32 {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    async fn call_next(conn: &Connection) -> Result<u32> {
+        conn.call_method(
+            None::<()>,
+            "/counter",
+            Some("org.zbus.Counter1"),
+            "Next",
+            &(),
+        )
+        .await?
+        .body()
+        .deserialize()
+    }
+
+    async fn check_listener(address: &str) -> Result<Listener> {
+        let counter = std::sync::Arc::default();
+        let listener = Listener::bind(address).await?.serve_with(move |builder| {
+            builder.serve_at("/counter", Counter(std::sync::Arc::clone(&counter)))
+        });
+        assert_eq!(listener.address().guid(), Some(listener.guid()));
+
+        // The interface state is shared by all the connections.
+        let mut incoming = listener.incoming();
+        for expected in 0..2 {
+            let client = Builder::address(listener.address().clone())?.p2p().build();
+            let (client, server) = futures_util::join!(client, incoming.next());
+            let (client, _server) = (client?, server.unwrap()?);
+            assert_eq!(call_next(&client).await?, expected);
+        }
+        drop(incoming);
+
+        Ok(listener)
+    }
+
+    #[cfg(any(unix, not(feature = "tokio")))]
+    #[test]
+    #[timeout(15000)]
+    fn unix_dir() {
+        use crate::address::transport::UnixSocket;
+
+        crate::utils::block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let listener = check_listener(&format!("unix:dir={}", dir.path().display())).await?;
+            let path = match listener.address().transport() {
+                Transport::Unix(unix) => match unix.path() {
+                    UnixSocket::File(path) => path.clone(),
+                    path => panic!("unexpected socket path: {path:?}"),
+                },
+                transport => panic!("unexpected transport: {transport}"),
+            };
+            assert_eq!(path.parent(), Some(dir.path()));
+            assert!(path.exists());
+
+            // The socket file is removed with the listener.
+            drop(listener);
+            assert!(!path.exists());
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[timeout(15000)]
+    fn unix_tmpdir() {
+        use crate::address::transport::UnixSocket;
+        crate::utils::block_on(async {
+            let listener = check_listener("unix:tmpdir=/tmp").await?;
+            match listener.address().transport() {
+                Transport::Unix(unix) => {
+                    assert!(matches!(unix.path(), UnixSocket::Abstract(name)
+                        if name.to_string_lossy().starts_with("/tmp/dbus-")));
+                }
+                transport => panic!("unexpected transport: {transport}"),
+            }
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn tcp() {
+        crate::utils::block_on(async {
+            let listener = check_listener("tcp:host=localhost,bind=127.0.0.1,port=0").await?;
+            match listener.address().transport() {
+                Transport::Tcp(tcp) => {
+                    assert_eq!(tcp.host(), "localhost");
+                    assert_eq!(tcp.bind(), None);
+                    assert_ne!(tcp.port(), 0);
+                }
+                transport => panic!("unexpected transport: {transport}"),
+            }
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn nonce_tcp() {
+        crate::utils::block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let nonce_path = dir.path().join("nonce");
+            let listener = check_listener(&format!(
+                "nonce-tcp:host=127.0.0.1,port=0,noncefile={}",
+                nonce_path.display()
+            ))
+            .await?;
+            assert!(nonce_path.exists());
+
+            // Clients that don't send the nonce are rejected.
+            let Transport::Tcp(tcp) = listener.address().transport() else {
+                panic!("unexpected transport");
+            };
+            let address = Address::new(Transport::Tcp(Tcp::new(tcp.host(), tcp.port())));
+            let client = Builder::address(address)?.p2p().build();
+            let (_, server) = futures_util::join!(client, listener.accept());
+            server.unwrap_err();
+
+            drop(listener);
+            assert!(!nonce_path.exists());
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+}
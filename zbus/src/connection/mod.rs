@@ -8,7 +8,10 @@ use std::{
     io::{self, ErrorKind},
     num::NonZeroU32,
     pin::Pin,
-    sync::{Arc, OnceLock, Weak},
+    sync::{
+        Arc, OnceLock, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
     task::{Context, Poll},
     time::Duration,
 };
@@ -31,13 +34,33 @@ use crate::{
 
 mod builder;
 pub use builder::Builder;
+mod interceptor;
+pub use interceptor::Interceptor;
+use interceptor::Interceptors;
+
+#[cfg(feature = "p2p")]
+mod listener;
+#[cfg(feature = "p2p")]
+pub use listener::{Incoming, Listener};
 
 pub mod socket;
 pub use socket::Socket;
 
+mod name_ownership;
+use name_ownership::Ownership;
+pub use name_ownership::{NameOwnership, NameOwnershipBuilder, NameState, NameStateStream};
+
 mod socket_reader;
 use socket_reader::SocketReader;
 
+mod stats;
+use stats::{Counters, PendingCall};
+pub use stats::{LatencyHistogram, Stats};
+
+mod reconnect;
+use reconnect::Reconnect;
+pub use reconnect::ReconnectPolicy;
+
 pub(crate) mod handshake;
 pub use handshake::AuthMechanism;
 use handshake::Authenticated;
@@ -49,12 +72,19 @@ const DEFAULT_MAX_METHOD_RETURN_QUEUED: usize = 8;
 #[derive(Debug)]
 pub(crate) struct ConnectionInner {
     server_guid: OwnedGuid,
+    // The server GUID after automatic reconnection.
+    current_server_guid: Replaceable<OwnedGuid>,
     #[cfg(unix)]
     cap_unix_fd: bool,
     #[cfg(feature = "p2p")]
     bus_conn: bool,
     unique_name: OnceLock<OwnedUniqueName>,
-    registered_names: Mutex<HashMap<WellKnownName<'static>, NameStatus>>,
+    // The unique name after automatic reconnection.
+    current_unique_name: Replaceable<OwnedUniqueName>,
+    registered_names:
+        Mutex<HashMap<WellKnownName<'static>, (BitFlags<RequestNameFlags>, NameStatus)>>,
+    // The names held through `NameOwnership` handles.
+    name_ownerships: std::sync::Mutex<Vec<Weak<Ownership>>>,
 
     activity_event: Arc<Event>,
     socket_write: Mutex<Box<dyn socket::WriteHalf>>,
@@ -80,6 +110,21 @@ pub(crate) struct ConnectionInner {
     method_timeout: Option<Duration>,
     // Cache the credentials.
     credentials: OnceLock<Arc<ConnectionCredentials>>,
+
+    // Set if the connection is to be re-established when lost.
+    reconnect: Option<Reconnect>,
+    // Whether `Connection::close` was called, in which case we don't reconnect.
+    closed: AtomicBool,
+
+    interceptors: Interceptors,
+
+    pub(crate) stats: Arc<Counters>,
+}
+
+impl ConnectionInner {
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
 }
 
 impl Drop for ConnectionInner {
@@ -223,6 +268,19 @@ pub struct Connection {
 pub(crate) struct PendingMethodCall {
     stream: Option<MessageStream>,
     serial: NonZeroU32,
+    _pending: PendingCall,
+}
+
+impl PendingMethodCall {
+    /// Wait for the reply, failing with [`Error::MethodTimeout`] if it isn't received in time.
+    pub(crate) async fn reply(self, method_timeout: Option<Duration>) -> Result<Message> {
+        match method_timeout {
+            Some(tout) => timeout(self, tout)
+                .await
+                .unwrap_or(Err(Error::MethodTimeout)),
+            None => self.await,
+        }
+    }
 }
 
 impl Future for PendingMethodCall {
@@ -291,6 +349,14 @@ impl OrderedFuture for PendingMethodCall {
 impl Connection {
     /// Send `msg` to the peer.
     pub async fn send(&self, msg: &Message) -> Result<()> {
+        let msg = match self.inner.interceptors.outgoing(msg)? {
+            Some(msg) => msg,
+            None => {
+                trace!("Outgoing message dropped by an interceptor: {:?}", msg);
+
+                return Ok(());
+            }
+        };
         #[cfg(unix)]
         if !msg.data().fds().is_empty() && !self.inner.cap_unix_fd {
             return Err(Error::Unsupported);
@@ -299,7 +365,10 @@ impl Connection {
         self.inner.activity_event.notify(usize::MAX);
         let mut write = self.inner.socket_write.lock().await;
 
-        write.send_message(msg).await
+        write.send_message(&msg).await?;
+        self.inner.stats.message_sent(&msg);
+
+        Ok(())
     }
 
     /// Send a method call.
@@ -339,11 +408,7 @@ impl Connection {
             .await?
             .expect("no reply");
 
-        if let Some(tout) = self.method_timeout() {
-            timeout(method, tout).await
-        } else {
-            method.await
-        }
+        method.reply(self.method_timeout()).await
     }
 
     /// Send a method call.
@@ -379,7 +444,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut builder = Message::method_call(path, method_name)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             builder = builder.sender(sender)?
         }
         if let Some(destination) = destination {
@@ -405,7 +470,11 @@ impl Connection {
         if flags.contains(Flags::NoReplyExpected) {
             Ok(None)
         } else {
-            Ok(Some(PendingMethodCall { stream, serial }))
+            Ok(Some(PendingMethodCall {
+                stream,
+                serial,
+                _pending: PendingCall::new(self.inner.stats.clone()),
+            }))
         }
     }
 
@@ -434,7 +503,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut b = Message::signal(path, interface, signal_name)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             b = b.sender(sender)?;
         }
         if let Some(destination) = destination {
@@ -456,7 +525,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut b = Message::method_return(call)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             b = b.sender(sender)?;
         }
         let m = b.build(body)?;
@@ -481,7 +550,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut b = Message::error(call, error_name)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             b = b.sender(sender)?;
         }
         let m = b.build(body)?;
@@ -640,13 +709,13 @@ impl Connection {
         let mut names = self.inner.registered_names.lock().await;
 
         match names.get(&well_known_name) {
-            Some(NameStatus::Owner(_)) => return Ok(RequestNameReply::AlreadyOwner),
-            Some(NameStatus::Queued(_)) => return Ok(RequestNameReply::InQueue),
+            Some((_, NameStatus::Owner(_))) => return Ok(RequestNameReply::AlreadyOwner),
+            Some((_, NameStatus::Queued(_))) => return Ok(RequestNameReply::InQueue),
             None => (),
         }
 
         if !self.is_bus() {
-            names.insert(well_known_name.to_owned(), NameStatus::Owner(None));
+            names.insert(well_known_name.to_owned(), (flags, NameStatus::Owner(None)));
 
             return Ok(RequestNameReply::PrimaryOwner);
         }
@@ -700,6 +769,8 @@ impl Connection {
 
                                     break;
                                 }
+                                // The name is requested again on reconnection.
+                                Err(Error::Reconnected) => (),
                                 Err(e) => warn!("Failed to parse `NameLost` signal: {}", e),
                             },
                             None => {
@@ -740,7 +811,7 @@ impl Connection {
                                 Some(signal) => match signal {
                                     Ok(_) => {
                                         let mut names = inner.registered_names.lock().await;
-                                        if let Some(status) = names.get_mut(&well_known_name) {
+                                        if let Some((_, status)) = names.get_mut(&well_known_name) {
                                             let task = name_lost_fut.map(|fut| {
                                                 inner.executor.spawn(fut, &lost_task_name)
                                             });
@@ -750,6 +821,7 @@ impl Connection {
                                         }
                                         // else the name was released in the meantime. :shrug:
                                     }
+                                    Err(Error::Reconnected) => (),
                                     Err(e) => warn!("Failed to parse `NameAcquired` signal: {}", e),
                                 },
                                 None => {
@@ -775,7 +847,7 @@ impl Connection {
             RequestNameReply::Exists => return Err(Error::NameTaken),
         };
 
-        names.insert(well_known_name.to_owned(), status);
+        names.insert(well_known_name.to_owned(), (flags, status));
 
         Ok(reply)
     }
@@ -817,6 +889,24 @@ impl Connection {
         .map(|r| r == ReleaseNameReply::Released)
     }
 
+    /// Create a builder for tracking the ownership of a well-known name over time.
+    ///
+    /// This is an alternative to [`Connection::request_name_with_flags`], returning a
+    /// [`NameOwnership`] handle that keeps track of the name being acquired or lost after the
+    /// request, and releases the name on drop.
+    pub fn name_ownership<'w, W>(&self, well_known_name: W) -> Result<NameOwnershipBuilder<'_>>
+    where
+        W: TryInto<WellKnownName<'w>>,
+        W::Error: Into<Error>,
+    {
+        let well_known_name = well_known_name.try_into().map_err(Into::into)?;
+
+        Ok(NameOwnershipBuilder::new(
+            self,
+            well_known_name.into_owned(),
+        ))
+    }
+
     /// Check if `self` is a connection to a message bus.
     ///
     /// This will return `false` for p2p connections. When the `p2p` feature is disabled, this will
@@ -835,11 +925,21 @@ impl Connection {
     /// The unique name of the connection, if set/applicable.
     ///
     /// The unique name is assigned by the message bus, or set manually using
-    /// [`Connection::set_unique_name`].
+    /// [`Connection::set_unique_name`]. This is the unique name of the initial connection; see
+    /// [`Connection::current_unique_name`] for the one assigned on automatic reconnection.
     pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
         self.inner.unique_name.get()
     }
 
+    /// The current unique name of the connection, if set/applicable.
+    ///
+    /// This is the same as [`Connection::unique_name`], unless the connection was re-established
+    /// (see [`Builder::auto_reconnect`]), in which case it's the name the bus assigned on the last
+    /// reconnection.
+    pub fn current_unique_name(&self) -> Option<OwnedUniqueName> {
+        self.inner.current_unique_name.get()
+    }
+
     /// Set the unique name of the connection (if not already set).
     ///
     /// This is mainly provided for bus implementations. All other users should not need to use this
@@ -861,6 +961,13 @@ impl Connection {
         Ok(())
     }
 
+    /// Statistics about the traffic on this connection.
+    ///
+    /// The [`crate::fdo::DebugStats`] interface can be served to expose these over D-Bus.
+    pub fn stats(&self) -> Stats {
+        self.inner.stats.snapshot()
+    }
+
     /// The capacity of the main (unfiltered) queue.
     pub fn max_queued(&self) -> usize {
         self.inner.msg_receiver.capacity()
@@ -872,10 +979,24 @@ impl Connection {
     }
 
     /// The server's GUID.
+    ///
+    /// This is the GUID of the server of the initial connection; see
+    /// [`Connection::current_server_guid`] for the one of the server reconnected to.
     pub fn server_guid(&self) -> &OwnedGuid {
         &self.inner.server_guid
     }
 
+    /// The GUID of the current server.
+    ///
+    /// This is the same as [`Connection::server_guid`], unless the connection was re-established
+    /// (see [`Builder::auto_reconnect`]), in which case the server might be a different one.
+    pub fn current_server_guid(&self) -> OwnedGuid {
+        self.inner
+            .current_server_guid
+            .get()
+            .expect("server GUID is always set")
+    }
+
     /// The underlying executor.
     ///
     /// When a connection is built with internal_executor set to false, zbus will not spawn a
@@ -967,7 +1088,12 @@ impl Connection {
                     let mut stream = match weak_conn.upgrade() {
                         Some(conn) => {
                             let mut builder = MatchRule::builder().msg_type(Type::MethodCall);
-                            if let Some(unique_name) = conn.unique_name() {
+                            // The unique name changes on reconnection. The bus only sends us
+                            // method calls destined to us anyway.
+                            if let Some(unique_name) = conn
+                                .unique_name()
+                                .filter(|_| conn.inner.reconnect.is_none())
+                            {
                                 builder = builder.destination(&**unique_name).expect("unique name");
                             }
                             let rule = builder.build();
@@ -992,12 +1118,16 @@ impl Connection {
                     }
 
                     trace!("waiting for incoming method call messages..");
-                    while let Some(msg) = stream.next().await.and_then(|m| {
-                        if let Err(e) = &m {
-                            debug!("Error while reading from object server stream: {:?}", e);
-                        }
-                        m.ok()
-                    }) {
+                    while let Some(msg) = stream.next().await {
+                        // The stream ends after an error, unless the connection is re-established.
+                        let msg = match msg {
+                            Ok(msg) => msg,
+                            Err(e) => {
+                                debug!("Error while reading from object server stream: {:?}", e);
+
+                                continue;
+                            }
+                        };
                         if let Some(conn) = weak_conn.upgrade() {
                             let hdr = msg.header();
                             // If we're connected to a bus, skip the destination check as the
@@ -1076,6 +1206,7 @@ impl Connection {
                     .await?;
                 }
                 e.insert((1, receiver.clone().deactivate()));
+                self.inner.stats.match_rule_added();
                 self.inner
                     .msg_senders
                     .lock()
@@ -1121,6 +1252,7 @@ impl Connection {
                         .await?;
                     }
                     e.remove();
+                    self.inner.stats.match_rule_removed();
                     self.inner
                         .msg_senders
                         .lock()
@@ -1132,6 +1264,69 @@ impl Connection {
         }
     }
 
+    /// The match rules currently in use.
+    pub(crate) async fn match_rules(&self) -> Vec<OwnedMatchRule> {
+        self.inner
+            .subscriptions
+            .lock()
+            .await
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Keep track of a name held through a `NameOwnership` handle.
+    pub(crate) fn add_name_ownership(&self, ownership: &Arc<Ownership>) {
+        let mut ownerships = self.inner.name_ownerships.lock().expect("lock poisoned");
+        ownerships.retain(|o| o.strong_count() > 0);
+        ownerships.push(Arc::downgrade(ownership));
+    }
+
+    /// Release all the well-known names registered through this connection.
+    ///
+    /// This includes the names held through `NameOwnership` handles, which are not requested again.
+    pub(crate) async fn release_all_names(&self) -> Result<()> {
+        // These might not be registered anymore, as names acquired from the queue after being lost
+        // aren't.
+        let ownerships: Vec<_> = self
+            .inner
+            .name_ownerships
+            .lock()
+            .expect("lock poisoned")
+            .drain(..)
+            .filter_map(|o| o.upgrade())
+            .collect();
+        for ownership in ownerships {
+            ownership.release(self).await?;
+        }
+
+        let names: Vec<_> = self
+            .inner
+            .registered_names
+            .lock()
+            .await
+            .keys()
+            .cloned()
+            .collect();
+        for name in names {
+            self.release_name(name).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The well-known names currently owned.
+    pub(crate) async fn owned_names(&self) -> Vec<WellKnownName<'static>> {
+        self.inner
+            .registered_names
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, (_, status))| matches!(status, NameStatus::Owner(_)))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
     pub(crate) fn queue_remove_match(&self, rule: OwnedMatchRule) {
         let conn = self.clone();
         let task_name = format!("Remove match `{}`", *rule);
@@ -1150,6 +1345,8 @@ impl Connection {
         #[allow(unused)] bus_connection: bool,
         executor: Executor<'static>,
         method_timeout: Option<Duration>,
+        reconnect: Option<Reconnect>,
+        interceptors: Interceptors,
     ) -> Result<Self> {
         #[cfg(unix)]
         let cap_unix_fd = auth.cap_unix_fd;
@@ -1185,12 +1382,14 @@ impl Connection {
             inner: Arc::new(ConnectionInner {
                 activity_event: Arc::new(Event::new()),
                 socket_write: Mutex::new(auth.socket_write),
+                current_server_guid: Replaceable::new(Some(auth.server_guid.clone())),
                 server_guid: auth.server_guid,
                 #[cfg(unix)]
                 cap_unix_fd,
                 #[cfg(feature = "p2p")]
                 bus_conn: bus_connection,
                 unique_name: OnceLock::new(),
+                current_unique_name: Replaceable::new(None),
                 subscriptions,
                 object_server: OnceLock::new(),
                 object_server_dispatch_task: OnceLock::new(),
@@ -1203,6 +1402,11 @@ impl Connection {
                 drop_event: Event::new(),
                 method_timeout,
                 credentials: OnceLock::new(),
+                name_ownerships: std::sync::Mutex::new(vec![]),
+                reconnect,
+                closed: AtomicBool::new(false),
+                interceptors,
+                stats: Arc::new(Counters::default()),
             }),
         };
 
@@ -1282,6 +1486,7 @@ impl Connection {
     ///
     /// After this call, all reading and writing operations will fail.
     pub async fn close(self) -> Result<()> {
+        self.inner.closed.store(true, Ordering::Release);
         self.inner.activity_event.notify(usize::MAX);
         self.inner
             .socket_write
@@ -1353,11 +1558,10 @@ impl Connection {
             .set(
                 SocketReader::new(
                     socket_read,
-                    inner.msg_senders.clone(),
                     already_read,
                     #[cfg(unix)]
                     already_received_fds,
-                    inner.activity_event.clone(),
+                    self,
                 )
                 .spawn(&inner.executor),
             )
@@ -1367,9 +1571,10 @@ impl Connection {
     fn set_unique_name_(&self, name: OwnedUniqueName) {
         self.inner
             .unique_name
-            .set(name)
+            .set(name.clone())
             // programmer (probably our) error if this fails.
             .expect("unique name already set");
+        self.inner.current_unique_name.set(name);
     }
 }
 
@@ -1409,6 +1614,26 @@ enum NameStatus {
     Queued(#[allow(unused)] Task<()>),
 }
 
+/// A value that can be replaced.
+///
+/// This is used for the connection properties that change on reconnection.
+#[derive(Debug)]
+struct Replaceable<T>(std::sync::RwLock<Option<T>>);
+
+impl<T: Clone> Replaceable<T> {
+    fn new(value: Option<T>) -> Self {
+        Self(std::sync::RwLock::new(value))
+    }
+
+    fn get(&self) -> Option<T> {
+        self.0.read().expect("lock poisoned").clone()
+    }
+
+    fn set(&self, value: T) {
+        *self.0.write().expect("lock poisoned") = Some(value);
+    }
+}
+
 static SERIAL_NUM_SEMAPHORE: Semaphore = Semaphore::new(1);
 
 // Make message creation and sending an atomic operation, using an async
@@ -1745,6 +1970,72 @@ mod p2p_tests {
         futures_util::try_join!(server_conn_builder.build(), client_conn_builder.build())
     }
 
+    #[test]
+    #[timeout(15000)]
+    fn nonce_tcp_p2p() {
+        crate::utils::block_on(test_nonce_tcp_p2p()).unwrap();
+    }
+
+    async fn test_nonce_tcp_p2p() -> Result<()> {
+        use crate::address::{
+            Address,
+            transport::{NonceFile, Tcp, Transport},
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let nonce_file = NonceFile::create(dir.path().join("nonce"))?;
+        let wrong_nonce_file = NonceFile::create(dir.path().join("wrong-nonce"))?;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let address = |nonce_file: &NonceFile| {
+            let path = nonce_file.path().as_os_str().as_encoded_bytes().to_vec();
+
+            Address::new(Transport::Tcp(
+                Tcp::new("127.0.0.1", port).set_nonce_file(Some(path)),
+            ))
+        };
+
+        for (client_nonce_file, valid) in [(&nonce_file, true), (&wrong_nonce_file, false)] {
+            let listener = listener.try_clone()?;
+            let server = async {
+                let (stream, _) =
+                    crate::Task::spawn_blocking(move || listener.accept(), "accept").await??;
+                #[cfg(feature = "tokio")]
+                let stream = {
+                    stream.set_nonblocking(true)?;
+                    tokio::net::TcpStream::from_std(stream)?
+                };
+
+                Builder::tcp_stream(stream)
+                    .server(Guid::generate())?
+                    .p2p()
+                    .auth_mechanism(AuthMechanism::Anonymous)
+                    .nonce_file(&nonce_file)
+                    .build()
+                    .await
+            };
+            let client = Builder::address(address(client_nonce_file))?.p2p().build();
+
+            let (server, client) = futures_util::join!(server, client);
+            if valid {
+                let (_server, _client) = (server?, client?);
+            } else {
+                assert!(matches!(server, Err(crate::Error::Handshake(_))));
+                client.unwrap_err();
+            }
+        }
+
+        // Only servers check the nonce.
+        let client = Builder::address(address(&nonce_file))?
+            .p2p()
+            .nonce_file(&nonce_file)
+            .build()
+            .await;
+        assert!(matches!(client, Err(crate::Error::Unsupported)));
+
+        Ok(())
+    }
+
     #[cfg(unix)]
     #[test]
     #[timeout(15000)]
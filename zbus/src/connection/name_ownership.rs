@@ -0,0 +1,495 @@
+//! Tracking of the ownership of a well-known name over time.
+
+use enumflags2::BitFlags;
+use futures_core::stream;
+use futures_lite::StreamExt;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+};
+use tracing::{Instrument, debug, info_span, trace, warn};
+use zbus_names::WellKnownName;
+
+use super::{Connection, WeakConnection};
+use crate::{
+    Error, MatchRule, MessageStream, Result, Task,
+    fdo::{DBusProxy, NameOwnerChanged, ReleaseNameReply, RequestNameFlags, RequestNameReply},
+    message::Type,
+};
+
+/// The maximum number of queued [`NameState`] changes, before the oldest ones are dropped.
+const MAX_QUEUED_CHANGES: usize = 16;
+
+/// The ownership state of a well-known name, as tracked by [`NameOwnership`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NameState {
+    /// The connection is the primary owner of the name.
+    PrimaryOwner,
+    /// The connection is in the queue of the name, waiting for the current owner to release it.
+    InQueue,
+    /// The connection neither owns the name nor is in its queue.
+    Lost,
+}
+
+/// A handle on the ownership of a well-known name.
+///
+/// [`Connection::request_name_with_flags`] only tells about the ownership of the name at the time
+/// of the request. The name can be acquired later on, when the connection is in its queue, or lost
+/// to another peer, if [`RequestNameFlags::AllowReplacement`] was passed. `NameOwnership` keeps
+/// track of these changes, and can request the name again after it's lost (see
+/// [`NameOwnershipBuilder::reacquire`]).
+///
+/// The name is released when the handle is dropped, or along with all the other names of the
+/// connection by [`ObjectServer::idle`]. It's not requested again after that.
+///
+/// Use [`Connection::name_ownership`] to create an instance of this type.
+///
+/// # Example
+///
+/// A hot standby for a service, taking over once the active instance goes away:
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use futures_util::StreamExt;
+/// use zbus::{Connection, connection::NameState, fdo::RequestNameFlags};
+///
+/// let connection = Connection::session().await?;
+/// let ownership = connection
+///     .name_ownership("org.zbus.Standby")?
+///     .flags(RequestNameFlags::AllowReplacement.into())
+///     .request()
+///     .await?;
+/// let mut changes = ownership.receive_state_changes();
+///
+/// while ownership.state() != NameState::PrimaryOwner {
+///     changes.next().await;
+/// }
+/// println!("Now serving requests");
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`ObjectServer::idle`]: crate::ObjectServer::idle
+pub struct NameOwnership {
+    conn: Connection,
+    ownership: Arc<Ownership>,
+    changes: async_broadcast::InactiveReceiver<NameState>,
+    task: Option<Task<()>>,
+}
+
+impl NameOwnership {
+    /// The connection owning (or trying to own) the name.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// The name.
+    pub fn name(&self) -> &WellKnownName<'static> {
+        &self.ownership.name
+    }
+
+    /// The current state of the ownership of the name.
+    pub fn state(&self) -> NameState {
+        self.ownership.state()
+    }
+
+    /// A stream of the changes to the state of the ownership of the name.
+    ///
+    /// Only the changes happening after this call are received.
+    pub fn receive_state_changes(&self) -> NameStateStream {
+        NameStateStream(self.changes.activate_cloned())
+    }
+
+    /// Release the name.
+    ///
+    /// This is the same as dropping the handle, except that the outcome is reported. Returns
+    /// `Ok(false)` if the connection neither owned the name nor was in its queue.
+    pub async fn release(mut self) -> Result<bool> {
+        self.task.take();
+
+        self.ownership.release(&self.conn).await
+    }
+}
+
+impl Drop for NameOwnership {
+    fn drop(&mut self) {
+        self.task.take();
+        if self.ownership.is_released() {
+            return;
+        }
+
+        let conn = self.conn.clone();
+        let ownership = self.ownership.clone();
+        let task_name = format!("Release name `{}`", ownership.name);
+        let release = async move {
+            if let Err(e) = ownership.release(&conn).await {
+                warn!("Failed to release name `{}`: {e}", ownership.name);
+            }
+        };
+        self.conn.executor().spawn(release, &task_name).detach();
+    }
+}
+
+impl fmt::Debug for NameOwnership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NameOwnership")
+            .field("name", &self.ownership.name)
+            .field("state", &self.state())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The ownership of a name, shared by a [`NameOwnership`], its [`Monitor`] and the connection.
+#[derive(Debug)]
+pub(crate) struct Ownership {
+    name: WellKnownName<'static>,
+    state: Mutex<NameState>,
+    changes: async_broadcast::Sender<NameState>,
+    /// Whether the name was released, after which it's not requested again.
+    released: AtomicBool,
+}
+
+impl Ownership {
+    fn state(&self) -> NameState {
+        *self.state.lock().expect("lock poisoned")
+    }
+
+    async fn set_state(&self, state: NameState) {
+        let previous = std::mem::replace(&mut *self.state.lock().expect("lock poisoned"), state);
+        if previous == state {
+            return;
+        }
+        debug!("Name `{}` ownership changed to {state:?}", self.name);
+
+        // Only fails if there are no active receivers.
+        let _ = self.changes.broadcast_direct(state).await;
+    }
+
+    fn is_released(&self) -> bool {
+        self.released.load(Ordering::Acquire)
+    }
+
+    /// Release the name, unless it was already.
+    ///
+    /// Returns `Ok(false)` if the connection neither owned the name nor was in its queue.
+    pub(crate) async fn release(&self, conn: &Connection) -> Result<bool> {
+        if self.released.swap(true, Ordering::AcqRel) {
+            return Ok(false);
+        }
+        let released = release(conn, &self.name).await?;
+        self.set_state(NameState::Lost).await;
+
+        Ok(released)
+    }
+}
+
+/// Builder for [`NameOwnership`].
+///
+/// Use [`Connection::name_ownership`] to create an instance of this type.
+#[derive(Debug)]
+#[must_use = "the name is only requested by `NameOwnershipBuilder::request`"]
+pub struct NameOwnershipBuilder<'c> {
+    conn: &'c Connection,
+    name: WellKnownName<'static>,
+    flags: BitFlags<RequestNameFlags>,
+    reacquire: bool,
+}
+
+impl<'c> NameOwnershipBuilder<'c> {
+    pub(super) fn new(conn: &'c Connection, name: WellKnownName<'static>) -> Self {
+        Self {
+            conn,
+            name,
+            flags: BitFlags::default(),
+            reacquire: false,
+        }
+    }
+
+    /// The flags to request the name with.
+    ///
+    /// By default, the same flags as [`Connection::request_name`] are used.
+    pub fn flags(mut self, flags: BitFlags<RequestNameFlags>) -> Self {
+        self.flags = flags;
+
+        self
+    }
+
+    /// Whether to request the name again, once it's released by its owner, after it's lost.
+    ///
+    /// This is only relevant with the [`RequestNameFlags::DoNotQueue`] flag, since the connection
+    /// is otherwise put in the queue of the name when losing it. Defaults to `false`.
+    pub fn reacquire(mut self, reacquire: bool) -> Self {
+        self.reacquire = reacquire;
+
+        self
+    }
+
+    /// Request the name.
+    ///
+    /// Unlike [`Connection::request_name_with_flags`], this doesn't fail if the name is already
+    /// owned by another peer and the connection wasn't queued. The state of the returned handle is
+    /// [`NameState::Lost`] then.
+    pub async fn request(self) -> Result<NameOwnership> {
+        let conn = self.conn;
+        let (mut sender, receiver) = async_broadcast::broadcast(MAX_QUEUED_CHANGES);
+        sender.set_overflow(true);
+        sender.set_await_active(false);
+
+        let new_ownership = |state| {
+            let ownership = Arc::new(Ownership {
+                name: self.name.clone(),
+                state: Mutex::new(state),
+                changes: sender,
+                released: AtomicBool::new(false),
+            });
+            conn.add_name_ownership(&ownership);
+
+            ownership
+        };
+
+        if !conn.is_bus() {
+            conn.request_name_with_flags(&self.name, self.flags).await?;
+
+            return Ok(NameOwnership {
+                conn: conn.clone(),
+                ownership: new_ownership(NameState::PrimaryOwner),
+                changes: receiver.deactivate(),
+                task: None,
+            });
+        }
+
+        // Subscribe before requesting the name, so no change is missed in between. The rule
+        // matches `NameAcquired`, `NameLost` and `NameOwnerChanged` for the name, in order.
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender("org.freedesktop.DBus")?
+            .interface("org.freedesktop.DBus")?
+            .path("/org/freedesktop/DBus")?
+            .arg(0, self.name.as_str())?
+            .build();
+        let signals = MessageStream::for_match_rule(rule, conn, None).await?;
+        let state = request(conn, &self.name, self.flags).await?;
+        let ownership = new_ownership(state);
+
+        let monitor = Monitor {
+            conn: conn.into(),
+            flags: self.flags,
+            reacquire: self.reacquire,
+            ownership: ownership.clone(),
+        };
+        let task_name = format!("monitor_name_ownership{{name={}}}", self.name);
+        let span = info_span!("monitor_name_ownership", name = %self.name);
+        let task = conn
+            .executor()
+            .spawn(monitor.run(signals).instrument(span), &task_name);
+
+        Ok(NameOwnership {
+            conn: conn.clone(),
+            ownership,
+            changes: receiver.deactivate(),
+            task: Some(task),
+        })
+    }
+}
+
+/// A [`stream::Stream`] of [`NameState`] changes.
+///
+/// Use [`NameOwnership::receive_state_changes`] to create an instance of this type.
+#[derive(Debug)]
+pub struct NameStateStream(async_broadcast::Receiver<NameState>);
+
+impl stream::Stream for NameStateStream {
+    type Item = NameState;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.poll_next(cx)
+    }
+}
+
+/// The task keeping the state of a [`NameOwnership`] up to date.
+struct Monitor {
+    conn: WeakConnection,
+    flags: BitFlags<RequestNameFlags>,
+    reacquire: bool,
+    ownership: Arc<Ownership>,
+}
+
+impl Monitor {
+    async fn run(self, mut signals: MessageStream) {
+        while let Some(msg) = signals.next().await {
+            let Some(conn) = self.conn.upgrade() else {
+                break;
+            };
+            if self.ownership.is_released() {
+                break;
+            }
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(Error::Reconnected) => {
+                    // The name might have been lost while disconnected.
+                    if self.reacquire || self.state() != NameState::Lost {
+                        self.request(&conn).await;
+                    }
+
+                    continue;
+                }
+                Err(e) => {
+                    debug!("Error receiving name ownership signals: {e}");
+
+                    continue;
+                }
+            };
+
+            let header = msg.header();
+            match header.member().map(|m| m.as_str()) {
+                Some("NameAcquired") => self.set_state(NameState::PrimaryOwner).await,
+                Some("NameLost") => {
+                    // The bus puts the previous owner in the queue, unless it asked not to be.
+                    let state = if self.flags.contains(RequestNameFlags::DoNotQueue) {
+                        NameState::Lost
+                    } else {
+                        NameState::InQueue
+                    };
+                    self.set_state(state).await;
+                }
+                Some("NameOwnerChanged") if self.reacquire && self.state() == NameState::Lost => {
+                    let released = NameOwnerChanged::from_message(msg.clone())
+                        .map(|signal| signal.args().map(|args| args.new_owner().is_none()));
+                    match released {
+                        Some(Ok(true)) => self.request(&conn).await,
+                        Some(Ok(false)) | None => (),
+                        Some(Err(e)) => debug!("Failed to parse `NameOwnerChanged` signal: {e}"),
+                    }
+                }
+                _ => (),
+            }
+        }
+        trace!(
+            "Stopped monitoring the ownership of name `{}`",
+            self.ownership.name
+        );
+    }
+
+    fn state(&self) -> NameState {
+        self.ownership.state()
+    }
+
+    async fn set_state(&self, state: NameState) {
+        self.ownership.set_state(state).await
+    }
+
+    async fn request(&self, conn: &Connection) {
+        if self.ownership.is_released() {
+            return;
+        }
+        match request(conn, &self.ownership.name, self.flags).await {
+            Ok(state) => self.set_state(state).await,
+            Err(e) => warn!(
+                "Failed to request name `{}` again: {e}",
+                self.ownership.name
+            ),
+        }
+    }
+}
+
+async fn request(
+    conn: &Connection,
+    name: &WellKnownName<'_>,
+    flags: BitFlags<RequestNameFlags>,
+) -> Result<NameState> {
+    match conn.request_name_with_flags(name, flags).await {
+        Ok(RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner) => {
+            Ok(NameState::PrimaryOwner)
+        }
+        Ok(RequestNameReply::InQueue) => Ok(NameState::InQueue),
+        Ok(RequestNameReply::Exists) | Err(Error::NameTaken) => Ok(NameState::Lost),
+        Err(e) => Err(e),
+    }
+}
+
+async fn release(conn: &Connection, name: &WellKnownName<'_>) -> Result<bool> {
+    let released = conn.release_name(name).await?;
+    if released || !conn.is_bus() {
+        return Ok(released);
+    }
+
+    // The connection stops keeping track of a name once it's lost, even if the name is acquired
+    // again later from the queue.
+    DBusProxy::new(conn)
+        .await?
+        .release_name(name.clone())
+        .await
+        .map(|reply| reply == ReleaseNameReply::Released)
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use enumflags2::BitFlags;
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::NameState;
+    use crate::{Result, connection, fdo::RequestNameFlags, utils::block_on};
+
+    #[test]
+    #[timeout(15000)]
+    fn name_ownership() {
+        block_on(test_name_ownership()).unwrap();
+    }
+
+    async fn test_name_ownership() -> Result<()> {
+        let name = "org.zbus.NameOwnershipTest";
+        let conn1 = connection::Builder::session()?.build().await?;
+        let ownership1 = conn1
+            .name_ownership(name)?
+            .flags(RequestNameFlags::AllowReplacement | RequestNameFlags::DoNotQueue)
+            .reacquire(true)
+            .request()
+            .await?;
+        assert_eq!(ownership1.state(), NameState::PrimaryOwner);
+        let mut changes1 = ownership1.receive_state_changes();
+
+        let conn2 = connection::Builder::session()?.build().await?;
+        let ownership2 = conn2
+            .name_ownership(name)?
+            .flags(BitFlags::empty())
+            .request()
+            .await?;
+        assert_eq!(ownership2.state(), NameState::InQueue);
+        let mut changes2 = ownership2.receive_state_changes();
+
+        // Another peer takes the name over.
+        let conn3 = connection::Builder::session()?.build().await?;
+        conn3
+            .request_name_with_flags(
+                name,
+                RequestNameFlags::ReplaceExisting | RequestNameFlags::DoNotQueue,
+            )
+            .await?;
+        assert_eq!(changes1.next().await.unwrap(), NameState::Lost);
+        assert_eq!(ownership1.state(), NameState::Lost);
+
+        // Once it releases the name, it goes to the next in the queue.
+        assert!(conn3.release_name(name).await?);
+        assert_eq!(changes2.next().await.unwrap(), NameState::PrimaryOwner);
+        assert_eq!(ownership2.state(), NameState::PrimaryOwner);
+
+        // Dropping the handle releases the name, which is then requested again by the first one.
+        drop(ownership2);
+        assert_eq!(changes1.next().await.unwrap(), NameState::PrimaryOwner);
+        assert_eq!(ownership1.state(), NameState::PrimaryOwner);
+
+        assert!(ownership1.release().await?);
+        assert!(!conn1.release_name(name).await?);
+
+        Ok(())
+    }
+}
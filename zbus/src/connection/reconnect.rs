@@ -0,0 +1,335 @@
+//! Automatic reconnection of bus connections.
+
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::{
+    Address, Connection, Error, Message, Result, abstractions::timeout::sleep, message::Type,
+};
+
+use super::{AuthMechanism, Authenticated, WeakConnection, builder};
+
+/// The policy for automatically reconnecting to the bus, when the connection is lost.
+///
+/// Reconnection attempts are made with an exponential backoff: the delay before the first attempt
+/// is [`ReconnectPolicy::initial_delay`] and it's doubled after each failed attempt, up to
+/// [`ReconnectPolicy::max_delay`].
+///
+/// Use [`crate::connection::Builder::auto_reconnect`] to enable automatic reconnection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    /// A policy with an initial delay of 100 milliseconds, a maximum delay of 10 seconds and no
+    /// limit on the number of attempts.
+    pub fn new() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            max_attempts: None,
+        }
+    }
+
+    /// Set the delay before the first reconnection attempt.
+    #[must_use]
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+
+        self
+    }
+
+    /// Set the maximum delay between two reconnection attempts.
+    #[must_use]
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+
+        self
+    }
+
+    /// Give up after the given number of failed reconnection attempts.
+    ///
+    /// The connection is then closed, as it is when automatic reconnection is not enabled.
+    #[must_use]
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+
+        self
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What's needed to reconnect a connection.
+#[derive(Clone, Debug)]
+pub(crate) struct Reconnect {
+    pub(crate) policy: ReconnectPolicy,
+    pub(crate) address: Address,
+    pub(crate) auth_mechanism: Option<AuthMechanism>,
+    pub(crate) user_id: Option<u32>,
+}
+
+impl Reconnect {
+    async fn connect(self) -> Result<Authenticated> {
+        let guid = self.address.guid().map(|guid| guid.to_owned().into());
+        let socket = builder::connect_address(self.address).await?;
+
+        Authenticated::client(socket, guid, self.auth_mechanism, true, self.user_id).await
+    }
+}
+
+/// Re-establish the connection, after its socket reader failed with `error`.
+///
+/// On success, the new socket is set up for writing and the rest is returned, for the socket reader
+/// to carry on with. The streams are told about the reconnection right away. The state of the
+/// connection is then restored in a separate task, as that requires the socket to be read.
+///
+/// Returns `None` if the connection is gone, it was closed or all the attempts failed.
+pub(super) async fn reconnect(conn: &WeakConnection, error: &Error) -> Option<Authenticated> {
+    warn!("Connection lost: {error}. Reconnecting..");
+
+    let mut attempt = 0;
+    let mut auth = loop {
+        // Don't keep the connection alive while waiting.
+        let (reconnect, delay) = {
+            let conn = conn.upgrade()?;
+            if conn.inner.is_closed() {
+                return None;
+            }
+            let reconnect = conn.inner.reconnect.clone()?;
+            if reconnect
+                .policy
+                .max_attempts
+                .is_some_and(|max| attempt >= max)
+            {
+                warn!("Giving up reconnecting after {attempt} attempts");
+
+                return None;
+            }
+            let delay = reconnect.policy.delay(attempt);
+
+            (reconnect, delay)
+        };
+        sleep(delay).await;
+        attempt += 1;
+
+        match reconnect.connect().await {
+            Ok(auth) => break auth,
+            Err(e) => debug!("Reconnection attempt {attempt} failed: {e}"),
+        }
+    };
+
+    let conn = conn.upgrade()?;
+    // This leaves the old write half in `auth`, for the caller to drop.
+    std::mem::swap(
+        &mut *conn.inner.socket_write.lock().await,
+        &mut auth.socket_write,
+    );
+    conn.inner.current_server_guid.set(auth.server_guid.clone());
+    if let Some(unique_name) = auth.unique_name.take() {
+        info!("Reconnected as `{unique_name}`");
+        conn.inner.current_unique_name.set(unique_name);
+    }
+
+    // Let all the streams know, before anything is received from the new socket.
+    let senders = conn.inner.msg_senders.lock().await;
+    for sender in senders.values() {
+        let _ = sender
+            .broadcast_direct(Err::<Message, _>(Error::Reconnected))
+            .await;
+    }
+    drop(senders);
+
+    conn.executor()
+        .spawn(restore(conn.clone()), "restore connection state")
+        .detach();
+
+    Some(auth)
+}
+
+/// Restore the state of the connection on the bus, after reconnecting.
+async fn restore(conn: Connection) {
+    // Match rules.
+    let rules: Vec<_> = conn
+        .inner
+        .subscriptions
+        .lock()
+        .await
+        .keys()
+        .filter(|rule| rule.msg_type().unwrap_or(Type::Signal) == Type::Signal)
+        .cloned()
+        .collect();
+    for rule in rules {
+        let res = conn
+            .call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "AddMatch",
+                &rule,
+            )
+            .await;
+        if let Err(e) = res {
+            warn!("Failed to restore match rule `{}`: {e}", *rule);
+        }
+    }
+
+    // Names. Those we lost in the meantime won't be requested again.
+    let names: Vec<_> = conn
+        .inner
+        .registered_names
+        .lock()
+        .await
+        .drain()
+        .map(|(name, (flags, _))| (name, flags))
+        .collect();
+    for (name, flags) in names {
+        if let Err(e) = conn.request_name_with_flags(&name, flags).await {
+            warn!("Failed to request name `{name}` again: {e}");
+        }
+    }
+}
+
+#[cfg(all(test, unix, feature = "bus-impl", not(feature = "tokio")))]
+mod tests {
+    use futures_util::{
+        StreamExt,
+        future::{Either, select},
+    };
+    use ntest::timeout;
+    use std::{future::Future, pin::pin, time::Duration};
+    use test_log::test;
+
+    use super::ReconnectPolicy;
+    use crate::{
+        Error, MessageStream, Result, bus::Bus, connection, fdo::DBusProxy, interface,
+        object_server::SignalEmitter, proxy, utils::block_on,
+    };
+
+    struct Greeter;
+
+    #[interface(name = "org.zbus.ReconnectTest")]
+    impl Greeter {
+        fn hello(&self, name: &str) -> String {
+            format!("Hello {name}!")
+        }
+    }
+
+    #[proxy(
+        interface = "org.zbus.ReconnectTest",
+        default_service = "org.zbus.ReconnectTest",
+        default_path = "/org/zbus/ReconnectTest"
+    )]
+    trait ReconnectTest {
+        fn hello(&self, name: &str) -> Result<String>;
+
+        #[zbus(signal)]
+        fn greeted(&self, name: &str) -> Result<()>;
+    }
+
+    // Run `bus` while `test` runs.
+    async fn with_bus<F, T>(bus: Bus, test: F) -> T
+    where
+        F: Future<Output = Result<T>>,
+    {
+        match select(pin!(bus.run()), pin!(test)).await {
+            Either::Left((res, _)) => panic!("bus stopped unexpectedly: {res:?}"),
+            Either::Right((res, _)) => res.unwrap(),
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn reconnect() {
+        block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let address = format!("unix:path={}", dir.path().join("bus").display());
+
+            let bus = Bus::for_address(address.as_str()).await.unwrap();
+            let (service, stream, old_guid) = with_bus(bus, async {
+                let service = connection::Builder::address(address.as_str())?
+                    .auto_reconnect(ReconnectPolicy::new().initial_delay(Duration::from_millis(10)))
+                    .serve_at("/org/zbus/ReconnectTest", Greeter)?
+                    .name("org.zbus.ReconnectTest")?
+                    .build()
+                    .await?;
+                let stream = MessageStream::from(&service);
+                let old_guid = service.current_server_guid();
+
+                Ok((service, stream, old_guid))
+            })
+            .await;
+            // Dropping the bus disconnects all its clients.
+
+            let bus = Bus::for_address(address.as_str()).await.unwrap();
+            with_bus(bus, test_reconnect(&address, service, stream, old_guid)).await;
+        });
+    }
+
+    async fn test_reconnect(
+        address: &str,
+        service: connection::Connection,
+        mut stream: MessageStream,
+        old_guid: crate::OwnedGuid,
+    ) -> Result<()> {
+        // The stream doesn't end but tells us about the reconnection.
+        loop {
+            match stream.next().await.unwrap() {
+                Err(Error::Reconnected) => break,
+                Err(e) => panic!("unexpected error: {e}"),
+                Ok(_) => (),
+            }
+        }
+        assert_ne!(service.current_server_guid(), old_guid);
+        assert_eq!(service.server_guid(), &old_guid);
+
+        let client = connection::Builder::address(address)?.build().await?;
+        let dbus = DBusProxy::new(&client).await?;
+        // Name requests are sent in a separate task.
+        let mut owner_changed = dbus
+            .receive_name_owner_changed_with_args(&[(0, "org.zbus.ReconnectTest")])
+            .await?;
+        if !dbus
+            .name_has_owner("org.zbus.ReconnectTest".try_into()?)
+            .await?
+        {
+            owner_changed.next().await.unwrap();
+        }
+        let owner = dbus
+            .get_name_owner("org.zbus.ReconnectTest".try_into()?)
+            .await?;
+        assert_eq!(Some(owner), service.current_unique_name());
+
+        // Objects are still served.
+        let proxy = ReconnectTestProxy::new(&client).await?;
+        assert_eq!(proxy.hello("zbus").await?, "Hello zbus!");
+
+        // Signal subscriptions are restored.
+        let mut greeted = ReconnectTestProxy::builder(&service)
+            .destination(client.unique_name().unwrap())?
+            .build()
+            .await?
+            .receive_greeted()
+            .await?;
+        SignalEmitter::new(&client, "/org/zbus/ReconnectTest")?
+            .emit("org.zbus.ReconnectTest", "Greeted", &("zbus",))
+            .await?;
+        let signal = greeted.next().await.unwrap();
+        assert_eq!(signal.args()?.name, "zbus");
+
+        Ok(())
+    }
+}
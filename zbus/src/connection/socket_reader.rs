@@ -7,7 +7,10 @@ use crate::{
     Executor, Message, OwnedMatchRule, Task, async_lock::Mutex, connection::MsgBroadcaster,
 };
 
-use super::socket::ReadHalf;
+use super::{
+    Connection, WeakConnection, interceptor::Interceptors, reconnect, socket::ReadHalf,
+    stats::Counters,
+};
 
 #[derive(Debug)]
 pub(crate) struct SocketReader {
@@ -18,24 +21,32 @@ pub(crate) struct SocketReader {
     already_received_fds: Vec<std::os::fd::OwnedFd>,
     prev_seq: u64,
     activity_event: Arc<Event>,
+    // Set if the connection is to be re-established when the socket fails.
+    reconnect: Option<WeakConnection>,
+    interceptors: Interceptors,
+    stats: Arc<Counters>,
 }
 
 impl SocketReader {
     pub fn new(
         socket: Box<dyn ReadHalf>,
-        senders: Arc<Mutex<HashMap<Option<OwnedMatchRule>, MsgBroadcaster>>>,
         already_received_bytes: Vec<u8>,
         #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
-        activity_event: Arc<Event>,
+        conn: &Connection,
     ) -> Self {
+        let inner = &conn.inner;
+
         Self {
             socket,
-            senders,
+            senders: inner.msg_senders.clone(),
             already_received_bytes,
             #[cfg(unix)]
             already_received_fds,
             prev_seq: 0,
-            activity_event,
+            activity_event: inner.activity_event.clone(),
+            reconnect: inner.reconnect.as_ref().map(|_| WeakConnection::from(conn)),
+            interceptors: inner.interceptors.clone(),
+            stats: inner.stats.clone(),
         }
     }
 
@@ -48,12 +59,41 @@ impl SocketReader {
     async fn receive_msg(mut self) {
         loop {
             trace!("Waiting for message on the socket..");
-            let msg = self.read_socket().await;
-            match &msg {
-                Ok(msg) => trace!("Message received on the socket: {:?}", msg),
-                Err(e) => trace!("Error reading from the socket: {:?}", e),
+            let msg = match self.read_socket().await {
+                Ok(msg) => {
+                    trace!("Message received on the socket: {:?}", msg);
+                    self.stats.message_received(&msg);
+                    match self.interceptors.incoming(msg) {
+                        Some(msg) => Ok(msg),
+                        None => {
+                            trace!("Incoming message dropped by an interceptor");
+
+                            continue;
+                        }
+                    }
+                }
+                Err(e) => {
+                    trace!("Error reading from the socket: {:?}", e);
+
+                    Err(e)
+                }
             };
 
+            // On success, the streams are notified about the reconnection instead of the error.
+            if let (Err(e), Some(conn)) = (&msg, &self.reconnect) {
+                if let Some(mut auth) = reconnect::reconnect(conn, e).await {
+                    // SAFETY: `Authenticated` is always built with this field set to `Some`.
+                    self.socket = auth.socket_read.take().unwrap();
+                    self.already_received_bytes = auth.already_received_bytes;
+                    #[cfg(unix)]
+                    {
+                        self.already_received_fds = auth.already_received_fds;
+                    }
+
+                    continue;
+                }
+            }
+
             let mut senders = self.senders.lock().await;
             for (rule, sender) in &*senders {
                 if let Ok(msg) = &msg {
@@ -70,6 +110,9 @@ impl SocketReader {
                     }
                 }
 
+                if sender.is_full() {
+                    self.stats.full_queue_wait();
+                }
                 if let Err(e) = sender.broadcast_direct(msg.clone()).await {
                     // An error would be due to either of these:
                     //
@@ -0,0 +1,383 @@
+//! Statistics about the traffic on a connection.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use zbus_names::{InterfaceName, OwnedInterfaceName};
+
+use crate::Message;
+
+/// The upper bounds of the buckets of a [`LatencyHistogram`], except for the last one.
+const LATENCY_BUCKETS: [Duration; 6] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// Statistics about the traffic on a [`Connection`].
+///
+/// This is a snapshot of the counters maintained by the connection, returned by
+/// [`Connection::stats`]. All the counters start at zero when the connection is created.
+///
+/// [`Connection`]: crate::Connection
+/// [`Connection::stats`]: crate::Connection::stats
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    incoming_messages: u64,
+    incoming_bytes: u64,
+    incoming_fds: u64,
+    outgoing_messages: u64,
+    outgoing_bytes: u64,
+    outgoing_fds: u64,
+    pending_method_calls: u64,
+    full_queue_waits: u64,
+    match_rules: u64,
+    dispatch_latencies: HashMap<OwnedInterfaceName, LatencyHistogram>,
+}
+
+impl Stats {
+    /// The number of messages received.
+    pub fn incoming_messages(&self) -> u64 {
+        self.incoming_messages
+    }
+
+    /// The number of bytes received, as part of messages.
+    pub fn incoming_bytes(&self) -> u64 {
+        self.incoming_bytes
+    }
+
+    /// The number of file descriptors received.
+    pub fn incoming_fds(&self) -> u64 {
+        self.incoming_fds
+    }
+
+    /// The number of messages sent.
+    pub fn outgoing_messages(&self) -> u64 {
+        self.outgoing_messages
+    }
+
+    /// The number of bytes sent, as part of messages.
+    pub fn outgoing_bytes(&self) -> u64 {
+        self.outgoing_bytes
+    }
+
+    /// The number of file descriptors sent.
+    pub fn outgoing_fds(&self) -> u64 {
+        self.outgoing_fds
+    }
+
+    /// The number of method calls currently awaiting a reply.
+    pub fn pending_method_calls(&self) -> u64 {
+        self.pending_method_calls
+    }
+
+    /// The number of times a received message couldn't be queued right away, as the queue of a
+    /// message stream had reached its maximum capacity.
+    ///
+    /// Messages aren't dropped when a queue is full. Instead, the reception of messages is paused
+    /// until room is made in the queue (see [`Connection::set_max_queued`]). A growing value here
+    /// hence means that some streams aren't being polled often enough.
+    ///
+    /// [`Connection::set_max_queued`]: crate::Connection::set_max_queued
+    pub fn full_queue_waits(&self) -> u64 {
+        self.full_queue_waits
+    }
+
+    /// The number of match rules currently in use on the connection.
+    pub fn match_rules(&self) -> u64 {
+        self.match_rules
+    }
+
+    /// The latencies of the method calls dispatched by the [`ObjectServer`], per interface.
+    ///
+    /// [`ObjectServer`]: crate::ObjectServer
+    pub fn dispatch_latencies(&self) -> &HashMap<OwnedInterfaceName, LatencyHistogram> {
+        &self.dispatch_latencies
+    }
+}
+
+/// A histogram of latencies.
+///
+/// The latencies are counted in buckets with exponentially growing upper bounds, from 10
+/// microseconds to 1 second, and a last bucket for all the higher latencies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    total: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// The buckets, as pairs of their (inclusive) upper bound and their count.
+    ///
+    /// The upper bound of the last bucket is `None`.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .zip(self.buckets.iter().copied())
+    }
+
+    /// The number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// The sum of all the recorded latencies.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// The highest recorded latency.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// The mean of the recorded latencies, if any.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count()).unwrap_or(u32::MAX);
+
+        (count > 0).then(|| self.total / count)
+    }
+
+    fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.total = self.total.saturating_add(latency);
+        self.max = self.max.max(latency);
+    }
+}
+
+/// The counters behind [`Stats`].
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    incoming_messages: AtomicU64,
+    incoming_bytes: AtomicU64,
+    incoming_fds: AtomicU64,
+    outgoing_messages: AtomicU64,
+    outgoing_bytes: AtomicU64,
+    outgoing_fds: AtomicU64,
+    pending_method_calls: AtomicU64,
+    full_queue_waits: AtomicU64,
+    match_rules: AtomicU64,
+    dispatch_latencies: std::sync::Mutex<HashMap<OwnedInterfaceName, LatencyHistogram>>,
+}
+
+impl Counters {
+    pub(crate) fn snapshot(&self) -> Stats {
+        Stats {
+            incoming_messages: self.incoming_messages.load(Ordering::Relaxed),
+            incoming_bytes: self.incoming_bytes.load(Ordering::Relaxed),
+            incoming_fds: self.incoming_fds.load(Ordering::Relaxed),
+            outgoing_messages: self.outgoing_messages.load(Ordering::Relaxed),
+            outgoing_bytes: self.outgoing_bytes.load(Ordering::Relaxed),
+            outgoing_fds: self.outgoing_fds.load(Ordering::Relaxed),
+            pending_method_calls: self.pending_method_calls.load(Ordering::Relaxed),
+            full_queue_waits: self.full_queue_waits.load(Ordering::Relaxed),
+            match_rules: self.match_rules.load(Ordering::Relaxed),
+            dispatch_latencies: self
+                .dispatch_latencies
+                .lock()
+                .expect("lock poisoned")
+                .clone(),
+        }
+    }
+
+    pub(crate) fn message_received(&self, msg: &Message) {
+        self.incoming_messages.fetch_add(1, Ordering::Relaxed);
+        self.incoming_bytes
+            .fetch_add(msg.data().len() as u64, Ordering::Relaxed);
+        #[cfg(unix)]
+        self.incoming_fds
+            .fetch_add(msg.data().fds().len() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_sent(&self, msg: &Message) {
+        self.outgoing_messages.fetch_add(1, Ordering::Relaxed);
+        self.outgoing_bytes
+            .fetch_add(msg.data().len() as u64, Ordering::Relaxed);
+        #[cfg(unix)]
+        self.outgoing_fds
+            .fetch_add(msg.data().fds().len() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn full_queue_wait(&self) {
+        self.full_queue_waits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn match_rule_added(&self) {
+        self.match_rules.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn match_rule_removed(&self) {
+        self.match_rules.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn method_dispatched(&self, iface: &InterfaceName<'_>, latency: Duration) {
+        let mut latencies = self.dispatch_latencies.lock().expect("lock poisoned");
+        match latencies.get_mut(iface.as_str()) {
+            Some(histogram) => histogram.record(latency),
+            None => {
+                let mut histogram = LatencyHistogram::default();
+                histogram.record(latency);
+                latencies.insert(iface.to_owned().into(), histogram);
+            }
+        }
+    }
+}
+
+/// Keeps a method call counted as pending, until dropped.
+#[derive(Debug)]
+pub(crate) struct PendingCall(Arc<Counters>);
+
+impl PendingCall {
+    pub(crate) fn new(counters: Arc<Counters>) -> Self {
+        counters
+            .pending_method_calls
+            .fetch_add(1, Ordering::Relaxed);
+
+        Self(counters)
+    }
+}
+
+impl Drop for PendingCall {
+    fn drop(&mut self) {
+        self.0.pending_method_calls.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use std::time::Duration;
+    use test_log::test;
+
+    use crate::{
+        MatchRule, Result, connection,
+        fdo::{DebugStats, StatsProxy},
+        interface, proxy,
+        timeout::sleep,
+        utils::block_on,
+    };
+
+    struct Counter(u32);
+
+    #[interface(name = "org.zbus.StatsTest")]
+    impl Counter {
+        fn increment(&mut self) -> u32 {
+            self.0 += 1;
+
+            self.0
+        }
+    }
+
+    #[proxy(
+        interface = "org.zbus.StatsTest",
+        default_service = "org.zbus.StatsTest",
+        default_path = "/org/zbus/StatsTest"
+    )]
+    trait Counter {
+        fn increment(&self) -> Result<u32>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn stats() {
+        block_on(test_stats()).unwrap();
+    }
+
+    async fn test_stats() -> Result<()> {
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/StatsTest", Counter(0))?
+            .serve_at("/org/zbus/StatsTest", DebugStats::default())?
+            .name("org.zbus.StatsTest")?
+            .build()
+            .await?;
+        let client = connection::Builder::session()?.build().await?;
+
+        let proxy = CounterProxy::new(&client).await?;
+        for _ in 0..3 {
+            proxy.increment().await?;
+        }
+
+        let stats = client.stats();
+        assert!(stats.outgoing_messages() >= 3);
+        assert!(stats.incoming_messages() >= 3);
+        assert!(stats.outgoing_bytes() > 0);
+        assert!(stats.incoming_bytes() > 0);
+        assert_eq!(stats.pending_method_calls(), 0);
+
+        // The latency of a call is only recorded after its reply is sent.
+        let stats = loop {
+            let stats = service.stats();
+            let count = stats
+                .dispatch_latencies()
+                .get("org.zbus.StatsTest")
+                .map(|latencies| latencies.count());
+            if count == Some(3) {
+                break stats;
+            }
+            sleep(Duration::from_millis(10)).await;
+        };
+        let latencies = stats
+            .dispatch_latencies()
+            .get("org.zbus.StatsTest")
+            .unwrap();
+        assert_eq!(latencies.buckets().map(|(_, count)| count).sum::<u64>(), 3);
+        assert!(latencies.max() <= latencies.total());
+        assert!(latencies.mean().unwrap() <= latencies.max());
+
+        // Match rules are counted.
+        let match_rules = service.stats().match_rules();
+        let rule = MatchRule::builder()
+            .msg_type(crate::message::Type::Signal)
+            .interface("org.zbus.StatsTest")?
+            .build();
+        let stream = crate::MessageStream::for_match_rule(rule, &service, None).await?;
+        assert_eq!(service.stats().match_rules(), match_rules + 1);
+
+        // The stats can be scraped over D-Bus.
+        let stats_proxy = StatsProxy::builder(&client)
+            .destination("org.zbus.StatsTest")?
+            .path("/org/zbus/StatsTest")?
+            .build()
+            .await?;
+        let stats = stats_proxy.get_stats().await?;
+        assert_eq!(stats.match_rules(), Some(match_rules as u32 + 1));
+        assert_eq!(stats.bus_names(), Some(1));
+        let conn_stats = stats_proxy
+            .get_connection_stats("org.zbus.StatsTest".try_into()?)
+            .await?;
+        assert_eq!(conn_stats.unique_name(), service.unique_name());
+        assert!(conn_stats.incoming_messages().unwrap() >= 5);
+        assert!(
+            stats_proxy
+                .get_connection_stats(client.unique_name().unwrap().into())
+                .await
+                .is_err()
+        );
+        let rules = stats_proxy.get_all_match_rules().await?;
+        let rules = rules.get(service.unique_name().unwrap()).unwrap();
+        assert!(
+            rules
+                .iter()
+                .any(|r| r.to_string().contains("org.zbus.StatsTest"))
+        );
+        drop(stream);
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use std::{convert::Infallible, error, fmt, io, sync::Arc};
-use zbus_names::{Error as NamesError, InterfaceName, OwnedErrorName};
+use zbus_names::{BusName, Error as NamesError, InterfaceName, OwnedErrorName};
 use zvariant::{Error as VariantError, ObjectPath};
 
 use crate::{
@@ -60,6 +60,18 @@ pub enum Error {
     InvalidSerial,
     /// The given interface already exists at the given path.
     InterfaceExists(InterfaceName<'static>, ObjectPath<'static>),
+    /// No reply to a method call was received in time.
+    MethodTimeout,
+    /// The connection was lost and then re-established.
+    ///
+    /// This is yielded by message streams after an automatic reconnection (see
+    /// [`crate::connection::Builder::auto_reconnect`]). Messages might have been missed while the
+    /// connection was down.
+    Reconnected,
+    /// The given name didn't get an owner in time.
+    ///
+    /// See [`crate::Proxy::wait_for_owner`].
+    OwnerTimeout(BusName<'static>),
 }
 
 impl PartialEq for Error {
@@ -85,6 +97,9 @@ impl PartialEq for Error {
             (Error::InputOutput(_), Self::InputOutput(_)) => false,
             (Self::Failure(s1), Self::Failure(s2)) => s1 == s2,
             (Self::InterfaceExists(s1, s2), Self::InterfaceExists(o1, o2)) => s1 == o1 && s2 == o2,
+            (Self::MethodTimeout, Self::MethodTimeout) => true,
+            (Self::Reconnected, Self::Reconnected) => true,
+            (Self::OwnerTimeout(s), Self::OwnerTimeout(o)) => s == o,
             (_, _) => false,
         }
     }
@@ -114,6 +129,9 @@ impl error::Error for Error {
             Error::MissingParameter(_) => None,
             Error::InvalidSerial => None,
             Error::InterfaceExists(_, _) => None,
+            Error::MethodTimeout => None,
+            Error::Reconnected => None,
+            Error::OwnerTimeout(_) => None,
         }
     }
 }
@@ -149,6 +167,9 @@ impl fmt::Display for Error {
             }
             Error::InvalidSerial => write!(f, "Serial number in the message header is 0"),
             Error::InterfaceExists(i, p) => write!(f, "Interface `{i}` already exists at `{p}`"),
+            Error::MethodTimeout => write!(f, "Method call timed out"),
+            Error::Reconnected => write!(f, "Connection was re-established"),
+            Error::OwnerTimeout(n) => write!(f, "Timed out waiting for `{n}` to have an owner"),
         }
     }
 }
@@ -182,6 +203,9 @@ impl Error {
             Error::MissingParameter(_) => Some("A required parameter is missing"),
             Error::InvalidSerial => Some("serial number in the message header is 0"),
             Error::InterfaceExists(_, _) => Some("interface already exists"),
+            Error::MethodTimeout => Some("method call timed out"),
+            Error::Reconnected => Some("connection re-established"),
+            Error::OwnerTimeout(_) => Some("timed out waiting for name owner"),
         }
     }
 }
@@ -212,6 +236,9 @@ impl Clone for Error {
             Error::MissingParameter(p) => Error::MissingParameter(p),
             Error::InvalidSerial => Error::InvalidSerial,
             Error::InterfaceExists(i, p) => Error::InterfaceExists(i.clone(), p.clone()),
+            Error::MethodTimeout => Error::MethodTimeout,
+            Error::Reconnected => Error::Reconnected,
+            Error::OwnerTimeout(n) => Error::OwnerTimeout(n.clone()),
         }
     }
 }
@@ -3,7 +3,7 @@
 //! The D-Bus specification defines the message bus messages and some standard interfaces that may
 //! be useful across various D-Bus applications. This module provides their proxy.
 
-use super::{Error, Result};
+use super::Result;
 use crate::{ObjectServer, interface, message::Header};
 
 /// Service-side implementation for the `org.freedesktop.DBus.Introspectable` interface.
@@ -25,11 +25,9 @@ impl Introspectable {
         #[zbus(header)] header: Header<'_>,
     ) -> Result<String> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
+        let enumerated = server.enumerate(path, false).await?;
         let root = server.root().read().await;
-        let node = root
-            .get_child(path)
-            .ok_or_else(|| Error::UnknownObject(format!("Unknown object '{path}'")))?;
 
-        Ok(node.introspect().await)
+        root.resolve(path).introspect(&enumerated).await
     }
 }
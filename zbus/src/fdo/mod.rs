@@ -22,6 +22,9 @@ pub use object_manager::{
     ObjectManagerProxy,
 };
 
+mod object_manager_client;
+pub use object_manager_client::{ObjectEvent, ObjectEventStream, ObjectManagerClient};
+
 pub(crate) mod peer;
 pub(crate) use peer::Peer;
 pub use peer::PeerProxy;
@@ -32,7 +35,7 @@ pub use properties::{
 };
 
 pub(crate) mod stats;
-pub use stats::StatsProxy;
+pub use stats::{DebugStats, StatsProxy};
 
 #[cfg(test)]
 mod tests {
@@ -7,7 +7,7 @@ use std::{borrow::Cow, collections::HashMap};
 use zbus_names::{InterfaceName, OwnedInterfaceName};
 use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
 
-use super::{Error, Result};
+use super::Result;
 use crate::{Connection, ObjectServer, interface, message::Header, object_server::SignalEmitter};
 
 /// The type returned by the [`ObjectManagerProxy::get_managed_objects`] method.
@@ -52,12 +52,12 @@ impl ObjectManager {
         #[zbus(header)] header: Header<'_>,
     ) -> Result<ManagedObjects> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
+        let enumerated = server.enumerate(path, true).await?;
         let root = server.root().read().await;
-        let node = root
-            .get_child(path)
-            .ok_or_else(|| Error::UnknownObject(format!("Unknown object '{path}'")))?;
 
-        node.get_managed_objects(server, connection).await
+        root.resolve(path)
+            .get_managed_objects(server, connection, &enumerated)
+            .await
     }
 
     /// This signal is emitted when either a new object is added or when an existing object gains
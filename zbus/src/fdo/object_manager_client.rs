@@ -0,0 +1,840 @@
+//! Client-side cache of the objects managed by an object manager.
+
+use futures_core::stream;
+use futures_lite::{StreamExt, stream::Boxed};
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tracing::{debug, trace};
+use zbus_names::{BusName, InterfaceName, OwnedInterfaceName, OwnedUniqueName};
+use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use super::{
+    DBusProxy, InterfacesAdded, InterfacesRemoved, ManagedObjects, NameOwnerChanged,
+    PropertiesChanged,
+};
+use crate::{
+    Connection, Error, MatchRule, Message, MessageStream, Proxy, Result, Task,
+    message::{Sequence, Type},
+    proxy::{self, PropertiesCache},
+};
+
+/// The maximum number of queued [`ObjectEvent`]s, before the oldest ones are dropped.
+const MAX_QUEUED_EVENTS: usize = 64;
+
+/// A change to the objects managed by an object manager.
+///
+/// See [`ObjectManagerClient::receive_object_events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ObjectEvent {
+    /// Interfaces were added to an object, which is a new object if it had no interfaces before.
+    InterfacesAdded {
+        /// The path of the object.
+        path: OwnedObjectPath,
+        /// The added interfaces.
+        interfaces: Vec<OwnedInterfaceName>,
+    },
+    /// Interfaces were removed from an object, which is gone if it has no interfaces left.
+    InterfacesRemoved {
+        /// The path of the object.
+        path: OwnedObjectPath,
+        /// The removed interfaces.
+        interfaces: Vec<OwnedInterfaceName>,
+    },
+}
+
+/// Client-side cache of the objects managed by an [Object Manager][om].
+///
+/// The objects, their interfaces and the values of their properties are fetched once, when the
+/// client is created, and then kept up to date by listening to the `InterfacesAdded`,
+/// `InterfacesRemoved` and `PropertiesChanged` signals.
+///
+/// The owner of the destination is tracked as well. When it vanishes, all the objects are removed
+/// from the cache. When the name gets a new owner, the objects are fetched again from it. In both
+/// cases, the corresponding [`ObjectEvent`]s are emitted. Proxies built before the owner changed
+/// aren't kept up to date anymore.
+///
+/// The typed proxies returned by [`ObjectManagerClient::proxy`] and
+/// [`ObjectManagerClient::proxies`] are built without any round trip to the peer and share the
+/// properties cache of the client.
+///
+/// # Example
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use futures_util::StreamExt;
+/// use zbus::{Connection, Result, fdo::{ObjectEvent, ObjectManagerClient}, proxy};
+///
+/// #[proxy(interface = "org.bluez.Adapter1", default_service = "org.bluez")]
+/// trait Adapter1 {
+///     #[zbus(property)]
+///     fn name(&self) -> Result<String>;
+/// }
+///
+/// let conn = Connection::system().await?;
+/// let client = ObjectManagerClient::new(&conn, "org.bluez", "/").await?;
+/// let mut events = client.receive_object_events();
+///
+/// for adapter in client.proxies::<Adapter1Proxy>()? {
+///     // No round trip here, the value is already cached.
+///     println!("{}: {}", adapter.inner().path(), adapter.name().await?);
+/// }
+///
+/// while let Some(event) = events.next().await {
+///     if let ObjectEvent::InterfacesAdded { path, .. } = event {
+///         if let Some(adapter) = client.proxy::<Adapter1Proxy>(&path)? {
+///             println!("New adapter: {}", adapter.name().await?);
+///         }
+///     }
+/// }
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [om]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-objectmanager
+#[derive(Clone, Debug)]
+pub struct ObjectManagerClient(Arc<ClientInner>);
+
+#[derive(Debug)]
+struct ClientInner {
+    conn: Connection,
+    destination: BusName<'static>,
+    path: ObjectPath<'static>,
+    objects: Arc<Mutex<Objects>>,
+    events: async_broadcast::InactiveReceiver<ObjectEvent>,
+    _task: Task<()>,
+}
+
+/// The cached objects, with the properties of each of their interfaces.
+type Objects = HashMap<OwnedObjectPath, HashMap<OwnedInterfaceName, Arc<PropertiesCache>>>;
+
+impl ObjectManagerClient {
+    /// Create a client for the object manager at `path` on `destination`.
+    ///
+    /// This fetches all the managed objects from the object manager.
+    pub async fn new<D, P>(conn: &Connection, destination: D, path: P) -> Result<Self>
+    where
+        D: TryInto<BusName<'static>>,
+        D::Error: Into<Error>,
+        P: TryInto<ObjectPath<'static>>,
+        P::Error: Into<Error>,
+    {
+        let destination = destination.try_into().map_err(Into::into)?;
+        let path = path.try_into().map_err(Into::into)?;
+
+        // Subscribe before fetching the objects, so no change is missed in between.
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender(destination.clone())?
+            .path_namespace(path.clone())?
+            .build();
+        let signals = MessageStream::for_match_rule(rule, conn, None).await?;
+        let (owner, owner_changes) = if conn.is_bus() {
+            let dbus = DBusProxy::new(conn).await?;
+            let owner_changes = dbus
+                .receive_name_owner_changed_with_args(&[(0, destination.as_str())])
+                .await?
+                .map(Update::OwnerChanged)
+                .boxed();
+            let owner = match &destination {
+                BusName::Unique(name) => Some(name.to_owned().into()),
+                BusName::WellKnown(_) => dbus.get_name_owner(destination.clone()).await.ok(),
+            };
+
+            (owner, owner_changes)
+        } else {
+            (None, futures_lite::stream::pending().boxed())
+        };
+
+        let (objects, since) = fetch_objects(conn, &destination, &path).await?;
+        let objects = Arc::new(Mutex::new(objects));
+
+        let (mut sender, receiver) = async_broadcast::broadcast(MAX_QUEUED_EVENTS);
+        sender.set_overflow(true);
+        sender.set_await_active(false);
+        let updater = Updater {
+            conn: conn.clone(),
+            destination: destination.clone(),
+            path: path.clone(),
+            owner,
+            objects: objects.clone(),
+            events: sender,
+        };
+        let task = conn.executor().spawn(
+            updater.run(signals.map(Update::Signal).or(owner_changes).boxed(), since),
+            "object manager client",
+        );
+
+        Ok(Self(Arc::new(ClientInner {
+            conn: conn.clone(),
+            destination,
+            path,
+            objects,
+            events: receiver.deactivate(),
+            _task: task,
+        })))
+    }
+
+    /// The connection of the client.
+    pub fn connection(&self) -> &Connection {
+        &self.0.conn
+    }
+
+    /// The peer the object manager is on.
+    pub fn destination(&self) -> &BusName<'static> {
+        &self.0.destination
+    }
+
+    /// The path of the object manager.
+    pub fn path(&self) -> &ObjectPath<'static> {
+        &self.0.path
+    }
+
+    /// A snapshot of all the managed objects, with their interfaces and properties.
+    pub fn managed_objects(&self) -> ManagedObjects {
+        self.objects()
+            .iter()
+            .map(|(path, interfaces)| {
+                let interfaces = interfaces
+                    .iter()
+                    .map(|(name, cache)| (name.clone(), cache.values()))
+                    .collect();
+
+                (path.clone(), interfaces)
+            })
+            .collect()
+    }
+
+    /// The paths of the managed objects implementing `interface`.
+    pub fn paths_with_interface(&self, interface: &InterfaceName<'_>) -> Vec<OwnedObjectPath> {
+        self.objects()
+            .iter()
+            .filter(|(_, interfaces)| interfaces.contains_key(interface))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// The cached properties of `interface` on the object at `path`.
+    ///
+    /// Returns `None` if there is no such object or it doesn't implement `interface`.
+    pub fn properties(
+        &self,
+        path: &ObjectPath<'_>,
+        interface: &InterfaceName<'_>,
+    ) -> Option<HashMap<String, OwnedValue>> {
+        self.objects()
+            .get(path)
+            .and_then(|interfaces| interfaces.get(interface))
+            .map(|cache| cache.values())
+    }
+
+    /// A typed proxy for the object at `path`, built from the cache.
+    ///
+    /// Returns `None` if there is no such object or it doesn't implement the interface of the
+    /// proxy.
+    pub fn proxy<T>(&self, path: &ObjectPath<'_>) -> Result<Option<T>>
+    where
+        T: From<Proxy<'static>> + proxy::Defaults,
+    {
+        let interface = proxy_interface::<T>()?;
+        let cache = self
+            .objects()
+            .get(path)
+            .and_then(|interfaces| interfaces.get(interface))
+            .cloned();
+
+        cache
+            .map(|cache| self.build_proxy(path.to_owned(), cache))
+            .transpose()
+    }
+
+    /// Typed proxies for all the managed objects implementing the interface of the proxy, built
+    /// from the cache.
+    pub fn proxies<T>(&self) -> Result<Vec<T>>
+    where
+        T: From<Proxy<'static>> + proxy::Defaults,
+    {
+        let interface = proxy_interface::<T>()?;
+        let caches: Vec<_> = self
+            .objects()
+            .iter()
+            .filter_map(|(path, interfaces)| {
+                let cache = interfaces.get(interface)?;
+
+                Some((path.clone(), cache.clone()))
+            })
+            .collect();
+
+        caches
+            .into_iter()
+            .map(|(path, cache)| self.build_proxy(path.into(), cache))
+            .collect()
+    }
+
+    /// A stream of the changes to the managed objects.
+    ///
+    /// Only the changes occurring after this call are received. If the events aren't consumed fast
+    /// enough, the oldest ones are dropped; the cache is kept up to date regardless.
+    pub fn receive_object_events(&self) -> ObjectEventStream {
+        ObjectEventStream(self.0.events.activate_cloned())
+    }
+
+    fn objects(&self) -> std::sync::MutexGuard<'_, Objects> {
+        self.0.objects.lock().expect("lock poisoned")
+    }
+
+    fn build_proxy<T>(&self, path: ObjectPath<'static>, cache: Arc<PropertiesCache>) -> Result<T>
+    where
+        T: From<Proxy<'static>> + proxy::Defaults,
+    {
+        let proxy = proxy::Builder::<T>::new(&self.0.conn)
+            .destination(self.0.destination.clone())?
+            .path(path)?
+            .properties_cache(cache)
+            .build_internal()?;
+
+        Ok(proxy.into())
+    }
+}
+
+/// A [`stream::Stream`] of [`ObjectEvent`]s.
+///
+/// Use [`ObjectManagerClient::receive_object_events`] to create an instance of this type.
+#[derive(Debug)]
+pub struct ObjectEventStream(async_broadcast::Receiver<ObjectEvent>);
+
+impl stream::Stream for ObjectEventStream {
+    type Item = ObjectEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.poll_next(cx)
+    }
+}
+
+fn proxy_interface<T>() -> Result<&'static InterfaceName<'static>>
+where
+    T: proxy::Defaults,
+{
+    T::INTERFACE
+        .as_ref()
+        .ok_or(Error::MissingParameter("interface"))
+}
+
+fn add_interfaces(
+    objects: &mut Objects,
+    path: &ObjectPath<'_>,
+    interfaces: &HashMap<InterfaceName<'_>, HashMap<&str, Value<'_>>>,
+) -> Vec<OwnedInterfaceName> {
+    let object = objects.entry(path.to_owned().into()).or_default();
+
+    interfaces
+        .iter()
+        .map(|(interface, properties)| {
+            let interface = OwnedInterfaceName::from(interface.to_owned());
+            object
+                .entry(interface.clone())
+                .or_insert_with(PropertiesCache::new_unmanaged)
+                .update(properties, &[], &interface);
+
+            interface
+        })
+        .collect()
+}
+
+/// Fetch all the objects managed by the object manager at `path` on `destination`.
+///
+/// The position of the reply is returned along with the objects, as the changes received before
+/// are part of the fetched state.
+async fn fetch_objects(
+    conn: &Connection,
+    destination: &BusName<'_>,
+    path: &ObjectPath<'_>,
+) -> Result<(Objects, Sequence)> {
+    let reply = conn
+        .call_method(
+            Some(destination),
+            path,
+            Some("org.freedesktop.DBus.ObjectManager"),
+            "GetManagedObjects",
+            &(),
+        )
+        .await?;
+    let body = reply.body();
+    let managed: HashMap<ObjectPath<'_>, HashMap<InterfaceName<'_>, HashMap<&str, Value<'_>>>> =
+        body.deserialize()?;
+    let mut objects = Objects::new();
+    for (object_path, interfaces) in managed {
+        add_interfaces(&mut objects, &object_path, &interfaces);
+    }
+
+    Ok((objects, reply.recv_position()))
+}
+
+/// An update received by the [`Updater`].
+enum Update {
+    Signal(Result<Message>),
+    OwnerChanged(NameOwnerChanged),
+}
+
+/// Keeps the cached objects up to date.
+struct Updater {
+    conn: Connection,
+    destination: BusName<'static>,
+    path: ObjectPath<'static>,
+    /// The current unique owner of the destination, on a bus.
+    owner: Option<OwnedUniqueName>,
+    objects: Arc<Mutex<Objects>>,
+    events: async_broadcast::Sender<ObjectEvent>,
+}
+
+impl Updater {
+    /// Apply the `updates` received after `since`.
+    async fn run(mut self, mut updates: Boxed<Update>, mut since: Sequence) {
+        while let Some(update) = updates.next().await {
+            let msg = match update {
+                Update::Signal(Ok(msg)) => msg,
+                Update::Signal(Err(e)) => {
+                    debug!("Error receiving object manager signals: {e}");
+
+                    continue;
+                }
+                Update::OwnerChanged(signal) => {
+                    self.owner_changed(signal, &mut since).await;
+
+                    continue;
+                }
+            };
+            if msg.recv_position() <= since {
+                // Already part of the current state.
+                continue;
+            }
+            if self.conn.is_bus() && msg.header().sender() != self.owner.as_deref() {
+                // The match rules of other streams on the connection also route signals here.
+                trace!(
+                    "Ignoring a signal not sent by the owner of `{}`",
+                    self.destination
+                );
+
+                continue;
+            }
+
+            let event = match handle_signal(&msg, &self.path, &self.objects) {
+                Ok(event) => event,
+                Err(e) => {
+                    debug!("Failed to handle signal: {e}");
+
+                    continue;
+                }
+            };
+            if let Some(event) = event {
+                self.emit(event).await;
+            }
+        }
+    }
+
+    /// Drop all the objects of the previous owner, and fetch the ones of the new owner, if any.
+    async fn owner_changed(&mut self, signal: NameOwnerChanged, since: &mut Sequence) {
+        let args = match signal.args() {
+            Ok(args) => args,
+            Err(e) => {
+                debug!("Failed to parse `NameOwnerChanged` signal: {e}");
+
+                return;
+            }
+        };
+        // Signals received before are from the previous owner.
+        *since = (*since).max(signal.message().recv_position());
+        self.owner = args
+            .new_owner()
+            .as_ref()
+            .map(|owner| owner.to_owned().into());
+
+        let removed = std::mem::take(&mut *self.objects.lock().expect("lock poisoned"));
+        for (path, interfaces) in removed {
+            let interfaces = interfaces.into_keys().collect();
+            self.emit(ObjectEvent::InterfacesRemoved { path, interfaces })
+                .await;
+        }
+
+        let Some(owner) = args.new_owner().as_ref() else {
+            trace!("`{}` vanished", self.destination);
+
+            return;
+        };
+        trace!("`{}` is now owned by `{owner}`", self.destination);
+        let objects = match fetch_objects(&self.conn, &self.destination, &self.path).await {
+            Ok((objects, position)) => {
+                *since = position;
+
+                objects
+            }
+            Err(e) => {
+                debug!("Failed to fetch the objects from `{owner}`: {e}");
+
+                return;
+            }
+        };
+        let added: Vec<_> = objects
+            .iter()
+            .map(|(path, interfaces)| ObjectEvent::InterfacesAdded {
+                path: path.clone(),
+                interfaces: interfaces.keys().cloned().collect(),
+            })
+            .collect();
+        *self.objects.lock().expect("lock poisoned") = objects;
+        for event in added {
+            self.emit(event).await;
+        }
+    }
+
+    async fn emit(&self, event: ObjectEvent) {
+        // Only fails if there are no active receivers.
+        let _ = self.events.broadcast_direct(event).await;
+    }
+}
+
+fn handle_signal(
+    msg: &Message,
+    manager_path: &ObjectPath<'_>,
+    objects: &Mutex<Objects>,
+) -> Result<Option<ObjectEvent>> {
+    let header = msg.header();
+    let from_manager = header.path() == Some(manager_path);
+
+    if let Some(signal) = InterfacesAdded::from_message(msg.clone()).filter(|_| from_manager) {
+        let args = signal.args()?;
+        trace!("Interfaces added to `{}`", args.object_path());
+        let mut objects = objects.lock().expect("lock poisoned");
+        let interfaces = add_interfaces(
+            &mut objects,
+            args.object_path(),
+            args.interfaces_and_properties(),
+        );
+
+        return Ok(Some(ObjectEvent::InterfacesAdded {
+            path: args.object_path().to_owned().into(),
+            interfaces,
+        }));
+    }
+
+    if let Some(signal) = InterfacesRemoved::from_message(msg.clone()).filter(|_| from_manager) {
+        let args = signal.args()?;
+        trace!("Interfaces removed from `{}`", args.object_path());
+        let path = OwnedObjectPath::from(args.object_path().to_owned());
+        let mut objects = objects.lock().expect("lock poisoned");
+        if let Some(object) = objects.get_mut(&path) {
+            for interface in args.interfaces().iter() {
+                object.remove(interface);
+            }
+            if object.is_empty() {
+                objects.remove(&path);
+            }
+        }
+        let interfaces = args
+            .interfaces()
+            .iter()
+            .map(|i| i.to_owned().into())
+            .collect();
+
+        return Ok(Some(ObjectEvent::InterfacesRemoved { path, interfaces }));
+    }
+
+    if let Some(signal) = PropertiesChanged::from_message(msg.clone()) {
+        let args = signal.args()?;
+        let Some(path) = header.path() else {
+            return Ok(None);
+        };
+        let cache = objects
+            .lock()
+            .expect("lock poisoned")
+            .get(path)
+            .and_then(|interfaces| interfaces.get(&args.interface_name))
+            .cloned();
+        if let Some(cache) = cache {
+            cache.update(
+                &args.changed_properties,
+                &args.invalidated_properties,
+                &args.interface_name,
+            );
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::{ObjectEvent, ObjectManagerClient};
+    use crate::{
+        Result, connection,
+        fdo::{self, ObjectManager},
+        interface,
+        object_server::SignalEmitter,
+        proxy,
+        utils::block_on,
+    };
+
+    struct Device {
+        name: String,
+    }
+
+    #[interface(name = "org.zbus.ObjectManagerClientTest.Device")]
+    impl Device {
+        async fn rename(
+            &mut self,
+            name: String,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        ) -> fdo::Result<()> {
+            self.name = name;
+
+            Ok(self.name_changed(&emitter).await?)
+        }
+
+        #[zbus(property)]
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    #[proxy(interface = "org.zbus.ObjectManagerClientTest.Device")]
+    trait Device {
+        fn rename(&self, name: &str) -> Result<()>;
+
+        #[zbus(property)]
+        fn name(&self) -> Result<String>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn object_manager_client() {
+        block_on(test_object_manager_client()).unwrap();
+    }
+
+    async fn test_object_manager_client() -> Result<()> {
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/ObjectManagerClientTest", ObjectManager)?
+            .serve_at(
+                "/org/zbus/ObjectManagerClientTest/dev0",
+                Device {
+                    name: "dev0".to_string(),
+                },
+            )?
+            .build()
+            .await?;
+        let conn = connection::Builder::session()?.build().await?;
+        let client = ObjectManagerClient::new(
+            &conn,
+            service.unique_name().unwrap().to_owned(),
+            "/org/zbus/ObjectManagerClientTest",
+        )
+        .await?;
+        let mut events = client.receive_object_events();
+
+        let objects = client.managed_objects();
+        assert_eq!(objects.len(), 1);
+        let devices = client.proxies::<DeviceProxy<'_>>()?;
+        assert_eq!(devices.len(), 1);
+        let device = &devices[0];
+        assert_eq!(
+            device.inner().path(),
+            "/org/zbus/ObjectManagerClientTest/dev0"
+        );
+        // The properties are readily available.
+        assert_eq!(device.cached_name()?.as_deref(), Some("dev0"));
+
+        // Property changes are applied.
+        let mut name_changes = device.receive_name_changed().await;
+        assert_eq!(name_changes.next().await.unwrap().get().await?, "dev0");
+        device.rename("renamed").await?;
+        assert_eq!(name_changes.next().await.unwrap().get().await?, "renamed");
+        let properties = client
+            .properties(
+                &"/org/zbus/ObjectManagerClientTest/dev0".try_into()?,
+                &"org.zbus.ObjectManagerClientTest.Device".try_into()?,
+            )
+            .unwrap();
+        assert_eq!(String::try_from(properties["Name"].clone())?, "renamed");
+
+        // Objects are added and removed.
+        service
+            .object_server()
+            .at(
+                "/org/zbus/ObjectManagerClientTest/dev1",
+                Device {
+                    name: "dev1".to_string(),
+                },
+            )
+            .await?;
+        match events.next().await.unwrap() {
+            ObjectEvent::InterfacesAdded { path, interfaces } => {
+                assert_eq!(path.as_str(), "/org/zbus/ObjectManagerClientTest/dev1");
+                assert!(
+                    interfaces
+                        .iter()
+                        .any(|i| i.as_str() == "org.zbus.ObjectManagerClientTest.Device")
+                );
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        let device = client
+            .proxy::<DeviceProxy<'_>>(&"/org/zbus/ObjectManagerClientTest/dev1".try_into()?)?
+            .unwrap();
+        assert_eq!(device.cached_name()?.as_deref(), Some("dev1"));
+        assert_eq!(client.proxies::<DeviceProxy<'_>>()?.len(), 2);
+
+        service
+            .object_server()
+            .remove::<Device, _>("/org/zbus/ObjectManagerClientTest/dev1")
+            .await?;
+        match events.next().await.unwrap() {
+            ObjectEvent::InterfacesRemoved { path, .. } => {
+                assert_eq!(path.as_str(), "/org/zbus/ObjectManagerClientTest/dev1");
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        assert!(
+            client
+                .proxy::<DeviceProxy<'_>>(&"/org/zbus/ObjectManagerClientTest/dev1".try_into()?)?
+                .is_none()
+        );
+        assert_eq!(
+            client.paths_with_interface(&"org.zbus.ObjectManagerClientTest.Device".try_into()?),
+            vec!["/org/zbus/ObjectManagerClientTest/dev0".try_into()?]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn owner_changes() {
+        block_on(test_owner_changes()).unwrap();
+    }
+
+    async fn test_owner_changes() -> Result<()> {
+        let name = "org.zbus.ObjectManagerClientTest.Owner";
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/ObjectManagerClientTest", ObjectManager)?
+            .serve_at(
+                "/org/zbus/ObjectManagerClientTest/dev0",
+                Device {
+                    name: "dev0".to_string(),
+                },
+            )?
+            .name(name)?
+            .build()
+            .await?;
+        let conn = connection::Builder::session()?.build().await?;
+        let client =
+            ObjectManagerClient::new(&conn, name, "/org/zbus/ObjectManagerClientTest").await?;
+        let mut events = client.receive_object_events();
+        assert_eq!(client.managed_objects().len(), 1);
+
+        // The objects are removed once the owner vanishes.
+        service.release_name(name).await?;
+        match events.next().await.unwrap() {
+            ObjectEvent::InterfacesRemoved { path, .. } => {
+                assert_eq!(path.as_str(), "/org/zbus/ObjectManagerClientTest/dev0");
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        assert!(client.managed_objects().is_empty());
+
+        // The objects of the new owner are fetched.
+        let _service = connection::Builder::session()?
+            .serve_at("/org/zbus/ObjectManagerClientTest", ObjectManager)?
+            .serve_at(
+                "/org/zbus/ObjectManagerClientTest/dev1",
+                Device {
+                    name: "dev1".to_string(),
+                },
+            )?
+            .name(name)?
+            .build()
+            .await?;
+        match events.next().await.unwrap() {
+            ObjectEvent::InterfacesAdded { path, .. } => {
+                assert_eq!(path.as_str(), "/org/zbus/ObjectManagerClientTest/dev1");
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        let device = client
+            .proxy::<DeviceProxy<'_>>(&"/org/zbus/ObjectManagerClientTest/dev1".try_into()?)?
+            .unwrap();
+        assert_eq!(device.cached_name()?.as_deref(), Some("dev1"));
+        assert_eq!(client.managed_objects().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn other_senders() {
+        block_on(test_other_senders()).unwrap();
+    }
+
+    async fn test_other_senders() -> Result<()> {
+        let path = "/org/zbus/ObjectManagerClientTest";
+        let name = "org.zbus.ObjectManagerClientTest.Mine";
+        let other_name = "org.zbus.ObjectManagerClientTest.Other";
+        let service = connection::Builder::session()?
+            .serve_at(path, ObjectManager)?
+            .serve_at(
+                "/org/zbus/ObjectManagerClientTest/dev0",
+                Device {
+                    name: "dev0".to_string(),
+                },
+            )?
+            .name(name)?
+            .build()
+            .await?;
+        let other_service = connection::Builder::session()?
+            .serve_at(path, ObjectManager)?
+            .name(other_name)?
+            .build()
+            .await?;
+        let conn = connection::Builder::session()?.build().await?;
+        let client = ObjectManagerClient::new(&conn, name, path).await?;
+        let mut events = client.receive_object_events();
+        let other_client = ObjectManagerClient::new(&conn, other_name, path).await?;
+        let mut other_events = other_client.receive_object_events();
+
+        // The signals of the other service also reach the connection, but only its client uses
+        // them.
+        other_service
+            .object_server()
+            .at(
+                "/org/zbus/ObjectManagerClientTest/dev9",
+                Device {
+                    name: "dev9".to_string(),
+                },
+            )
+            .await?;
+        match other_events.next().await.unwrap() {
+            ObjectEvent::InterfacesAdded { path, .. } => {
+                assert_eq!(path.as_str(), "/org/zbus/ObjectManagerClientTest/dev9");
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        service.release_name(name).await?;
+        match events.next().await.unwrap() {
+            ObjectEvent::InterfacesRemoved { path, .. } => {
+                assert_eq!(path.as_str(), "/org/zbus/ObjectManagerClientTest/dev0");
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        assert!(client.managed_objects().is_empty());
+        assert_eq!(other_client.managed_objects().len(), 1);
+
+        Ok(())
+    }
+}
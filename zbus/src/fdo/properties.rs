@@ -33,12 +33,7 @@ impl Properties {
     ) -> Result<OwnedValue> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
-        let iface = root
-            .get_child(path)
-            .and_then(|node| node.interface_lock(interface_name.as_ref()))
-            .ok_or_else(|| {
-                Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
-            })?;
+        let iface = root.resolve(path).interface(&interface_name)?;
 
         let res = iface
             .instance
@@ -67,12 +62,7 @@ impl Properties {
     ) -> Result<()> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
-        let iface = root
-            .get_child(path)
-            .and_then(|node| node.interface_lock(interface_name.as_ref()))
-            .ok_or_else(|| {
-                Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
-            })?;
+        let iface = root.resolve(path).interface(&interface_name)?;
 
         match iface.instance.read().await.set(
             property_name,
@@ -89,7 +79,10 @@ impl Properties {
                 )));
             }
             zbus::object_server::DispatchResult::Async(f) => {
-                return f.await.map_err(Into::into);
+                return f.await.map_err(|e| match e {
+                    crate::Error::FDO(e) => *e,
+                    e => e.into(),
+                });
             }
         }
         let res = iface
@@ -123,12 +116,7 @@ impl Properties {
     ) -> Result<HashMap<String, OwnedValue>> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
-        let iface = root
-            .get_child(path)
-            .and_then(|node| node.interface_lock(interface_name.as_ref()))
-            .ok_or_else(|| {
-                Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
-            })?;
+        let iface = root.resolve(path).interface(&interface_name)?;
 
         let res = iface
             .instance
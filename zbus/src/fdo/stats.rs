@@ -4,12 +4,15 @@
 //! be useful across various D-Bus applications. This module provides their proxy.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU32, Ordering},
+};
 use zbus_names::{BusName, OwnedUniqueName};
 use zvariant::{OwnedValue, Type, as_value::optional};
 
-use super::Result;
-use crate::proxy;
+use super::{Error, Result};
+use crate::{Connection, interface, proxy};
 
 /// Proxy for the [`org.freedesktop.DBus.Debug.Stats`][link] interface.
 ///
@@ -270,3 +273,137 @@ impl ConnectionStats {
         &self.rest
     }
 }
+
+/// Service-side implementation of the [`org.freedesktop.DBus.Debug.Stats`][link] interface, for
+/// the statistics of the connection it's served on.
+///
+/// This allows tools that speak this interface (e.g. through [`StatsProxy`]) to scrape the
+/// statistics that [`Connection::stats`] returns. Since the interface is meant for message buses,
+/// its methods are served as follows:
+///
+/// * `GetStats` returns the statistics of the connection, with the ones the specification doesn't
+///   define for it under their own keys (e.g. `IncomingMessages` and `PendingMethodCalls`).
+/// * `GetConnectionStats` returns the same statistics, if the given name is the unique name of the
+///   connection or a well-known name it owns, and fails with
+///   [`Error::NameHasNoOwner`] otherwise.
+/// * `GetAllMatchRules` returns the match rules in use on the connection.
+///
+/// Counters are capped at [`u32::MAX`], as the interface uses 32-bit values.
+///
+/// # Example
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::{connection::Builder, fdo::DebugStats};
+///
+/// let conn = Builder::session()?
+///     .name("org.zbus.MyService")?
+///     .serve_at("/org/zbus/MyService", DebugStats::default())?
+///     .build()
+///     .await?;
+/// # drop(conn);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [link]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-bus-debug-stats-interface
+#[derive(Debug, Default)]
+pub struct DebugStats {
+    serial: AtomicU32,
+}
+
+#[interface(name = "org.freedesktop.DBus.Debug.Stats", introspection_docs = false)]
+impl DebugStats {
+    /// Get the statistics of the connection.
+    async fn get_stats(&self, #[zbus(connection)] conn: &Connection) -> Result<Stats> {
+        let stats = conn.stats();
+        // The traffic counters are only defined for `GetConnectionStats`.
+        let mut rest = extra_stats(&stats);
+        for (key, value) in [
+            ("IncomingMessages", stats.incoming_messages()),
+            ("OutgoingMessages", stats.outgoing_messages()),
+            ("IncomingBytes", stats.incoming_bytes()),
+            ("OutgoingBytes", stats.outgoing_bytes()),
+            ("IncomingFDs", stats.incoming_fds()),
+            ("OutgoingFDs", stats.outgoing_fds()),
+        ] {
+            rest.insert(key.to_string(), OwnedValue::from(saturate(value)));
+        }
+
+        Ok(Stats {
+            serial: Some(self.next_serial()),
+            match_rules: Some(saturate(stats.match_rules())),
+            bus_names: Some(saturate(conn.owned_names().await.len())),
+            rest,
+            ..Default::default()
+        })
+    }
+
+    /// Get the statistics of the connection, if it's identified by `name`.
+    async fn get_connection_stats(
+        &self,
+        name: BusName<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<ConnectionStats> {
+        let names = conn.owned_names().await;
+        let is_ours = match &name {
+            BusName::Unique(name) => conn.current_unique_name().is_some_and(|n| n == *name),
+            BusName::WellKnown(name) => names.contains(name),
+        };
+        if !is_ours {
+            return Err(Error::NameHasNoOwner(format!(
+                "`{name}` doesn't refer to this connection"
+            )));
+        }
+        let stats = conn.stats();
+
+        Ok(ConnectionStats {
+            serial: Some(self.next_serial()),
+            unique_name: conn.current_unique_name(),
+            match_rules: Some(saturate(stats.match_rules())),
+            bus_names: Some(saturate(names.len())),
+            incoming_messages: Some(saturate(stats.incoming_messages())),
+            outgoing_messages: Some(saturate(stats.outgoing_messages())),
+            incoming_bytes: Some(saturate(stats.incoming_bytes())),
+            outgoing_bytes: Some(saturate(stats.outgoing_bytes())),
+            incoming_fds: Some(saturate(stats.incoming_fds())),
+            outgoing_fds: Some(saturate(stats.outgoing_fds())),
+            rest: extra_stats(&stats),
+            ..Default::default()
+        })
+    }
+
+    /// Get the match rules in use on the connection, keyed by its unique name.
+    async fn get_all_match_rules(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<HashMap<OwnedUniqueName, Vec<crate::OwnedMatchRule>>> {
+        let mut rules = HashMap::new();
+        if let Some(name) = conn.current_unique_name() {
+            rules.insert(name, conn.match_rules().await);
+        }
+
+        Ok(rules)
+    }
+}
+
+impl DebugStats {
+    fn next_serial(&self) -> u32 {
+        self.serial.fetch_add(1, Ordering::Relaxed).wrapping_add(1)
+    }
+}
+
+fn saturate<N: TryInto<u32>>(n: N) -> u32 {
+    n.try_into().unwrap_or(u32::MAX)
+}
+
+/// The zbus-specific statistics, not defined by the specification.
+fn extra_stats(stats: &crate::connection::Stats) -> HashMap<String, OwnedValue> {
+    [
+        ("PendingMethodCalls", stats.pending_method_calls()),
+        ("FullQueueWaits", stats.full_queue_waits()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), OwnedValue::from(saturate(value))))
+    .collect()
+}
@@ -88,9 +88,19 @@ pub use utils::*;
 #[macro_use]
 pub mod fdo;
 
+pub mod polkit;
+
 #[cfg(feature = "blocking-api")]
 pub mod blocking;
 
+#[cfg(feature = "bus-impl")]
+pub mod bus;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "proxy-from-xml")]
+pub use zbus_macros::proxy_from_xml;
 pub use zbus_macros::{DBusError, interface, proxy};
 
 // Required for the macros to function within this crate.
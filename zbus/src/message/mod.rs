@@ -234,6 +234,21 @@ impl Message {
         self.inner.recv_seq
     }
 
+    /// Set the receive ordering of a message, e.g. of one rebuilt from a received message.
+    pub(crate) fn set_recv_position(&mut self, recv_seq: Sequence) {
+        if self.inner.recv_seq == recv_seq {
+            return;
+        }
+
+        self.inner = Arc::new(Inner {
+            primary_header: self.inner.primary_header.clone(),
+            quick_fields: self.inner.quick_fields.clone(),
+            bytes: self.inner.bytes.clone(),
+            body_offset: self.inner.body_offset,
+            recv_seq,
+        });
+    }
+
     fn quick_fields(&self) -> &QuickFields {
         self.inner.quick_fields.get_or_init(|| {
             let bytes = &self.inner.bytes;
@@ -0,0 +1,201 @@
+//! Tracking of the activity of the object server, to tell when it's idle.
+
+use event_listener::Event;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The activity of an object server.
+#[derive(Debug)]
+pub(crate) struct Activity {
+    state: Mutex<State>,
+    changed: Event,
+}
+
+#[derive(Debug)]
+struct State {
+    /// The number of live [`BusyGuard`]s.
+    busy: usize,
+    /// When the object server was last busy.
+    last_busy: Instant,
+}
+
+impl Activity {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                busy: 0,
+                last_busy: Instant::now(),
+            }),
+            changed: Event::new(),
+        }
+    }
+
+    /// Mark the object server as busy, until the returned guard is dropped.
+    pub(crate) fn busy(self: &Arc<Self>) -> BusyGuard {
+        self.state.lock().expect("lock poisoned").busy += 1;
+        self.changed.notify(usize::MAX);
+
+        BusyGuard(self.clone())
+    }
+
+    /// Wait for the object server to not be busy for `timeout`.
+    pub(crate) async fn wait_idle(&self, timeout: Duration) {
+        loop {
+            let listener = self.changed.listen();
+            let (busy, last_busy) = {
+                let state = self.state.lock().expect("lock poisoned");
+
+                (state.busy, state.last_busy)
+            };
+            if busy > 0 {
+                listener.await;
+
+                continue;
+            }
+
+            let idle = last_busy.elapsed();
+            if idle >= timeout {
+                return;
+            }
+            // Wait for the remaining time, unless the object server gets busy in the meantime.
+            crate::timeout::timeout(listener, timeout - idle).await;
+        }
+    }
+
+    /// Wait for the object server to not be busy.
+    pub(crate) async fn wait_not_busy(&self) {
+        self.wait_idle(Duration::ZERO).await
+    }
+}
+
+/// A guard keeping the [`ObjectServer`] busy, as long as it's alive.
+///
+/// Use [`ObjectServer::busy`] to create an instance of this type.
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::busy`]: crate::ObjectServer::busy
+#[must_use = "the object server is only kept busy as long as the guard is alive"]
+pub struct BusyGuard(Arc<Activity>);
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        {
+            let mut state = self.0.state.lock().expect("lock poisoned");
+            state.busy -= 1;
+            state.last_busy = Instant::now();
+        }
+        self.0.changed.notify(usize::MAX);
+    }
+}
+
+impl fmt::Debug for BusyGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BusyGuard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use std::time::{Duration, Instant};
+    use test_log::test;
+
+    use crate::{
+        Result,
+        connection::{self, NameState},
+        fdo::{DBusProxy, RequestNameFlags},
+        interface, proxy,
+        timeout::{sleep, timeout},
+        utils::block_on,
+    };
+
+    struct Worker;
+
+    #[interface(name = "org.zbus.IdleTest.Worker")]
+    impl Worker {
+        fn work(&self) {}
+    }
+
+    #[proxy(
+        interface = "org.zbus.IdleTest.Worker",
+        default_service = "org.zbus.IdleTest",
+        default_path = "/org/zbus/IdleTest"
+    )]
+    trait Worker {
+        fn work(&self) -> Result<()>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn idle() {
+        block_on(test_idle()).unwrap();
+    }
+
+    async fn test_idle() -> Result<()> {
+        let service = connection::Builder::session()?
+            .name("org.zbus.IdleTest")?
+            .serve_at("/org/zbus/IdleTest", Worker)?
+            .build()
+            .await?;
+        let ownership = service
+            .name_ownership("org.zbus.IdleTest.Standby")?
+            .flags(RequestNameFlags::DoNotQueue.into())
+            .reacquire(true)
+            .request()
+            .await?;
+        let server = service.object_server();
+        let client = connection::Builder::session()?.build().await?;
+        let worker = WorkerProxy::new(&client).await?;
+
+        // Method calls and busy guards keep the object server from being idle.
+        let guard = server.busy();
+        let (idle, busy_until) =
+            futures_util::join!(server.idle(Duration::from_millis(100)), async {
+                worker.work().await?;
+                drop(guard);
+                for _ in 0..3 {
+                    sleep(Duration::from_millis(50)).await;
+                    worker.work().await?;
+                }
+
+                Ok::<_, crate::Error>(Instant::now())
+            });
+        idle?;
+        assert!(busy_until?.elapsed() >= Duration::from_millis(100));
+
+        // The names are released, for the service to be activated again.
+        let dbus = DBusProxy::new(&client).await?;
+        assert!(!dbus.name_has_owner("org.zbus.IdleTest".try_into()?).await?);
+        assert_eq!(ownership.state(), NameState::Lost);
+        sleep(Duration::from_millis(50)).await;
+        assert!(
+            !dbus
+                .name_has_owner("org.zbus.IdleTest.Standby".try_into()?)
+                .await?
+        );
+
+        // Objects held by peers keep the object server busy as well.
+        let owner = connection::Builder::session()?.build().await?;
+        server
+            .object("/org/zbus/IdleTest/job")?
+            .with(Worker)
+            .owned_by(owner.unique_name().unwrap())?
+            .register()
+            .await?;
+        assert!(
+            timeout(
+                server.idle(Duration::from_millis(50)),
+                Duration::from_millis(300)
+            )
+            .await
+            .is_none()
+        );
+        owner.close().await?;
+        server.idle(Duration::from_millis(50)).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+//! Authorization of method calls.
+
+use async_trait::async_trait;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, RwLock},
+};
+use tracing::debug;
+use zbus_names::{InterfaceName, MemberName, OwnedUniqueName};
+
+use crate::{
+    Connection,
+    fdo::{self, ConnectionCredentials, DBusProxy},
+    message::Header,
+};
+
+/// The maximum number of peers whose credentials are cached.
+const MAX_CACHED_CREDENTIALS: usize = 256;
+
+/// A policy deciding whether method calls are allowed to be dispatched.
+///
+/// Set an authorizer on the [`ObjectServer`] through [`ObjectServer::set_authorizer`] or
+/// [`connection::Builder::authorizer`]. Once set, the authorizer is consulted for every method call
+/// dispatched by the object server, before the method is called. Calls that aren't authorized are
+/// replied to with [`fdo::Error::AccessDenied`].
+///
+/// Methods can be tied to an action through the `#[zbus(authorize = "action")]` attribute of the
+/// [`interface`] macro, which is then available through [`AuthorizationRequest::action`]. Calls to
+/// such methods are always denied if no authorizer is set.
+///
+/// # Example
+///
+/// Only allowing the root user to call the privileged methods:
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::{
+///     connection::Builder,
+///     fdo,
+///     interface,
+///     object_server::{AuthorizationRequest, Authorizer},
+/// };
+///
+/// #[derive(Debug)]
+/// struct RootOnly;
+///
+/// #[zbus::export::async_trait::async_trait]
+/// impl Authorizer for RootOnly {
+///     async fn authorize(&self, request: &AuthorizationRequest<'_>) -> fdo::Result<bool> {
+///         if request.action().is_none() {
+///             return Ok(true);
+///         }
+///         let credentials = request.credentials().await?;
+///
+///         Ok(credentials.unix_user_id() == Some(0))
+///     }
+/// }
+///
+/// struct Service;
+///
+/// #[interface(name = "org.zbus.Service")]
+/// impl Service {
+///     #[zbus(authorize = "org.zbus.Service.reboot")]
+///     fn reboot(&self) {
+///         // ...
+///     }
+/// }
+///
+/// let _conn = Builder::system()?
+///     .authorizer(RootOnly)
+///     .serve_at("/org/zbus/Service", Service)?
+///     .name("org.zbus.Service")?
+///     .build()
+///     .await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::set_authorizer`]: crate::ObjectServer::set_authorizer
+/// [`connection::Builder::authorizer`]: crate::connection::Builder::authorizer
+/// [`interface`]: crate::interface
+#[async_trait]
+pub trait Authorizer: std::fmt::Debug + Send + Sync + 'static {
+    /// Decide whether the method call described by `request` is allowed.
+    ///
+    /// Returning an error denies the call as well, with the error being returned to the caller.
+    async fn authorize(&self, request: &AuthorizationRequest<'_>) -> fdo::Result<bool>;
+}
+
+/// A method call to be authorized by an [`Authorizer`].
+#[derive(Debug)]
+pub struct AuthorizationRequest<'r> {
+    connection: &'r Connection,
+    header: &'r Header<'r>,
+    interface: &'r InterfaceName<'r>,
+    member: &'r MemberName<'r>,
+    action: Option<&'r str>,
+    credentials: &'r CredentialsCache,
+}
+
+impl<'r> AuthorizationRequest<'r> {
+    /// The connection the call was received on.
+    pub fn connection(&self) -> &Connection {
+        self.connection
+    }
+
+    /// The header of the method call message.
+    pub fn header(&self) -> &Header<'r> {
+        self.header
+    }
+
+    /// The called interface.
+    pub fn interface(&self) -> &InterfaceName<'r> {
+        self.interface
+    }
+
+    /// The called method.
+    pub fn member(&self) -> &MemberName<'r> {
+        self.member
+    }
+
+    /// The action the method is tied to, through the `#[zbus(authorize = "action")]` attribute.
+    pub fn action(&self) -> Option<&str> {
+        self.action
+    }
+
+    /// The credentials of the caller.
+    ///
+    /// On bus connections, these are requested from the bus and cached per unique name of the
+    /// callers. On peer-to-peer connections, these are the credentials of the peer.
+    pub async fn credentials(&self) -> fdo::Result<Arc<ConnectionCredentials>> {
+        self.credentials.get(self.connection, self.header).await
+    }
+}
+
+/// The authorization state of an `ObjectServer`.
+#[derive(Debug, Default)]
+pub(crate) struct Authorization {
+    authorizer: RwLock<Option<Arc<dyn Authorizer>>>,
+    credentials: CredentialsCache,
+}
+
+impl Authorization {
+    pub(crate) fn set_authorizer(&self, authorizer: Arc<dyn Authorizer>) {
+        *self.authorizer.write().expect("lock poisoned") = Some(authorizer);
+    }
+
+    /// Check if the call described by `header` to a method tied to `action` is authorized.
+    pub(crate) async fn check(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+        action: Option<&str>,
+    ) -> fdo::Result<()> {
+        let authorizer = self.authorizer.read().expect("lock poisoned").clone();
+        let Some(authorizer) = authorizer else {
+            return match action {
+                Some(action) => Err(fdo::Error::AccessDenied(format!(
+                    "No authorizer set for action `{action}`"
+                ))),
+                None => Ok(()),
+            };
+        };
+        let (Some(interface), Some(member)) = (header.interface(), header.member()) else {
+            return Err(fdo::Error::Failed("Missing interface or member".into()));
+        };
+
+        let request = AuthorizationRequest {
+            connection,
+            header,
+            interface,
+            member,
+            action,
+            credentials: &self.credentials,
+        };
+        if authorizer.authorize(&request).await? {
+            Ok(())
+        } else {
+            debug!("Call to `{interface}.{member}` denied");
+
+            Err(fdo::Error::AccessDenied(format!(
+                "Not authorized to call `{interface}.{member}`"
+            )))
+        }
+    }
+}
+
+/// A cache of the credentials of the peers, per unique name.
+///
+/// Unique names are never reused, so entries never get stale. The oldest entries are evicted to
+/// keep the cache from growing with each peer ever seen. The lock is not held while the
+/// credentials are fetched, so concurrent calls from new peers don't wait on each other.
+#[derive(Debug, Default)]
+struct CredentialsCache(Mutex<CachedCredentials>);
+
+#[derive(Debug, Default)]
+struct CachedCredentials {
+    credentials: HashMap<OwnedUniqueName, Arc<ConnectionCredentials>>,
+    order: VecDeque<OwnedUniqueName>,
+}
+
+impl CredentialsCache {
+    async fn get(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+    ) -> fdo::Result<Arc<ConnectionCredentials>> {
+        let sender = match header.sender() {
+            Some(sender) if connection.is_bus() => sender,
+            _ => {
+                return connection
+                    .peer_creds()
+                    .await
+                    .cloned()
+                    .map_err(|e| fdo::Error::IOError(e.to_string()));
+            }
+        };
+
+        if let Some(credentials) = self
+            .0
+            .lock()
+            .expect("lock poisoned")
+            .credentials
+            .get(sender.as_str())
+        {
+            return Ok(credentials.clone());
+        }
+
+        let credentials = Arc::new(
+            DBusProxy::new(connection)
+                .await?
+                .get_connection_credentials(sender.as_ref().into())
+                .await?,
+        );
+        let mut cache = self.0.lock().expect("lock poisoned");
+        // Another call from the same peer may have fetched the credentials in the meantime.
+        if let Some(credentials) = cache.credentials.get(sender.as_str()) {
+            return Ok(credentials.clone());
+        }
+        let sender = OwnedUniqueName::from(sender.to_owned());
+        if cache.order.len() >= MAX_CACHED_CREDENTIALS {
+            if let Some(oldest) = cache.order.pop_front() {
+                cache.credentials.remove(&oldest);
+            }
+        }
+        cache.order.push_back(sender.clone());
+        cache.credentials.insert(sender, credentials.clone());
+
+        Ok(credentials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::{AuthorizationRequest, Authorizer};
+    use crate::{Result, connection, fdo, interface, proxy, utils::block_on};
+
+    /// Only allows the `allowed` action, and only to peers whose credentials are known.
+    #[derive(Debug)]
+    struct AllowedOnly;
+
+    #[async_trait::async_trait]
+    impl Authorizer for AllowedOnly {
+        async fn authorize(&self, request: &AuthorizationRequest<'_>) -> fdo::Result<bool> {
+            let Some(action) = request.action() else {
+                return Ok(true);
+            };
+            assert_eq!(request.interface(), "org.zbus.AuthorizationTest");
+            let credentials = request.credentials().await?;
+            #[cfg(unix)]
+            assert!(credentials.unix_user_id().is_some());
+            #[cfg(not(unix))]
+            let _ = credentials;
+
+            Ok(action == "org.zbus.AuthorizationTest.allowed")
+        }
+    }
+
+    struct Service;
+
+    #[interface(name = "org.zbus.AuthorizationTest")]
+    impl Service {
+        fn open(&self) -> u32 {
+            1
+        }
+
+        #[zbus(authorize = "org.zbus.AuthorizationTest.allowed")]
+        fn allowed(&self) -> u32 {
+            2
+        }
+
+        #[zbus(authorize = "org.zbus.AuthorizationTest.denied")]
+        fn denied(&mut self) -> u32 {
+            3
+        }
+    }
+
+    #[proxy(
+        interface = "org.zbus.AuthorizationTest",
+        default_path = "/org/zbus/AuthorizationTest"
+    )]
+    trait Service {
+        fn open(&self) -> Result<u32>;
+        fn allowed(&self) -> Result<u32>;
+        fn denied(&self) -> Result<u32>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn authorization() {
+        block_on(test_authorization()).unwrap();
+    }
+
+    async fn test_authorization() -> Result<()> {
+        let service = connection::Builder::session()?
+            .authorizer(AllowedOnly)
+            .serve_at("/org/zbus/AuthorizationTest", Service)?
+            .build()
+            .await?;
+        let unauthorized = connection::Builder::session()?
+            .serve_at("/org/zbus/AuthorizationTest", Service)?
+            .build()
+            .await?;
+        let client = connection::Builder::session()?.build().await?;
+
+        let proxy = ServiceProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .build()
+            .await?;
+        assert_eq!(proxy.open().await?, 1);
+        assert_eq!(proxy.allowed().await?, 2);
+        // Twice, to hit the credentials cache.
+        assert_eq!(proxy.allowed().await?, 2);
+        assert_access_denied(proxy.denied().await);
+
+        // Without an authorizer, only the methods not tied to an action can be called.
+        let proxy = ServiceProxy::builder(&client)
+            .destination(unauthorized.unique_name().unwrap())?
+            .build()
+            .await?;
+        assert_eq!(proxy.open().await?, 1);
+        assert_access_denied(proxy.allowed().await);
+
+        Ok(())
+    }
+
+    fn assert_access_denied(res: Result<u32>) {
+        match res {
+            Err(crate::Error::MethodError(name, _, _)) => {
+                assert_eq!(name, "org.freedesktop.DBus.Error.AccessDenied")
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+}
@@ -0,0 +1,792 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use tracing::trace;
+use zbus_names::{InterfaceName, MemberName, PropertyName};
+use zvariant::{OwnedStructure, OwnedValue, Signature, StructureBuilder, Value};
+
+use crate::{
+    Connection, Error, ObjectServer, Result, fdo,
+    message::{Flags, Header, Message},
+    object_server::{DispatchResult, Interface, SignalEmitter},
+};
+
+type MethodFuture = Pin<Box<dyn Future<Output = fdo::Result<Vec<Value<'static>>>> + Send>>;
+type MethodHandler = Box<dyn Fn(Vec<Value<'static>>) -> MethodFuture + Send + Sync>;
+type PropertyGetter = Box<dyn Fn() -> fdo::Result<Value<'static>> + Send + Sync>;
+type PropertySetter = Box<dyn Fn(Value<'static>) -> fdo::Result<()> + Send + Sync>;
+
+/// An interface whose members are registered at runtime.
+///
+/// The [`crate::interface`] macro requires the interface to be known at compile time. When the
+/// methods, properties and signals of an interface are only known at runtime (e.g. they come from a
+/// configuration file or a plugin), use a `DynamicInterface` instead. Each member is registered by
+/// name and signature, and methods and properties are handled by closures working on
+/// [`Value`](enum@Value)s. The introspection data is generated from these registrations.
+///
+/// Signatures of method and signal arguments are given as a sequence of complete types, one per
+/// argument (e.g. `"su"` for a string and an unsigned integer, or `""` for no arguments). The
+/// arguments and return values of method calls are checked against these signatures.
+///
+/// Register the interface with [`ObjectServer::at_dynamic`]. `DynamicInterface` is cheap to clone,
+/// so you can keep a clone around to emit its signals and property changes.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use async_io::block_on;
+/// use zbus::{Connection, object_server::DynamicInterface, zvariant::Value};
+///
+/// # block_on(async {
+/// let iface = DynamicInterface::builder("org.zbus.Bridge1")?
+///     .method("Add", "ii", "i", |args| async move {
+///         match &args[..] {
+///             [Value::I32(a), Value::I32(b)] => Ok(vec![Value::from(a + b)]),
+///             _ => unreachable!("arguments are checked against the signature"),
+///         }
+///     })?
+///     .property("Version", "s", || Ok(Value::from("1.0")))?
+///     .signal("Added", "i")?
+///     .build();
+///
+/// let connection = Connection::session().await?;
+/// connection
+///     .object_server()
+///     .at_dynamic("/org/zbus/Bridge", iface.clone())
+///     .await?;
+///
+/// let emitter = zbus::object_server::SignalEmitter::new(&connection, "/org/zbus/Bridge")?;
+/// iface.emit(&emitter, "Added", vec![Value::from(42)]).await?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+#[derive(Clone)]
+pub struct DynamicInterface(Arc<Inner>);
+
+struct Inner {
+    name: InterfaceName<'static>,
+    methods: BTreeMap<String, Method>,
+    properties: BTreeMap<String, Property>,
+    signals: BTreeMap<String, Args>,
+}
+
+struct Method {
+    inputs: Args,
+    outputs: Args,
+    handler: MethodHandler,
+}
+
+struct Property {
+    ty: Signature,
+    getter: PropertyGetter,
+    setter: Option<PropertySetter>,
+}
+
+/// The arguments of a method or a signal.
+struct Args {
+    /// The signature of the whole message body.
+    signature: Signature,
+    /// The signature of each argument.
+    args: Vec<Signature>,
+}
+
+impl Args {
+    fn parse(signature: &str) -> Result<Self> {
+        let parsed = Signature::try_from(signature)?;
+        let args = match &parsed {
+            Signature::Unit => vec![],
+            // A top-level structure is either the only argument, or the list of arguments if it
+            // isn't enclosed in parentheses.
+            Signature::Structure(fields) if parsed.string_len() != signature.len() => {
+                fields.iter().cloned().collect()
+            }
+            _ => vec![parsed.clone()],
+        };
+
+        Ok(Self {
+            signature: parsed,
+            args,
+        })
+    }
+
+    /// Check `values` against the signatures of the arguments and turn them into a body.
+    fn body<'v>(&self, values: Vec<Value<'v>>) -> Result<Option<zvariant::Structure<'v>>> {
+        let matches = values.len() == self.args.len()
+            && values
+                .iter()
+                .zip(&self.args)
+                .all(|(value, arg)| value.value_signature() == arg);
+        if !matches {
+            let signature = values
+                .iter()
+                .map(|v| v.value_signature().to_string())
+                .collect::<String>();
+            return Err(Error::Variant(zvariant::Error::SignatureMismatch(
+                Signature::try_from(signature.as_str())?,
+                format!("`{}`", self.signature.to_string_no_parens()),
+            )));
+        }
+        if values.is_empty() {
+            return Ok(None);
+        }
+
+        values
+            .into_iter()
+            .fold(StructureBuilder::new(), StructureBuilder::append_field)
+            .build()
+            .map(Some)
+            .map_err(Into::into)
+    }
+
+    fn introspect(&self, writer: &mut dyn Write, direction: Option<&str>, level: usize) {
+        let direction = direction
+            .map(|d| format!(" direction=\"{d}\""))
+            .unwrap_or_default();
+        for arg in &self.args {
+            writeln!(
+                writer,
+                "{:indent$}<arg type=\"{arg}\"{direction}/>",
+                "",
+                indent = level
+            )
+            .unwrap();
+        }
+    }
+}
+
+impl DynamicInterface {
+    /// Create a builder for an interface named `name`.
+    pub fn builder<N>(name: N) -> Result<DynamicInterfaceBuilder>
+    where
+        N: TryInto<InterfaceName<'static>>,
+        N::Error: Into<Error>,
+    {
+        Ok(DynamicInterfaceBuilder(Inner {
+            name: name.try_into().map_err(Into::into)?,
+            methods: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            signals: BTreeMap::new(),
+        }))
+    }
+
+    /// The name of the interface.
+    pub fn name(&self) -> &InterfaceName<'static> {
+        &self.0.name
+    }
+
+    /// Emit the signal named `signal` with the given arguments.
+    ///
+    /// Returns an error if the signal wasn't registered or the arguments don't match its
+    /// signature.
+    pub async fn emit(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        signal: &str,
+        args: Vec<Value<'_>>,
+    ) -> Result<()> {
+        let signal_args = self
+            .0
+            .signals
+            .get(signal)
+            .ok_or_else(|| Error::Failure(format!("Unknown signal `{signal}`")))?;
+        match signal_args.body(args)? {
+            Some(body) => emitter.emit(&self.0.name, signal, &body).await,
+            None => emitter.emit(&self.0.name, signal, &()).await,
+        }
+    }
+
+    /// Emit the `PropertiesChanged` signal with the current value of the property named
+    /// `property`.
+    ///
+    /// This method should be called if a property value changes outside its setter.
+    pub async fn property_changed(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        property: &str,
+    ) -> Result<()> {
+        let value = self
+            .0
+            .get(property)
+            .ok_or_else(|| Error::Failure(format!("Unknown property `{property}`")))??;
+        let mut changed = HashMap::new();
+        changed.insert(property, Value::from(value));
+        emitter
+            .emit_properties_changed(self.0.name.clone(), changed, &[])
+            .await
+    }
+}
+
+impl fmt::Debug for DynamicInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicInterface")
+            .field("name", &self.0.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Inner {
+    fn get(&self, property: &str) -> Option<fdo::Result<OwnedValue>> {
+        let property = self.properties.get(property)?;
+        let value = (property.getter)().and_then(|value| {
+            if value.value_signature() != &property.ty {
+                return Err(fdo::Error::Failed(format!(
+                    "Property value of type `{}` doesn't match the expected type `{}`",
+                    value.value_signature(),
+                    property.ty,
+                )));
+            }
+
+            value
+                .try_into_owned()
+                .map_err(|e| fdo::Error::Failed(e.to_string()))
+        });
+
+        Some(value)
+    }
+
+    async fn call(&self, msg: &Message, method: &Method) -> fdo::Result<Vec<Value<'static>>> {
+        let body = msg.body();
+        if body.signature() != &method.inputs.signature {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Expected arguments of type `{}`, got `{}`",
+                method.inputs.signature.to_string_no_parens(),
+                body.signature().to_string_no_parens(),
+            )));
+        }
+        let args = if method.inputs.args.is_empty() {
+            vec![]
+        } else {
+            // Deserialize with the signatures of the individual arguments, as the body signature
+            // doesn't tell apart a single structure argument from multiple arguments.
+            let signature = Signature::structure(method.inputs.args.clone());
+            let (OwnedStructure(args), _) = body
+                .data()
+                .deserialize_for_dynamic_signature(signature)
+                .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+
+            args.into_fields()
+        };
+
+        (method.handler)(args).await
+    }
+}
+
+/// A builder for [`DynamicInterface`].
+pub struct DynamicInterfaceBuilder(Inner);
+
+impl DynamicInterfaceBuilder {
+    /// Register a method named `name`.
+    ///
+    /// `inputs` and `outputs` are the signatures of the arguments and return values of the method.
+    /// `handler` is called with the arguments of each call, and returns the return values.
+    ///
+    /// Returns an error if the name or signatures are invalid, or a method with this name was
+    /// already registered.
+    pub fn method<N, F, Fut>(
+        mut self,
+        name: N,
+        inputs: &str,
+        outputs: &str,
+        handler: F,
+    ) -> Result<Self>
+    where
+        N: TryInto<MemberName<'static>>,
+        N::Error: Into<Error>,
+        F: Fn(Vec<Value<'static>>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<Vec<Value<'static>>>> + Send + 'static,
+    {
+        let name = name.try_into().map_err(Into::into)?.to_string();
+        if self.0.methods.contains_key(&name) {
+            return Err(Error::Failure(format!(
+                "Method `{name}` already registered"
+            )));
+        }
+        let method = Method {
+            inputs: Args::parse(inputs)?,
+            outputs: Args::parse(outputs)?,
+            handler: Box::new(move |args| Box::pin(handler(args))),
+        };
+        self.0.methods.insert(name, method);
+
+        Ok(self)
+    }
+
+    /// Register a read-only property named `name`, of type `ty`.
+    ///
+    /// `getter` is called to get the value of the property.
+    ///
+    /// Returns an error if the name or type are invalid, or a property with this name was already
+    /// registered.
+    pub fn property<N, S, G>(self, name: N, ty: S, getter: G) -> Result<Self>
+    where
+        N: TryInto<PropertyName<'static>>,
+        N::Error: Into<Error>,
+        S: TryInto<Signature>,
+        S::Error: Into<Error>,
+        G: Fn() -> fdo::Result<Value<'static>> + Send + Sync + 'static,
+    {
+        self.add_property(name, ty, Box::new(getter), None)
+    }
+
+    /// Register a read-write property named `name`, of type `ty`.
+    ///
+    /// `getter` is called to get the value of the property and `setter` to set it. Values are
+    /// checked against `ty` before they are passed to `setter`, and the `PropertiesChanged` signal
+    /// is emitted after each successful call to `setter`.
+    ///
+    /// Returns an error if the name or type are invalid, or a property with this name was already
+    /// registered.
+    pub fn writable_property<N, S, G, W>(self, name: N, ty: S, getter: G, setter: W) -> Result<Self>
+    where
+        N: TryInto<PropertyName<'static>>,
+        N::Error: Into<Error>,
+        S: TryInto<Signature>,
+        S::Error: Into<Error>,
+        G: Fn() -> fdo::Result<Value<'static>> + Send + Sync + 'static,
+        W: Fn(Value<'static>) -> fdo::Result<()> + Send + Sync + 'static,
+    {
+        self.add_property(name, ty, Box::new(getter), Some(Box::new(setter)))
+    }
+
+    fn add_property<N, S>(
+        mut self,
+        name: N,
+        ty: S,
+        getter: PropertyGetter,
+        setter: Option<PropertySetter>,
+    ) -> Result<Self>
+    where
+        N: TryInto<PropertyName<'static>>,
+        N::Error: Into<Error>,
+        S: TryInto<Signature>,
+        S::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?.to_string();
+        if self.0.properties.contains_key(&name) {
+            return Err(Error::Failure(format!(
+                "Property `{name}` already registered"
+            )));
+        }
+        let property = Property {
+            ty: ty.try_into().map_err(Into::into)?,
+            getter,
+            setter,
+        };
+        self.0.properties.insert(name, property);
+
+        Ok(self)
+    }
+
+    /// Register a signal named `name`, with arguments of the given signature.
+    ///
+    /// Returns an error if the name or signature are invalid, or a signal with this name was
+    /// already registered.
+    pub fn signal<N>(mut self, name: N, args: &str) -> Result<Self>
+    where
+        N: TryInto<MemberName<'static>>,
+        N::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?.to_string();
+        if self.0.signals.contains_key(&name) {
+            return Err(Error::Failure(format!(
+                "Signal `{name}` already registered"
+            )));
+        }
+        self.0.signals.insert(name, Args::parse(args)?);
+
+        Ok(self)
+    }
+
+    /// Build the interface.
+    pub fn build(self) -> DynamicInterface {
+        DynamicInterface(Arc::new(self.0))
+    }
+}
+
+impl fmt::Debug for DynamicInterfaceBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicInterfaceBuilder")
+            .field("name", &self.0.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The [`Interface`] implementation of a registered [`DynamicInterface`].
+///
+/// [`Interface::name`] can't provide the name of a dynamic interface, so instances are always
+/// registered under the runtime name of their [`DynamicInterface`]. This type isn't public, so it
+/// can't be used with the APIs relying on [`Interface::name`] (e.g. [`ObjectServer::interface`]).
+pub(crate) struct Dispatcher(pub DynamicInterface);
+
+/// The placeholder for [`Interface::name`] of [`Dispatcher`], never used to register it.
+const DISPATCHER_NAME: &str = "org.zbus.DynamicInterface";
+
+#[async_trait]
+impl Interface for Dispatcher {
+    fn name() -> InterfaceName<'static> {
+        InterfaceName::from_static_str_unchecked(DISPATCHER_NAME)
+    }
+
+    async fn get(
+        &self,
+        property_name: &str,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<OwnedValue>> {
+        self.0.0.get(property_name)
+    }
+
+    async fn get_all(
+        &self,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>> {
+        self.0
+            .0
+            .properties
+            .keys()
+            .map(|name| Ok((name.clone(), self.0.0.get(name).unwrap()?)))
+            .collect()
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        _server: &'call ObjectServer,
+        _connection: &'call Connection,
+        _header: Option<&'call Header<'_>>,
+        emitter: &'call SignalEmitter<'_>,
+    ) -> DispatchResult<'call> {
+        let Some(property) = self.0.0.properties.get(property_name) else {
+            return DispatchResult::NotFound;
+        };
+
+        DispatchResult::Async(Box::pin(async move {
+            let Some(setter) = &property.setter else {
+                return Err(fdo::Error::PropertyReadOnly(format!(
+                    "Property `{property_name}` is read-only"
+                ))
+                .into());
+            };
+            if value.value_signature() != &property.ty {
+                return Err(fdo::Error::InvalidArgs(format!(
+                    "Expected a value of type `{}`, got `{}`",
+                    property.ty,
+                    value.value_signature(),
+                ))
+                .into());
+            }
+            setter(value.try_to_owned()?.into())?;
+
+            self.0.property_changed(emitter, property_name).await
+        }))
+    }
+
+    async fn set_mut(
+        &mut self,
+        _property_name: &str,
+        _value: &Value<'_>,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<()>> {
+        // `set` handles all the properties.
+        None
+    }
+
+    fn call<'call>(
+        &'call self,
+        _server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        let Some(method) = self.0.0.methods.get(name.as_str()) else {
+            return DispatchResult::NotFound;
+        };
+
+        DispatchResult::Async(Box::pin(async move {
+            let ret = self.0.0.call(msg, method).await.and_then(|values| {
+                method
+                    .outputs
+                    .body(values)
+                    .map_err(|e| fdo::Error::Failed(format!("Invalid return values: {e}")))
+            });
+            let hdr = msg.header();
+            if hdr.primary().flags().contains(Flags::NoReplyExpected) {
+                trace!("No reply expected for {:?} by the caller.", msg);
+                return Ok(());
+            }
+            match ret {
+                Ok(Some(body)) => connection.reply(&hdr, &body).await,
+                Ok(None) => connection.reply(&hdr, &()).await,
+                Err(e) => connection.reply_dbus_error(&hdr, e).await,
+            }
+            .map(|_seq| ())
+        }))
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        _server: &'call ObjectServer,
+        _connection: &'call Connection,
+        _msg: &'call Message,
+        _name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        DispatchResult::NotFound
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        let iface = &self.0.0;
+        writeln!(
+            writer,
+            "{:indent$}<interface name=\"{}\">",
+            "",
+            iface.name,
+            indent = level
+        )
+        .unwrap();
+        {
+            let level = level + 2;
+            for (name, method) in &iface.methods {
+                writeln!(
+                    writer,
+                    "{:indent$}<method name=\"{name}\">",
+                    "",
+                    indent = level
+                )
+                .unwrap();
+                method.inputs.introspect(writer, Some("in"), level + 2);
+                method.outputs.introspect(writer, Some("out"), level + 2);
+                writeln!(writer, "{:indent$}</method>", "", indent = level).unwrap();
+            }
+            for (name, args) in &iface.signals {
+                writeln!(
+                    writer,
+                    "{:indent$}<signal name=\"{name}\">",
+                    "",
+                    indent = level
+                )
+                .unwrap();
+                args.introspect(writer, None, level + 2);
+                writeln!(writer, "{:indent$}</signal>", "", indent = level).unwrap();
+            }
+            for (name, property) in &iface.properties {
+                let access = if property.setter.is_some() {
+                    "readwrite"
+                } else {
+                    "read"
+                };
+                writeln!(
+                    writer,
+                    "{:indent$}<property name=\"{name}\" type=\"{}\" access=\"{access}\"/>",
+                    "",
+                    property.ty,
+                    indent = level
+                )
+                .unwrap();
+            }
+        }
+        writeln!(writer, "{:indent$}</interface>", "", indent = level).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::proxy::{self, CacheProperties};
+
+    #[test]
+    #[timeout(15000)]
+    fn dynamic_interface() {
+        crate::block_on(dynamic_interface_async());
+    }
+
+    async fn dynamic_interface_async() {
+        let greeting = Arc::new(Mutex::new(String::from("Hello")));
+        let getter_greeting = greeting.clone();
+        let iface = DynamicInterface::builder("org.zbus.Dynamic1")
+            .unwrap()
+            .method("Add", "ii", "i", |args| async move {
+                match &args[..] {
+                    [Value::I32(a), Value::I32(b)] => Ok(vec![Value::from(a + b)]),
+                    _ => unreachable!(),
+                }
+            })
+            .unwrap()
+            .method("Swap", "(su)", "(us)", |mut args| async move {
+                let Some(Value::Structure(pair)) = args.pop() else {
+                    unreachable!();
+                };
+                let swapped = pair
+                    .into_fields()
+                    .into_iter()
+                    .rev()
+                    .fold(StructureBuilder::new(), StructureBuilder::append_field)
+                    .build()
+                    .map_err(crate::Error::from)?;
+                Ok(vec![Value::from(swapped)])
+            })
+            .unwrap()
+            .method("Broken", "", "s", |_| async move {
+                Ok(vec![Value::from(42u32)])
+            })
+            .unwrap()
+            .property("Version", "u", || Ok(Value::from(1u32)))
+            .unwrap()
+            .writable_property(
+                "Greeting",
+                "s",
+                move || Ok(Value::from(getter_greeting.lock().unwrap().clone())),
+                move |value| {
+                    *greeting.lock().unwrap() =
+                        String::try_from(value).map_err(crate::Error::from)?;
+                    Ok(())
+                },
+            )
+            .unwrap()
+            .signal("Greeted", "su")
+            .unwrap()
+            .build();
+        assert!(
+            DynamicInterface::builder("org.zbus.Dynamic1")
+                .unwrap()
+                .signal("Greeted", "")
+                .unwrap()
+                .signal("Greeted", "s")
+                .is_err()
+        );
+
+        let service = crate::Connection::session().await.unwrap();
+        assert!(
+            service
+                .object_server()
+                .at_dynamic("/org/zbus/Dynamic", iface.clone())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !service
+                .object_server()
+                .at_dynamic("/org/zbus/Dynamic", iface.clone())
+                .await
+                .unwrap()
+        );
+        service
+            .request_name("org.zbus.DynamicInterfaceTest")
+            .await
+            .unwrap();
+
+        let client = crate::Connection::session().await.unwrap();
+        let proxy: crate::Proxy<'_> = proxy::Builder::new(&client)
+            .destination("org.zbus.DynamicInterfaceTest")
+            .unwrap()
+            .path("/org/zbus/Dynamic")
+            .unwrap()
+            .interface("org.zbus.Dynamic1")
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+
+        let sum: i32 = proxy.call("Add", &(40i32, 2i32)).await.unwrap();
+        assert_eq!(sum, 42);
+        let err = proxy.call::<_, _, i32>("Add", &(40u32,)).await.unwrap_err();
+        assert!(matches!(fdo::Error::from(err), fdo::Error::InvalidArgs(_)));
+        let swapped: ((u32, String),) = proxy.call("Swap", &(("one", 1u32),)).await.unwrap();
+        assert_eq!(swapped, ((1, "one".to_string()),));
+        let err = proxy.call::<_, _, String>("Broken", &()).await.unwrap_err();
+        assert!(matches!(fdo::Error::from(err), fdo::Error::Failed(_)));
+
+        assert_eq!(proxy.get_property::<u32>("Version").await.unwrap(), 1);
+        let err = proxy.set_property("Version", 2u32).await.unwrap_err();
+        assert!(matches!(err, fdo::Error::PropertyReadOnly(_)));
+        let err = proxy.set_property("Greeting", 2u32).await.unwrap_err();
+        assert!(matches!(err, fdo::Error::InvalidArgs(_)));
+
+        let props = fdo::PropertiesProxy::builder(&client)
+            .destination("org.zbus.DynamicInterfaceTest")
+            .unwrap()
+            .path("/org/zbus/Dynamic")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let mut changes = props.receive_properties_changed().await.unwrap();
+        proxy.set_property("Greeting", "Bonjour").await.unwrap();
+        assert_eq!(
+            proxy.get_property::<String>("Greeting").await.unwrap(),
+            "Bonjour"
+        );
+        let changed = changes.next().await.unwrap();
+        let args = changed.args().unwrap();
+        assert_eq!(args.interface_name(), "org.zbus.Dynamic1");
+        assert_eq!(
+            args.changed_properties()["Greeting"],
+            Value::from("Bonjour")
+        );
+
+        let mut greeted = proxy.receive_signal("Greeted").await.unwrap();
+        let emitter = SignalEmitter::new(&service, "/org/zbus/Dynamic").unwrap();
+        assert!(
+            iface
+                .emit(&emitter, "Greeted", vec![Value::from("x")])
+                .await
+                .is_err()
+        );
+        iface
+            .emit(
+                &emitter,
+                "Greeted",
+                vec![Value::from("you"), Value::from(3u32)],
+            )
+            .await
+            .unwrap();
+        let signal = greeted.next().await.unwrap();
+        let (name, count): (String, u32) = signal.body().deserialize().unwrap();
+        assert_eq!((name.as_str(), count), ("you", 3));
+
+        let xml = proxy.introspect().await.unwrap();
+        assert!(xml.contains(r#"<interface name="org.zbus.Dynamic1">"#));
+        assert!(xml.contains(r#"<arg type="(su)" direction="in"/>"#));
+        assert!(xml.contains(r#"<property name="Greeting" type="s" access="readwrite"/>"#));
+        assert!(xml.contains(r#"<property name="Version" type="u" access="read"/>"#));
+
+        assert!(
+            service
+                .object_server()
+                .remove_dynamic("/org/zbus/Dynamic", "org.zbus.Dynamic1")
+                .await
+                .unwrap()
+        );
+        let err = proxy
+            .call::<_, _, i32>("Add", &(40i32, 2i32))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            fdo::Error::from(err),
+            fdo::Error::UnknownObject(_)
+        ));
+    }
+}
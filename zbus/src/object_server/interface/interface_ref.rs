@@ -1,7 +1,7 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::{future::Future, marker::PhantomData, sync::Arc};
 
 use super::{Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter};
-use crate::async_lock::RwLock;
+use crate::{Result, async_lock::RwLock, object_server::PropertiesBatch};
 
 /// Wrapper over an interface, along with its corresponding `SignalEmitter`
 /// instance. A reference to the underlying interface may be obtained via
@@ -99,6 +99,81 @@ where
         &self.emitter
     }
 
+    /// Coalesce property changes into a single `PropertiesChanged` signal per interface.
+    ///
+    /// `f` is passed a signal emitter, through which property changes are recorded instead of
+    /// being emitted right away. This applies to the `<property>_changed` and
+    /// `<property>_invalidate` methods generated by the [`interface`] macro, as well as to
+    /// [`SignalEmitter::emit_properties_changed`], but not to
+    /// [`Properties::properties_changed`], which always emits the signal right away. Once the
+    /// future returned by `f` completes, the recorded changes are emitted, with a single signal per
+    /// interface. Multiple changes to the same property are merged, only keeping the latest one.
+    /// Changes made through the emitter afterwards (e.g through a clone of it) are emitted right
+    /// away.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use zbus::{Connection, interface};
+    /// #
+    /// struct Sensor {
+    ///     temperature: f64,
+    ///     humidity: f64,
+    /// }
+    ///
+    /// #[interface(name = "org.zbus.Sensor")]
+    /// impl Sensor {
+    ///     #[zbus(property)]
+    ///     fn temperature(&self) -> f64 {
+    ///         self.temperature
+    ///     }
+    ///
+    ///     #[zbus(property)]
+    ///     fn humidity(&self) -> f64 {
+    ///         self.humidity
+    ///     }
+    /// }
+    ///
+    /// # zbus::block_on(async {
+    /// # let connection = Connection::session().await?;
+    /// # let path = "/org/zbus/Sensor";
+    /// # let sensor = Sensor { temperature: 0., humidity: 0. };
+    /// # connection.object_server().at(path, sensor).await?;
+    /// let iface_ref = connection
+    ///     .object_server()
+    ///     .interface::<_, Sensor>(path)
+    ///     .await?;
+    /// let sensor_ref = iface_ref.clone();
+    /// iface_ref
+    ///     .batch(|emitter| async move {
+    ///         let mut sensor = sensor_ref.get_mut().await;
+    ///         sensor.temperature = 21.5;
+    ///         sensor.temperature_changed(&emitter).await?;
+    ///         sensor.humidity = 0.4;
+    ///         sensor.humidity_changed(&emitter).await
+    ///     })
+    ///     .await??;
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// # })?;
+    /// #
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    ///
+    /// [`interface`]: crate::interface
+    /// [`Properties::properties_changed`]: crate::fdo::Properties::properties_changed
+    pub async fn batch<F, Fut, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(SignalEmitter<'static>) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        let batch = Arc::new(PropertiesBatch::default());
+        let res = f(self.emitter.clone().with_batch(batch.clone())).await;
+        batch.emit(&self.emitter).await?;
+
+        Ok(res)
+    }
+
     #[deprecated(since = "0.5.0", note = "Please use `signal_emitter` instead.")]
     pub fn signal_context(&self) -> &SignalEmitter<'static> {
         &self.emitter
@@ -114,3 +189,113 @@ impl<I> Clone for InterfaceRef<I> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use crate::{Result, connection, fdo::PropertiesProxy, interface, utils::block_on};
+
+    struct Sensor {
+        temperature: u32,
+        humidity: u32,
+        mode: String,
+    }
+
+    #[interface(name = "org.zbus.BatchTest")]
+    impl Sensor {
+        #[zbus(property)]
+        fn temperature(&self) -> u32 {
+            self.temperature
+        }
+
+        #[zbus(property)]
+        fn humidity(&self) -> u32 {
+            self.humidity
+        }
+
+        #[zbus(property(emits_changed_signal = "invalidates"))]
+        fn mode(&self) -> &str {
+            &self.mode
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn batch() {
+        block_on(test_batch()).unwrap();
+    }
+
+    async fn test_batch() -> Result<()> {
+        let sensor = Sensor {
+            temperature: 0,
+            humidity: 0,
+            mode: "idle".into(),
+        };
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/BatchTest", sensor)?
+            .build()
+            .await?;
+        let client = connection::Builder::session()?.build().await?;
+        let proxy = PropertiesProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .path("/org/zbus/BatchTest")?
+            .build()
+            .await?;
+        let mut changes = proxy.receive_properties_changed().await?;
+
+        let iface_ref = service
+            .object_server()
+            .interface::<_, Sensor>("/org/zbus/BatchTest")
+            .await?;
+        let sensor_ref = iface_ref.clone();
+        let mut late_emitter = None;
+        iface_ref
+            .batch(|emitter| {
+                late_emitter = Some(emitter.clone());
+
+                async move {
+                    let mut sensor = sensor_ref.get_mut().await;
+                    for temperature in [20, 21] {
+                        sensor.temperature = temperature;
+                        sensor.temperature_changed(&emitter).await?;
+                    }
+                    sensor.humidity = 40;
+                    sensor.humidity_changed(&emitter).await?;
+                    sensor.mode = "active".into();
+                    sensor.mode_invalidate(&emitter).await
+                }
+            })
+            .await??;
+        // Outside of a batch, changes are emitted right away.
+        let mut sensor = iface_ref.get_mut().await;
+        sensor.humidity = 50;
+        sensor.humidity_changed(iface_ref.signal_emitter()).await?;
+        // As well as once the batch was emitted.
+        sensor.temperature = 22;
+        sensor.temperature_changed(&late_emitter.unwrap()).await?;
+
+        let signal = changes.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.interface_name(), "org.zbus.BatchTest");
+        let changed = args.changed_properties();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed["Temperature"], 21u32.into());
+        assert_eq!(changed["Humidity"], 40u32.into());
+        assert_eq!(**args.invalidated_properties(), ["Mode"]);
+
+        let signal = changes.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.changed_properties().len(), 1);
+        assert_eq!(args.changed_properties()["Humidity"], 50u32.into());
+
+        let signal = changes.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.changed_properties().len(), 1);
+        assert_eq!(args.changed_properties()["Temperature"], 22u32.into());
+
+        Ok(())
+    }
+}
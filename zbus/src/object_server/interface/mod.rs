@@ -138,6 +138,16 @@ pub trait Interface: Any + Send + Sync {
 
     /// Write introspection XML to the writer, with the given indentation level.
     fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize);
+
+    /// The action the given method is tied to, for authorization purposes.
+    ///
+    /// See [`crate::object_server::Authorizer`] for details. The default implementation returns
+    /// `None`.
+    fn authorization(&self, method: &MemberName<'_>) -> Option<&'static str> {
+        let _ = method;
+
+        None
+    }
 }
 
 /// A type for a reference-counted Interface trait-object, with associated run-time details and a
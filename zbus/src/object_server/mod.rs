@@ -1,10 +1,10 @@
 //! The object server API.
 
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{collections::HashMap, marker::PhantomData, sync::Arc, time::Duration};
 use tracing::{Instrument, debug, instrument, trace, trace_span};
 
-use zbus_names::InterfaceName;
-use zvariant::{ObjectPath, Value};
+use zbus_names::{InterfaceName, OwnedUniqueName};
+use zvariant::{ObjectPath, OwnedObjectPath, Value};
 
 use crate::{
     Connection, Error, Result,
@@ -15,11 +15,16 @@ use crate::{
     message::{Header, Message},
 };
 
+mod activity;
+use activity::Activity;
+pub use activity::BusyGuard;
+
 mod interface;
 pub(crate) use interface::ArcInterface;
 pub use interface::{DispatchResult, Interface, InterfaceDeref, InterfaceDerefMut, InterfaceRef};
 
 mod signal_emitter;
+pub(crate) use signal_emitter::PropertiesBatch;
 pub use signal_emitter::SignalEmitter;
 #[deprecated(since = "5.0.0", note = "Please use `SignalEmitter` instead.")]
 pub type SignalContext<'s> = SignalEmitter<'s>;
@@ -27,8 +32,24 @@ pub type SignalContext<'s> = SignalEmitter<'s>;
 mod dispatch_notifier;
 pub use dispatch_notifier::ResponseDispatchNotifier;
 
+mod authorization;
+use authorization::Authorization;
+pub use authorization::{AuthorizationRequest, Authorizer};
+
+mod dynamic_interface;
+pub use dynamic_interface::{DynamicInterface, DynamicInterfaceBuilder};
+
 mod node;
-pub(crate) use node::Node;
+pub(crate) use node::{Enumerated, Node};
+
+mod node_enumerator;
+pub use node_enumerator::NodeEnumerator;
+
+mod object_builder;
+pub use object_builder::ObjectBuilder;
+
+mod owners;
+use owners::{Cleanup, Owners};
 
 /// An object server, holding server-side D-Bus objects & interfaces.
 ///
@@ -91,6 +112,9 @@ pub(crate) use node::Node;
 pub struct ObjectServer {
     conn: WeakConnection,
     root: Arc<RwLock<Node>>,
+    authorization: Arc<Authorization>,
+    owners: Arc<Owners>,
+    activity: Arc<Activity>,
 }
 
 impl ObjectServer {
@@ -101,13 +125,118 @@ impl ObjectServer {
             root: Arc::new(RwLock::new(Node::new(
                 "/".try_into().expect("zvariant bug"),
             ))),
+            authorization: Arc::new(Authorization::default()),
+            owners: Arc::new(Owners::default()),
+            activity: Arc::new(Activity::new()),
         }
     }
 
+    /// Set the [`Authorizer`] to consult before dispatching method calls.
+    ///
+    /// This replaces the previously set authorizer, if any. See [`Authorizer`] for details.
+    pub fn set_authorizer<A>(&self, authorizer: A)
+    where
+        A: Authorizer,
+    {
+        self.set_arc_authorizer(Arc::new(authorizer));
+    }
+
+    pub(crate) fn set_arc_authorizer(&self, authorizer: Arc<dyn Authorizer>) {
+        self.authorization.set_authorizer(authorizer);
+    }
+
+    /// Call the node enumerators for the object at `path` and, if `recursive`, its descendants.
+    ///
+    /// The enumerators are called without holding the lock on the tree of nodes.
+    pub(crate) async fn enumerate(
+        &self,
+        path: &ObjectPath<'_>,
+        recursive: bool,
+    ) -> fdo::Result<Enumerated> {
+        let mut enumerated = Enumerated::new();
+        let mut paths = vec![OwnedObjectPath::from(path.to_owned())];
+        while let Some(path) = paths.pop() {
+            let enumerator = self.root.read().await.resolve(&path).enumerator();
+            if let Some(enumerator) = enumerator {
+                let children = enumerator.children(&path).await?;
+                enumerated.insert(path.clone(), children);
+            }
+            if recursive {
+                let root = self.root.read().await;
+                let children = root.resolve(&path).children(&enumerated);
+                paths.extend(children.iter().map(|child| child.path().clone()));
+            }
+        }
+
+        Ok(enumerated)
+    }
+
     pub(crate) fn root(&self) -> &RwLock<Node> {
         &self.root
     }
 
+    /// Keep the object server busy, as long as the returned guard is alive.
+    ///
+    /// The object server is busy while dispatching method calls, and while any of its objects is
+    /// owned by a peer (see [`ObjectBuilder::owned_by`]). Use this for any other work that should
+    /// prevent the service from exiting, e.g. a job started by a method call and still running
+    /// after it returned. See [`ObjectServer::idle`].
+    pub fn busy(&self) -> BusyGuard {
+        self.activity.busy()
+    }
+
+    /// Wait for the object server to be idle for the given duration.
+    ///
+    /// This is meant for services started on demand through [D-Bus activation][da], to exit once
+    /// they're not needed anymore. The object server is idle while it's not [busy][busy]. Once it
+    /// has been idle for `timeout`, all the well-known names of the connection are released,
+    /// including the ones held through a [`NameOwnership`], and the method calls received in the
+    /// meantime are handled, before this returns. Calls made
+    /// afterwards are queued by the bus until the service is activated again, instead of failing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # zbus::block_on(async {
+    /// use std::time::Duration;
+    /// use zbus::{connection, interface};
+    ///
+    /// struct Greeter;
+    ///
+    /// #[interface(name = "org.zbus.Greeter")]
+    /// impl Greeter {
+    ///     fn say_hello(&self, name: &str) -> String {
+    ///         format!("Hello {name}!")
+    ///     }
+    /// }
+    ///
+    /// let connection = connection::Builder::session()?
+    ///     .name("org.zbus.Greeter")?
+    ///     .serve_at("/org/zbus/Greeter", Greeter)?
+    ///     .build()
+    ///     .await?;
+    /// connection
+    ///     .object_server()
+    ///     .idle(Duration::from_secs(30))
+    ///     .await?;
+    /// // Not needed anymore, exit.
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// [da]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-bus-starting-services
+    /// [busy]: ObjectServer::busy
+    /// [`NameOwnership`]: crate::connection::NameOwnership
+    pub async fn idle(&self, timeout: Duration) -> Result<()> {
+        self.activity.wait_idle(timeout).await;
+        debug!("Object server idle for {timeout:?}, releasing names");
+        self.connection().release_all_names().await?;
+        // Calls received before the names were released still deserve a reply.
+        self.activity.wait_not_busy().await;
+
+        Ok(())
+    }
+
     /// Register a D-Bus [`Interface`] at a given path (see the example above).
     ///
     /// Typically you'd want your interfaces to be registered immediately after the associated
@@ -115,7 +244,8 @@ impl ObjectServer {
     /// However, there are situations where you'd need to register interfaces dynamically and that's
     /// where this method becomes useful.
     ///
-    /// If the interface already exists at this path, returns false.
+    /// If the interface already exists at this path, or the object at this path is owned by a peer
+    /// (see [`ObjectBuilder::owned_by`]), returns false.
     pub async fn at<'p, P, I>(&self, path: P, iface: I) -> Result<bool>
     where
         I: Interface,
@@ -126,12 +256,106 @@ impl ObjectServer {
             .await
     }
 
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// This is the equivalent of [`ObjectServer::at`] for interfaces whose members are only known
+    /// at runtime.
+    ///
+    /// If the interface already exists at this path, returns false.
+    pub async fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let name = iface.name().clone();
+        let arc_iface = ArcInterface::new(dynamic_interface::Dispatcher(iface));
+
+        self.add_arc_interface(path, name, arc_iface).await
+    }
+
     pub(crate) async fn add_arc_interface<'p, P>(
         &self,
         path: P,
         name: InterfaceName<'static>,
         arc_iface: ArcInterface,
     ) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        self.add_arc_interfaces(path, vec![(name, arc_iface)], None)
+            .await
+    }
+
+    /// Register an object with multiple interfaces at once.
+    ///
+    /// This returns an [`ObjectBuilder`], to add the interfaces to. The object is only registered
+    /// by [`ObjectBuilder::register`], with all its interfaces in one step. If the object is
+    /// managed by an [`ObjectManager`], a single `InterfacesAdded` signal is emitted for all of
+    /// them.
+    ///
+    /// See [`ObjectServer::remove_object`] for the atomic removal of an object.
+    pub fn object<'p, P>(&self, path: P) -> Result<ObjectBuilder<'_>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+
+        Ok(ObjectBuilder::new(self, path.into_owned()))
+    }
+
+    /// Register an object owned by the peer named `owner`, removing it once the owner vanishes.
+    pub(crate) async fn add_owned_arc_interfaces(
+        &self,
+        path: ObjectPath<'static>,
+        interfaces: Vec<(InterfaceName<'static>, ArcInterface)>,
+        owner: OwnedUniqueName,
+        cleanup: Option<Cleanup>,
+    ) -> Result<bool> {
+        // Owners can only be watched on a bus.
+        if !self.connection().is_bus() {
+            return Err(Error::Unsupported);
+        }
+        if !self
+            .add_arc_interfaces(path.clone(), interfaces, Some(owner.clone()))
+            .await?
+        {
+            return Ok(false);
+        }
+        if let Err(e) = self
+            .owners
+            .add(self, owner.clone(), path.clone().into(), cleanup)
+            .await
+        {
+            // Nothing would remove the object otherwise.
+            if let Err(e) = self.remove_object_of(&path, Some(&owner)).await {
+                debug!("Failed to remove the object at `{path}`: {e}");
+            }
+
+            return Err(e);
+        }
+
+        // The owner might have vanished before we started watching it.
+        let has_owner = fdo::DBusProxy::new(&self.connection())
+            .await?
+            .name_has_owner(owner.as_ref().into())
+            .await?;
+        if !has_owner {
+            self.owners.vanished(self, &owner).await;
+
+            return Err(fdo::Error::NameHasNoOwner(format!("`{owner}` is not connected")).into());
+        }
+
+        Ok(true)
+    }
+
+    pub(crate) async fn add_arc_interfaces<'p, P>(
+        &self,
+        path: P,
+        interfaces: Vec<(InterfaceName<'static>, ArcInterface)>,
+        owner: Option<OwnedUniqueName>,
+    ) -> Result<bool>
     where
         P: TryInto<ObjectPath<'p>>,
         P::Error: Into<Error>,
@@ -140,42 +364,65 @@ impl ObjectServer {
         let mut root = self.root().write().await;
         let (node, manager_path) = root.get_child_mut(&path, true);
         let node = node.unwrap();
-        let added = node.add_arc_interface(name.clone(), arc_iface);
-        if added {
+        let conflict = interfaces.iter().enumerate().any(|(i, (name, _))| {
+            node.interface_lock(name.clone()).is_some()
+                || interfaces[..i].iter().any(|(n, _)| n == name)
+        });
+        // All the interfaces of an owned object are removed with it, so it can't share its path.
+        if conflict || node.owner().is_some() || (owner.is_some() && node.has_interfaces()) {
+            return Ok(false);
+        }
+        if owner.is_some() {
+            node.set_owner(owner);
+        }
+
+        let mut added_manager = false;
+        let mut names = Vec::with_capacity(interfaces.len());
+        for (name, arc_iface) in interfaces {
+            node.add_arc_interface(name.clone(), arc_iface);
             if name == ObjectManager::name() {
-                // Just added an object manager. Need to signal all managed objects under it.
-                let emitter = SignalEmitter::new(&self.connection(), path)?;
-                let objects = node.get_managed_objects(self, &self.connection()).await?;
-                for (path, owned_interfaces) in objects {
-                    let interfaces = owned_interfaces
-                        .iter()
-                        .map(|(i, props)| {
-                            let props = props
-                                .iter()
-                                .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
-                                .collect::<Result<_>>();
-                            Ok((i.into(), props?))
-                        })
-                        .collect::<Result<_>>()?;
-                    ObjectManager::interfaces_added(&emitter, path.into(), interfaces).await?;
-                }
-            } else if let Some(manager_path) = manager_path {
-                let emitter = SignalEmitter::new(&self.connection(), manager_path.clone())?;
-                let mut interfaces = HashMap::new();
-                let owned_props = node
+                added_manager = true;
+            } else {
+                names.push(name);
+            }
+        }
+
+        if let Some(manager_path) = manager_path.filter(|_| !names.is_empty()) {
+            let emitter = SignalEmitter::new(&self.connection(), manager_path.clone())?;
+            let mut owned_interfaces = HashMap::new();
+            for name in names {
+                let props = node
                     .get_properties(self, &self.connection(), name.clone())
                     .await?;
-                let props = owned_props
+                owned_interfaces.insert(name, props);
+            }
+            let interfaces = owned_interfaces
+                .iter()
+                .map(|(name, props)| Ok((name.clone(), borrowed_properties(props)?)))
+                .collect::<Result<_>>()?;
+
+            ObjectManager::interfaces_added(&emitter, path.clone(), interfaces).await?;
+        }
+
+        if added_manager {
+            // Just added an object manager. Need to signal all managed objects under it.
+            let emitter = SignalEmitter::new(&self.connection(), path.clone())?;
+            // The enumerators aren't called with the lock held, so the enumerated objects aren't
+            // signaled.
+            let objects = root
+                .resolve(&path)
+                .get_managed_objects(self, &self.connection(), &Enumerated::new())
+                .await?;
+            for (path, owned_interfaces) in objects {
+                let interfaces = owned_interfaces
                     .iter()
-                    .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                    .map(|(i, props)| Ok((i.into(), borrowed_properties(props)?)))
                     .collect::<Result<_>>()?;
-                interfaces.insert(name, props);
-
-                ObjectManager::interfaces_added(&emitter, path, interfaces).await?;
+                ObjectManager::interfaces_added(&emitter, path.into(), interfaces).await?;
             }
         }
 
-        Ok(added)
+        Ok(true)
     }
 
     /// Unregister a D-Bus [`Interface`] at a given path.
@@ -187,33 +434,201 @@ impl ObjectServer {
         I: Interface,
         P: TryInto<ObjectPath<'p>>,
         P::Error: Into<Error>,
+    {
+        self.remove_interface(path, I::name()).await
+    }
+
+    /// Unregister the interface named `name`, typically a [`DynamicInterface`], at a given path.
+    ///
+    /// If there are no more interfaces left at that path, destroys the object as well.
+    /// Returns whether the object was destroyed.
+    pub async fn remove_dynamic<'p, P, N>(&self, path: P, name: N) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        N: TryInto<InterfaceName<'static>>,
+        N::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+
+        self.remove_interface(path, name).await
+    }
+
+    async fn remove_interface<'p, P>(&self, path: P, name: InterfaceName<'static>) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
     {
         let path = path.try_into().map_err(Into::into)?;
         let mut root = self.root.write().await;
         let (node, manager_path) = root.get_child_mut(&path, false);
         let node = node.ok_or(Error::InterfaceNotFound)?;
-        if !node.remove_interface(I::name()) {
+        let owner = node.owner().map(|o| OwnedUniqueName::from(o.clone()));
+        if !node.remove_interface(name.clone()) {
             return Err(Error::InterfaceNotFound);
         }
+        if let Some(owner) = owner.filter(|_| node.owner().is_none()) {
+            self.owners.forget(&owner, &path.to_owned().into());
+        }
         if let Some(manager_path) = manager_path {
             let ctxt = SignalEmitter::new(&self.connection(), manager_path.clone())?;
-            ObjectManager::interfaces_removed(&ctxt, path.clone(), (&[I::name()]).into()).await?;
+            ObjectManager::interfaces_removed(&ctxt, path.clone(), (&[name]).into()).await?;
         }
         if node.is_empty() {
-            let mut path_parts = path.rsplit('/').filter(|i| !i.is_empty());
-            let last_part = path_parts.next().unwrap();
-            let ppath = ObjectPath::from_string_unchecked(
-                path_parts.fold(String::new(), |a, p| format!("/{p}{a}")),
-            );
-            root.get_child_mut(&ppath, false)
-                .0
-                .unwrap()
-                .remove_node(last_part);
+            remove_node(&mut root, &path);
             return Ok(true);
         }
         Ok(false)
     }
 
+    /// Unregister the object at the given path, with all its interfaces at once.
+    ///
+    /// If the object is managed by an [`ObjectManager`], a single `InterfacesRemoved` signal is
+    /// emitted for all its interfaces. The object is destroyed, unless it has children objects.
+    /// Returns whether the object was destroyed.
+    ///
+    /// # Errors
+    ///
+    /// If there is no object with interfaces at the given path, an `Error::InterfaceNotFound` error
+    /// is returned.
+    pub async fn remove_object<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+
+        self.remove_object_of(&path, None).await
+    }
+
+    /// Remove the object at `path`, only if owned by `owner`, if specified.
+    pub(crate) async fn remove_object_of(
+        &self,
+        path: &ObjectPath<'_>,
+        owner: Option<&OwnedUniqueName>,
+    ) -> Result<bool> {
+        let mut root = self.root.write().await;
+        let (node, manager_path) = root.get_child_mut(path, false);
+        let node = node.ok_or(Error::InterfaceNotFound)?;
+        if owner.is_some_and(|owner| node.owner() != Some(owner)) {
+            return Err(Error::InterfaceNotFound);
+        }
+        let node_owner = node.owner().map(|o| OwnedUniqueName::from(o.clone()));
+        let mut names = node.remove_all_interfaces();
+        if names.is_empty() {
+            return Err(Error::InterfaceNotFound);
+        }
+        // Unless the owner vanished, it doesn't need to be watched for this object anymore.
+        if let (None, Some(node_owner)) = (owner, node_owner) {
+            self.owners.forget(&node_owner, &path.to_owned().into());
+        }
+        names.retain(|name| *name != ObjectManager::name());
+        if let Some(manager_path) = manager_path.filter(|_| !names.is_empty()) {
+            let ctxt = SignalEmitter::new(&self.connection(), manager_path.clone())?;
+            ObjectManager::interfaces_removed(&ctxt, path.clone(), names.into()).await?;
+        }
+
+        Ok(remove_unused_node(&mut root, path))
+    }
+
+    /// Register a fallback D-Bus [`Interface`] for all the objects under a path prefix.
+    ///
+    /// Calls to the interface on any descendant of `prefix` are dispatched to `iface`, unless the
+    /// called object has an interface of the same name registered. This allows serving a large
+    /// number of objects, materialized on demand, without registering each of them. The called
+    /// object path is available to the methods through the `#[zbus(header)]` argument, and to the
+    /// properties through the `#[zbus(signal_emitter)]` argument.
+    ///
+    /// Since the objects aren't registered, they're not listed by the introspection of their
+    /// parents, nor by an [`ObjectManager`], unless a [`NodeEnumerator`] is set through
+    /// [`ObjectServer::set_node_enumerator`]. No `InterfacesAdded` signal is emitted for them
+    /// either.
+    ///
+    /// If a fallback for this interface already exists at this prefix, returns false.
+    pub async fn at_fallback<'p, P, I>(&self, prefix: P, iface: I) -> Result<bool>
+    where
+        I: Interface,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let prefix = prefix.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let (node, _) = root.get_child_mut(&prefix, true);
+
+        Ok(node
+            .unwrap()
+            .add_fallback(I::name(), ArcInterface::new(iface)))
+    }
+
+    /// Unregister a fallback D-Bus [`Interface`] at a given path prefix.
+    ///
+    /// If nothing else is left at that path, destroys the object as well. Returns whether the
+    /// object was destroyed.
+    ///
+    /// # Errors
+    ///
+    /// If there is no such fallback at the given prefix, an `Error::InterfaceNotFound` error is
+    /// returned.
+    pub async fn remove_fallback<'p, I, P>(&self, prefix: P) -> Result<bool>
+    where
+        I: Interface,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let prefix = prefix.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let (node, _) = root.get_child_mut(&prefix, false);
+        let node = node.ok_or(Error::InterfaceNotFound)?;
+        if !node.remove_fallback(&I::name()) {
+            return Err(Error::InterfaceNotFound);
+        }
+
+        Ok(remove_unused_node(&mut root, &prefix))
+    }
+
+    /// Set the [`NodeEnumerator`] listing the objects under a path prefix.
+    ///
+    /// The enumerator is used for the objects at `prefix` and under it, unless a descendant has
+    /// its own. This replaces the previously set enumerator at this prefix, if any.
+    pub async fn set_node_enumerator<'p, P, E>(&self, prefix: P, enumerator: E) -> Result<()>
+    where
+        E: NodeEnumerator,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let prefix = prefix.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let (node, _) = root.get_child_mut(&prefix, true);
+        node.unwrap().set_enumerator(Some(Arc::new(enumerator)));
+
+        Ok(())
+    }
+
+    /// Unset the [`NodeEnumerator`] at a given path prefix.
+    ///
+    /// If nothing else is left at that path, destroys the object as well. Returns whether the
+    /// object was destroyed.
+    ///
+    /// # Errors
+    ///
+    /// If there is no enumerator set at the given prefix, an `Error::InterfaceNotFound` error is
+    /// returned.
+    pub async fn remove_node_enumerator<'p, P>(&self, prefix: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let prefix = prefix.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let (node, _) = root.get_child_mut(&prefix, false);
+        let node = node.ok_or(Error::InterfaceNotFound)?;
+        if node.set_enumerator(None).is_none() {
+            return Err(Error::InterfaceNotFound);
+        }
+
+        Ok(remove_unused_node(&mut root, &prefix))
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -295,6 +710,25 @@ impl ObjectServer {
         connection: &Connection,
         msg: &Message,
         hdr: &Header<'_>,
+    ) -> fdo::Result<()> {
+        let start = std::time::Instant::now();
+        let res = self.call_iface(iface, connection, msg, hdr).await;
+        if let Some(iface_name) = hdr.interface() {
+            connection
+                .inner
+                .stats
+                .method_dispatched(iface_name, start.elapsed());
+        }
+
+        res
+    }
+
+    async fn call_iface(
+        &self,
+        iface: Arc<RwLock<dyn Interface>>,
+        connection: &Connection,
+        msg: &Message,
+        hdr: &Header<'_>,
     ) -> fdo::Result<()> {
         let member = hdr
             .member()
@@ -303,6 +737,9 @@ impl ObjectServer {
             .interface()
             .ok_or_else(|| fdo::Error::Failed("Missing interface".into()))?;
 
+        let action = iface.read().await.authorization(member);
+        self.authorization.check(connection, hdr, action).await?;
+
         trace!("acquiring read lock on interface `{}`", iface_name);
         let read_lock = iface.read().await;
         trace!("acquired read lock on interface `{}`", iface_name);
@@ -346,6 +783,7 @@ impl ObjectServer {
         msg: &Message,
         hdr: &Header<'_>,
     ) -> fdo::Result<()> {
+        let busy = self.busy();
         let path = hdr
             .path()
             .ok_or_else(|| fdo::Error::Failed("Missing object path".into()))?;
@@ -379,13 +817,7 @@ impl ObjectServer {
                 path.clone()
             };
 
-            let node = root
-                .get_child(&path)
-                .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))?;
-
-            let iface = node.interface_lock(iface_name.as_ref()).ok_or_else(|| {
-                fdo::Error::UnknownInterface(format!("Unknown interface '{iface_name}'"))
-            })?;
+            let iface = root.resolve(&path).interface(iface_name)?;
             (iface.instance, iface.spawn_tasks_for_methods)
         };
 
@@ -397,6 +829,7 @@ impl ObjectServer {
             executor
                 .spawn(
                     async move {
+                        let _busy = busy;
                         let server = connection.object_server();
                         let hdr = msg.header();
                         if let Err(e) = server
@@ -456,6 +889,43 @@ impl ObjectServer {
     }
 }
 
+/// Remove the node at `path`, which must exist, from its parent.
+fn remove_node(root: &mut Node, path: &ObjectPath<'_>) {
+    let mut path_parts = path.rsplit('/').filter(|i| !i.is_empty());
+    let last_part = path_parts.next().unwrap();
+    let ppath = ObjectPath::from_string_unchecked(
+        path_parts.fold(String::new(), |a, p| format!("/{p}{a}")),
+    );
+    root.get_child_mut(&ppath, false)
+        .0
+        .unwrap()
+        .remove_node(last_part);
+}
+
+/// Remove the node at `path`, which must exist, if nothing is left in it.
+///
+/// Returns whether the node was removed.
+fn remove_unused_node(root: &mut Node, path: &ObjectPath<'_>) -> bool {
+    let (node, _) = root.get_child_mut(path, false);
+    let node = node.unwrap();
+    if !node.is_empty() || node.has_children() || path.as_str() == "/" {
+        return false;
+    }
+    remove_node(root, path);
+
+    true
+}
+
+/// Borrow the owned property values, as expected by the `InterfacesAdded` signal.
+fn borrowed_properties(
+    properties: &HashMap<String, zvariant::OwnedValue>,
+) -> Result<HashMap<&str, Value<'_>>> {
+    properties
+        .iter()
+        .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+        .collect()
+}
+
 #[cfg(feature = "blocking-api")]
 impl From<crate::blocking::ObjectServer> for ObjectServer {
     fn from(server: crate::blocking::ObjectServer) -> Self {
@@ -3,9 +3,11 @@
 use std::{
     collections::{BTreeMap, HashMap, btree_map, hash_map},
     fmt::Write,
+    sync::Arc,
 };
 
-use zbus_names::InterfaceName;
+use tracing::debug;
+use zbus_names::{InterfaceName, OwnedUniqueName, UniqueName};
 use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
 
 use crate::{
@@ -14,13 +16,21 @@ use crate::{
     object_server::SignalEmitter,
 };
 
-use super::{ArcInterface, Interface};
+use super::{ArcInterface, Interface, NodeEnumerator};
+
+/// The names of the children listed by the node enumerators, per path of their parent.
+pub(crate) type Enumerated = HashMap<OwnedObjectPath, Vec<String>>;
 
 #[derive(Default, Debug)]
 pub(crate) struct Node {
     path: OwnedObjectPath,
     children: HashMap<String, Node>,
     interfaces: BTreeMap<InterfaceName<'static>, ArcInterface>,
+    /// The interfaces of all the descendants, unless they have their own.
+    fallbacks: BTreeMap<InterfaceName<'static>, ArcInterface>,
+    enumerator: Option<Arc<dyn NodeEnumerator>>,
+    /// The peer owning the object, if any.
+    owner: Option<OwnedUniqueName>,
 }
 
 impl Node {
@@ -97,16 +107,118 @@ impl Node {
     }
 
     pub(super) fn remove_interface(&mut self, interface_name: InterfaceName<'static>) -> bool {
-        self.interfaces.remove(&interface_name).is_some()
+        if self.interfaces.remove(&interface_name).is_none() {
+            return false;
+        }
+        // An object without interfaces has no owner anymore.
+        if !self.has_interfaces() {
+            self.owner = None;
+        }
+
+        true
+    }
+
+    /// Whether there are interfaces, other than the standard ones.
+    pub(super) fn has_interfaces(&self) -> bool {
+        self.interfaces
+            .keys()
+            .any(|k| *k != Peer::name() && *k != Introspectable::name() && *k != Properties::name())
+    }
+
+    /// Remove all the interfaces, except for the standard ones, returning their names.
+    pub(super) fn remove_all_interfaces(&mut self) -> Vec<InterfaceName<'static>> {
+        let names: Vec<_> = self
+            .interfaces
+            .keys()
+            .filter(|k| {
+                **k != Peer::name() && **k != Introspectable::name() && **k != Properties::name()
+            })
+            .cloned()
+            .collect();
+        for name in &names {
+            self.interfaces.remove(name);
+        }
+        self.owner = None;
+
+        names
+    }
+
+    pub(super) fn owner(&self) -> Option<&UniqueName<'static>> {
+        self.owner.as_deref()
+    }
+
+    pub(super) fn set_owner(&mut self, owner: Option<OwnedUniqueName>) {
+        self.owner = owner;
+    }
+
+    pub(super) fn has_children(&self) -> bool {
+        !self.children.is_empty()
     }
 
     pub(super) fn is_empty(&self) -> bool {
-        !self.interfaces.keys().any(|k| {
-            *k != Peer::name()
-                && *k != Introspectable::name()
-                && *k != Properties::name()
-                && *k != ObjectManager::name()
-        })
+        !self.is_fallback()
+            && !self.interfaces.keys().any(|k| {
+                *k != Peer::name()
+                    && *k != Introspectable::name()
+                    && *k != Properties::name()
+                    && *k != ObjectManager::name()
+            })
+    }
+
+    /// Whether the descendants of this node are served, even if not registered.
+    fn is_fallback(&self) -> bool {
+        !self.fallbacks.is_empty() || self.enumerator.is_some()
+    }
+
+    pub(super) fn add_fallback(
+        &mut self,
+        name: InterfaceName<'static>,
+        arc_iface: ArcInterface,
+    ) -> bool {
+        match self.fallbacks.entry(name) {
+            btree_map::Entry::Vacant(e) => {
+                e.insert(arc_iface);
+                true
+            }
+            btree_map::Entry::Occupied(_) => false,
+        }
+    }
+
+    pub(super) fn remove_fallback(&mut self, name: &InterfaceName<'static>) -> bool {
+        self.fallbacks.remove(name).is_some()
+    }
+
+    pub(super) fn set_enumerator(
+        &mut self,
+        enumerator: Option<Arc<dyn NodeEnumerator>>,
+    ) -> Option<Arc<dyn NodeEnumerator>> {
+        std::mem::replace(&mut self.enumerator, enumerator)
+    }
+
+    /// Resolve `path`, including the fallbacks and the enumerator of its ancestors.
+    pub(crate) fn resolve<'n>(&'n self, path: &ObjectPath<'_>) -> Resolved<'n> {
+        let mut resolved = Resolved {
+            root: self,
+            path: path.to_owned().into(),
+            node: None,
+            fallbacks: BTreeMap::new(),
+            enumerator: None,
+            covered: false,
+        };
+        let mut node = Some(self);
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            let Some(current) = node else {
+                break;
+            };
+            resolved.inherit(current);
+            node = current.children.get(part);
+        }
+        if let Some(enumerator) = node.and_then(|n| n.enumerator.as_ref()) {
+            resolved.enumerator = Some(enumerator);
+        }
+        resolved.node = node;
+
+        resolved
     }
 
     pub(super) fn remove_node(&mut self, node: &str) -> bool {
@@ -134,7 +246,15 @@ impl Node {
         self.add_arc_interface(I::name(), ArcInterface::new(iface))
     }
 
-    async fn introspect_to_writer<W: Write + Send>(&self, writer: &mut W) {
+    /// Write the introspection XML of this node and its descendants.
+    ///
+    /// The `fallbacks` interfaces and the `enumerated` children are only added to this node.
+    async fn introspect_to_writer<W: Write + Send>(
+        &self,
+        writer: &mut W,
+        fallbacks: &[&ArcInterface],
+        enumerated: &[String],
+    ) {
         enum Fragment<'a> {
             /// Represent an unclosed node tree, could be further splitted into sub-`Fragment`s.
             Node {
@@ -196,6 +316,19 @@ impl Node {
                             .await
                             .introspect_to_writer(writer, level + 2);
                     }
+
+                    if level == 0 {
+                        for iface in fallbacks {
+                            iface
+                                .instance
+                                .read()
+                                .await
+                                .introspect_to_writer(writer, level + 2);
+                        }
+                        for name in enumerated {
+                            writeln!(writer, "  <node name=\"{name}\"/>").unwrap();
+                        }
+                    }
                 }
                 Fragment::End { level } => {
                     writeln!(writer, "{:indent$}</node>", "", indent = level).unwrap();
@@ -204,57 +337,241 @@ impl Node {
         }
     }
 
-    pub(crate) async fn introspect(&self) -> String {
-        let mut xml = String::with_capacity(1024);
+    pub(super) async fn get_properties(
+        &self,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        interface_name: InterfaceName<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>> {
+        let iface = self
+            .interface_lock(interface_name)
+            .expect("Interface was added but not found");
+
+        get_properties(&iface, &self.path, object_server, connection).await
+    }
+
+    /// The interfaces of this node, except for the standard ones.
+    fn managed_interfaces(&self) -> impl Iterator<Item = (&InterfaceName<'static>, &ArcInterface)> {
+        self.interfaces.iter().filter(|(n, _)| {
+            // Filter standard interfaces.
+            *n != &Peer::name()
+                && *n != &Introspectable::name()
+                && *n != &Properties::name()
+                && *n != &ObjectManager::name()
+        })
+    }
+}
+
+/// An object path resolved against the tree of nodes.
+///
+/// The object at the path is either registered, or served through the fallbacks of its ancestors.
+#[derive(Debug)]
+pub(crate) struct Resolved<'n> {
+    root: &'n Node,
+    path: OwnedObjectPath,
+    /// The node at the path, if any.
+    node: Option<&'n Node>,
+    /// The fallback interfaces of the ancestors, the closest ones taking precedence.
+    fallbacks: BTreeMap<&'n str, &'n ArcInterface>,
+    /// The enumerator of the node, or else the closest one of the ancestors.
+    enumerator: Option<&'n Arc<dyn NodeEnumerator>>,
+    /// Whether any ancestor serves its descendants.
+    covered: bool,
+}
+
+impl<'n> Resolved<'n> {
+    fn inherit(&mut self, ancestor: &'n Node) {
+        self.fallbacks
+            .extend(ancestor.fallbacks.iter().map(|(n, i)| (n.as_str(), i)));
+        if let Some(enumerator) = &ancestor.enumerator {
+            self.enumerator = Some(enumerator);
+        }
+        self.covered |= ancestor.is_fallback();
+    }
+
+    /// Get the interface named `name` of the object.
+    pub(crate) fn interface(&self, name: &InterfaceName<'_>) -> fdo::Result<ArcInterface> {
+        let iface = self
+            .node
+            .and_then(|node| node.interfaces.get(name.as_str()))
+            .or_else(|| self.fallbacks.get(name.as_str()).copied());
+        if let Some(iface) = iface {
+            return Ok(iface.clone());
+        }
 
-        self.introspect_to_writer(&mut xml).await;
+        match self.node {
+            None if !self.covered => Err(fdo::Error::UnknownObject(format!(
+                "Unknown object '{}'",
+                self.path
+            ))),
+            // Unregistered objects still have the standard interfaces, which are stateless.
+            None if *name == Introspectable::name() || *name == Properties::name() => Ok(self
+                .root
+                .interfaces
+                .get(name.as_str())
+                .expect("standard interface not found")
+                .clone()),
+            _ => Err(fdo::Error::UnknownInterface(format!(
+                "Unknown interface '{name}'"
+            ))),
+        }
+    }
+
+    /// The fallback interfaces applying to the object, in addition to its own.
+    fn extra_fallbacks(&self) -> Vec<(&'n str, &'n ArcInterface)> {
+        self.fallbacks
+            .iter()
+            .filter(|(name, _)| {
+                !self
+                    .node
+                    .is_some_and(|node| node.interfaces.contains_key(**name))
+            })
+            .map(|(name, iface)| (*name, *iface))
+            .collect()
+    }
 
-        xml
+    /// The enumerator of the object, if any.
+    pub(crate) fn enumerator(&self) -> Option<Arc<dyn NodeEnumerator>> {
+        self.enumerator.cloned()
     }
 
+    /// The path of the object.
+    pub(crate) fn path(&self) -> &OwnedObjectPath {
+        &self.path
+    }
+
+    /// The children listed by the enumerator, except for the registered ones.
+    fn enumerated_children(&self, enumerated: &Enumerated) -> Vec<String> {
+        let Some(names) = enumerated.get(&self.path) else {
+            return vec![];
+        };
+
+        let mut names: Vec<_> = names
+            .iter()
+            .filter(|name| {
+                let valid = !name.is_empty()
+                    && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+                if !valid {
+                    debug!("Ignoring invalid object path element `{name}` from node enumerator");
+                }
+
+                valid
+                    && !self
+                        .node
+                        .is_some_and(|node| node.children.contains_key(*name))
+            })
+            .cloned()
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+
+        names
+    }
+
+    /// Get the child of the object named `name`.
+    fn child(&self, name: &str) -> Resolved<'n> {
+        let path = match self.path.as_str() {
+            "/" => format!("/{name}"),
+            parent => format!("{parent}/{name}"),
+        };
+        let mut child = Resolved {
+            root: self.root,
+            path: ObjectPath::from_string_unchecked(path).into(),
+            node: None,
+            fallbacks: self.fallbacks.clone(),
+            enumerator: self.enumerator,
+            covered: self.covered,
+        };
+        if let Some(node) = self.node {
+            child.inherit(node);
+            child.node = node.children.get(name);
+            if let Some(enumerator) = child.node.and_then(|n| n.enumerator.as_ref()) {
+                child.enumerator = Some(enumerator);
+            }
+        }
+
+        child
+    }
+
+    /// The introspection XML of the object, listing the `enumerated` children as well.
+    pub(crate) async fn introspect(&self, enumerated: &Enumerated) -> fdo::Result<String> {
+        // Make sure the object exists.
+        self.interface(&Introspectable::name())?;
+        let enumerated = self.enumerated_children(enumerated);
+        let fallbacks: Vec<_> = self
+            .extra_fallbacks()
+            .into_iter()
+            .map(|(_, iface)| iface)
+            .collect();
+        let mut xml = String::with_capacity(1024);
+        match self.node {
+            Some(node) => {
+                node.introspect_to_writer(&mut xml, &fallbacks, &enumerated)
+                    .await
+            }
+            None => {
+                Node::new(self.path.clone())
+                    .introspect_to_writer(&mut xml, &fallbacks, &enumerated)
+                    .await
+            }
+        }
+
+        Ok(xml)
+    }
+
+    /// Get the managed objects under this object, including the `enumerated` ones.
     pub(crate) async fn get_managed_objects(
         &self,
         object_server: &ObjectServer,
         connection: &Connection,
+        enumerated: &Enumerated,
     ) -> fdo::Result<ManagedObjects> {
         let mut managed_objects = ManagedObjects::new();
 
-        // Recursively get all properties of all interfaces of descendants.
-        let mut node_list: Vec<_> = self.children.values().collect();
-        while let Some(node) = node_list.pop() {
+        // Recursively get all properties of all interfaces of descendants, registered or not.
+        let mut object_list = self.children(enumerated);
+        while let Some(object) = object_list.pop() {
+            let own = object
+                .node
+                .into_iter()
+                .flat_map(Node::managed_interfaces)
+                .map(|(name, iface)| (name.as_str(), iface));
             let mut interfaces = HashMap::new();
-            for iface_name in node.interfaces.keys().filter(|n| {
-                // Filter standard interfaces.
-                *n != &Peer::name()
-                    && *n != &Introspectable::name()
-                    && *n != &Properties::name()
-                    && *n != &ObjectManager::name()
-            }) {
-                let props = node
-                    .get_properties(object_server, connection, iface_name.clone())
-                    .await?;
-                interfaces.insert(iface_name.clone().into(), props);
+            for (name, iface) in own.chain(object.extra_fallbacks()) {
+                let props = get_properties(iface, &object.path, object_server, connection).await?;
+                interfaces.insert(InterfaceName::from_str_unchecked(name).into(), props);
             }
-            managed_objects.insert(node.path.clone(), interfaces);
-            node_list.extend(node.children.values());
+            managed_objects.insert(object.path.clone(), interfaces);
+            object_list.extend(object.children(enumerated));
         }
 
         Ok(managed_objects)
     }
 
-    pub(super) async fn get_properties(
-        &self,
-        object_server: &ObjectServer,
-        connection: &Connection,
-        interface_name: InterfaceName<'_>,
-    ) -> fdo::Result<HashMap<String, OwnedValue>> {
-        let emitter = SignalEmitter::new(connection, self.path.clone())?;
-        self.interface_lock(interface_name)
-            .expect("Interface was added but not found")
-            .instance
-            .read()
-            .await
-            .get_all(object_server, connection, None, &emitter)
-            .await
+    /// The children of the object, registered and `enumerated`.
+    pub(crate) fn children(&self, enumerated: &Enumerated) -> Vec<Resolved<'n>> {
+        let registered = self.node.into_iter().flat_map(|node| node.children.keys());
+        let enumerated = self.enumerated_children(enumerated);
+
+        registered
+            .map(String::as_str)
+            .chain(enumerated.iter().map(String::as_str))
+            .map(|name| self.child(name))
+            .collect()
     }
 }
+
+async fn get_properties(
+    iface: &ArcInterface,
+    path: &OwnedObjectPath,
+    object_server: &ObjectServer,
+    connection: &Connection,
+) -> fdo::Result<HashMap<String, OwnedValue>> {
+    let emitter = SignalEmitter::new(connection, path.clone())?;
+    iface
+        .instance
+        .read()
+        .await
+        .get_all(object_server, connection, None, &emitter)
+        .await
+}
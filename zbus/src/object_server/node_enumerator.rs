@@ -0,0 +1,285 @@
+//! Enumeration of the objects materialized on demand.
+
+use async_trait::async_trait;
+use zvariant::ObjectPath;
+
+use crate::fdo;
+
+/// A callback listing the objects under a path prefix, on demand.
+///
+/// Objects served through fallback interfaces (see [`ObjectServer::at_fallback`]) aren't
+/// registered individually, so the object server can't know which of them exist. Set an
+/// enumerator on a path prefix through [`ObjectServer::set_node_enumerator`] for
+/// `org.freedesktop.DBus.Introspectable` and `org.freedesktop.DBus.ObjectManager` to list them.
+///
+/// The enumerator is called without the object server being locked, so it's free to access it.
+///
+/// # Example
+///
+/// Serving the rows of a table as objects, without registering an object per row:
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::{
+///     Connection, fdo, interface,
+///     message::Header,
+///     object_server::NodeEnumerator,
+///     zvariant::ObjectPath,
+/// };
+///
+/// struct Row;
+///
+/// #[interface(name = "org.zbus.Row")]
+/// impl Row {
+///     fn describe(&self, #[zbus(header)] header: Header<'_>) -> String {
+///         // The path of the called object tells which row to describe.
+///         format!("Row at {}", header.path().unwrap())
+///     }
+/// }
+///
+/// #[derive(Debug)]
+/// struct Rows;
+///
+/// #[zbus::export::async_trait::async_trait]
+/// impl NodeEnumerator for Rows {
+///     async fn children(&self, path: &ObjectPath<'_>) -> fdo::Result<Vec<String>> {
+///         if path.as_str() != "/org/zbus/Table" {
+///             // Rows have no children.
+///             return Ok(vec![]);
+///         }
+///
+///         Ok((0..1000).map(|i| i.to_string()).collect())
+///     }
+/// }
+///
+/// let connection = Connection::session().await?;
+/// let server = connection.object_server();
+/// server.at_fallback("/org/zbus/Table", Row).await?;
+/// server.set_node_enumerator("/org/zbus/Table", Rows).await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`ObjectServer::at_fallback`]: crate::ObjectServer::at_fallback
+/// [`ObjectServer::set_node_enumerator`]: crate::ObjectServer::set_node_enumerator
+#[async_trait]
+pub trait NodeEnumerator: std::fmt::Debug + Send + Sync + 'static {
+    /// The names of the direct children of the object at `path`.
+    ///
+    /// `path` is either the path the enumerator was set at, or one of its descendants. Names that
+    /// aren't valid object path elements are ignored.
+    async fn children(&self, path: &ObjectPath<'_>) -> fdo::Result<Vec<String>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use test_log::test;
+    use zvariant::ObjectPath;
+
+    use super::NodeEnumerator;
+    use crate::{
+        Result, connection,
+        fdo::{self, IntrospectableProxy, ObjectManager, ObjectManagerProxy},
+        interface,
+        message::Header,
+        object_server::SignalEmitter,
+        proxy,
+        utils::block_on,
+    };
+
+    struct Row;
+
+    #[interface(name = "org.zbus.FallbackTest.Row")]
+    impl Row {
+        fn index(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<u32> {
+            row_index(header.path().unwrap())
+        }
+
+        #[zbus(property)]
+        fn name(&self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) -> fdo::Result<String> {
+            row_index(emitter.path()).map(|i| format!("row{i}"))
+        }
+    }
+
+    fn row_index(path: &ObjectPath<'_>) -> fdo::Result<u32> {
+        path.rsplit('/')
+            .next()
+            .and_then(|i| i.parse().ok())
+            .filter(|i| *i < 3)
+            .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))
+    }
+
+    #[derive(Debug)]
+    struct Rows;
+
+    #[async_trait::async_trait]
+    impl NodeEnumerator for Rows {
+        async fn children(&self, path: &ObjectPath<'_>) -> fdo::Result<Vec<String>> {
+            if path.as_str() != "/org/zbus/FallbackTest/rows" {
+                return Ok(vec![]);
+            }
+
+            Ok(vec!["0".into(), "1".into(), "2".into(), "not valid".into()])
+        }
+    }
+
+    #[proxy(interface = "org.zbus.FallbackTest.Row")]
+    trait Row {
+        fn index(&self) -> Result<u32>;
+
+        #[zbus(property)]
+        fn name(&self) -> Result<String>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn fallback() {
+        block_on(test_fallback()).unwrap();
+    }
+
+    async fn test_fallback() -> Result<()> {
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/FallbackTest", ObjectManager)?
+            .build()
+            .await?;
+        let server = service.object_server();
+        assert!(
+            server
+                .at_fallback("/org/zbus/FallbackTest/rows", Row)
+                .await?
+        );
+        assert!(
+            !server
+                .at_fallback("/org/zbus/FallbackTest/rows", Row)
+                .await?
+        );
+        let client = connection::Builder::session()?.build().await?;
+        let destination = service.unique_name().unwrap();
+
+        // Calls to any descendant are dispatched to the fallback.
+        let row = RowProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest/rows/2")?
+            .cache_properties(proxy::CacheProperties::No)
+            .build()
+            .await?;
+        assert_eq!(row.index().await?, 2);
+        assert_eq!(row.name().await?, "row2");
+        let row = RowProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest/rows/7")?
+            .cache_properties(proxy::CacheProperties::No)
+            .build()
+            .await?;
+        assert_error(
+            row.index().await,
+            "org.freedesktop.DBus.Error.UnknownObject",
+        );
+
+        // But not to the prefix itself, nor outside of it.
+        let row = RowProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest/rows")?
+            .cache_properties(proxy::CacheProperties::No)
+            .build()
+            .await?;
+        assert_error(
+            row.index().await,
+            "org.freedesktop.DBus.Error.UnknownInterface",
+        );
+        let row = RowProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest/columns/0")?
+            .cache_properties(proxy::CacheProperties::No)
+            .build()
+            .await?;
+        assert_error(
+            row.index().await,
+            "org.freedesktop.DBus.Error.UnknownObject",
+        );
+
+        // Without an enumerator, the rows aren't listed.
+        let introspectable = IntrospectableProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest/rows")?
+            .build()
+            .await?;
+        assert!(!introspectable.introspect().await?.contains(r#"name="0""#));
+        let manager = ObjectManagerProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest")?
+            .build()
+            .await?;
+        assert!(
+            !manager
+                .get_managed_objects()
+                .await?
+                .keys()
+                .any(|p| p.starts_with("/org/zbus/FallbackTest/rows/"))
+        );
+
+        // With one, they're listed on demand.
+        server
+            .set_node_enumerator("/org/zbus/FallbackTest/rows", Rows)
+            .await?;
+        let xml = introspectable.introspect().await?;
+        for i in 0..3 {
+            assert!(xml.contains(&format!(r#"<node name="{i}"/>"#)));
+        }
+        assert!(!xml.contains("not valid"));
+        let introspectable = IntrospectableProxy::builder(&client)
+            .destination(destination)?
+            .path("/org/zbus/FallbackTest/rows/1")?
+            .build()
+            .await?;
+        assert!(
+            introspectable
+                .introspect()
+                .await?
+                .contains(r#"<interface name="org.zbus.FallbackTest.Row">"#)
+        );
+
+        let objects = manager.get_managed_objects().await?;
+        for i in 0..3 {
+            let path = ObjectPath::try_from(format!("/org/zbus/FallbackTest/rows/{i}"))?;
+            let interfaces = &objects[&path];
+            assert_eq!(
+                interfaces["org.zbus.FallbackTest.Row"]["Name"],
+                zvariant::Str::from(format!("row{i}")).into()
+            );
+        }
+
+        // The node is only destroyed once both are removed.
+        assert!(
+            !server
+                .remove_node_enumerator("/org/zbus/FallbackTest/rows")
+                .await?
+        );
+        assert!(
+            server
+                .remove_fallback::<Row, _>("/org/zbus/FallbackTest/rows")
+                .await?
+        );
+        assert_error(
+            RowProxy::builder(&client)
+                .destination(destination)?
+                .path("/org/zbus/FallbackTest/rows/0")?
+                .cache_properties(proxy::CacheProperties::No)
+                .build()
+                .await?
+                .index()
+                .await,
+            "org.freedesktop.DBus.Error.UnknownObject",
+        );
+
+        Ok(())
+    }
+
+    fn assert_error(res: Result<u32>, error: &str) {
+        match res {
+            Err(crate::Error::MethodError(name, _, _)) => assert_eq!(name, error),
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+use zbus_names::{InterfaceName, OwnedUniqueName, UniqueName};
+use zvariant::ObjectPath;
+
+use crate::{
+    Error, ObjectServer, Result,
+    object_server::{
+        ArcInterface, DynamicInterface, Interface, dynamic_interface::Dispatcher, owners::Cleanup,
+    },
+};
+
+/// Builder for registering an object with all its interfaces at once.
+///
+/// Registering the interfaces of an object one by one, through [`ObjectServer::at`], makes each
+/// of them visible to the peers as soon as it's registered. If the object is managed by an
+/// [`crate::fdo::ObjectManager`], an `InterfacesAdded` signal is also emitted for each of them.
+/// Peers can then observe the object with only some of its interfaces. `ObjectBuilder` registers
+/// all the interfaces in one step instead, with a single `InterfacesAdded` signal carrying all of
+/// them.
+///
+/// Use [`ObjectServer::object`] to create an instance of this type.
+///
+/// # Example
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::{Connection, interface};
+///
+/// struct Device;
+///
+/// #[interface(name = "org.zbus.Device")]
+/// impl Device {}
+///
+/// struct Battery;
+///
+/// #[interface(name = "org.zbus.Battery")]
+/// impl Battery {}
+///
+/// let connection = Connection::session().await?;
+/// connection
+///     .object_server()
+///     .object("/org/zbus/devices/0")?
+///     .with(Device)
+///     .with(Battery)
+///     .register()
+///     .await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[must_use = "the object is only registered by `ObjectBuilder::register`"]
+pub struct ObjectBuilder<'s> {
+    server: &'s ObjectServer,
+    path: ObjectPath<'static>,
+    interfaces: Vec<(InterfaceName<'static>, ArcInterface)>,
+    owner: Option<OwnedUniqueName>,
+    cleanup: Option<Cleanup>,
+}
+
+impl<'s> ObjectBuilder<'s> {
+    pub(super) fn new(server: &'s ObjectServer, path: ObjectPath<'static>) -> Self {
+        Self {
+            server,
+            path,
+            interfaces: vec![],
+            owner: None,
+            cleanup: None,
+        }
+    }
+
+    /// Add an [`Interface`] to the object.
+    pub fn with<I>(mut self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        self.interfaces.push((I::name(), ArcInterface::new(iface)));
+
+        self
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    pub fn with_dynamic(mut self, iface: DynamicInterface) -> Self {
+        let name = iface.name().clone();
+        self.interfaces
+            .push((name, ArcInterface::new(Dispatcher(iface))));
+
+        self
+    }
+
+    /// Tie the lifetime of the object to the peer with the given unique name.
+    ///
+    /// This is typically the sender of the method call creating the object. Once the owner
+    /// disconnects from the bus, the object is removed as through [`ObjectServer::remove_object`],
+    /// unless it was already removed or replaced in the meantime.
+    pub fn owned_by<'n, N>(mut self, owner: N) -> Result<Self>
+    where
+        N: TryInto<UniqueName<'n>>,
+        N::Error: Into<Error>,
+    {
+        let owner = owner.try_into().map_err(Into::into)?;
+        self.owner = Some(owner.into_owned().into());
+
+        Ok(self)
+    }
+
+    /// Set a callback to call after the object is removed, as its owner disconnected.
+    ///
+    /// The callback is passed the path of the object. This is only relevant to objects with an
+    /// owner, set through [`ObjectBuilder::owned_by`].
+    pub fn on_owner_vanished<F>(mut self, cleanup: F) -> Self
+    where
+        F: FnOnce(ObjectPath<'static>) + Send + 'static,
+    {
+        self.cleanup = Some(Box::new(cleanup));
+
+        self
+    }
+
+    /// Register the object with all its interfaces.
+    ///
+    /// If any of the interfaces already exists at the path of the object, or was added more than
+    /// once, none of them are registered and `false` is returned. The same goes for an object
+    /// with an owner, if there are other interfaces at its path, or for adding interfaces to an
+    /// owned object.
+    ///
+    /// # Errors
+    ///
+    /// Objects can only have an owner on a bus connection. Otherwise, [`Error::Unsupported`] is
+    /// returned.
+    ///
+    /// If the owner set through [`ObjectBuilder::owned_by`] is already disconnected, the object is
+    /// removed right away and an [`fdo::Error::NameHasNoOwner`] error is returned.
+    ///
+    /// [`fdo::Error::NameHasNoOwner`]: crate::fdo::Error::NameHasNoOwner
+    pub async fn register(self) -> Result<bool> {
+        match self.owner {
+            Some(owner) => {
+                self.server
+                    .add_owned_arc_interfaces(self.path, self.interfaces, owner, self.cleanup)
+                    .await
+            }
+            None => {
+                self.server
+                    .add_arc_interfaces(self.path, self.interfaces, None)
+                    .await
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ObjectBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectBuilder")
+            .field("path", &self.path)
+            .field("interfaces", &self.interfaces)
+            .field("owner", &self.owner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use std::sync::mpsc;
+    use test_log::test;
+
+    use crate::{
+        Error, Result, connection,
+        fdo::{self, ObjectManager, ObjectManagerProxy},
+        interface,
+        utils::block_on,
+    };
+
+    struct Device;
+
+    #[interface(name = "org.zbus.ObjectBuilderTest.Device")]
+    impl Device {
+        #[zbus(property)]
+        fn model(&self) -> &str {
+            "zbus"
+        }
+    }
+
+    struct Battery;
+
+    #[interface(name = "org.zbus.ObjectBuilderTest.Battery")]
+    impl Battery {
+        #[zbus(property)]
+        fn level(&self) -> u8 {
+            42
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn object_builder() {
+        block_on(test_object_builder()).unwrap();
+    }
+
+    async fn test_object_builder() -> Result<()> {
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/ObjectBuilderTest", ObjectManager)?
+            .build()
+            .await?;
+        let client = connection::Builder::session()?.build().await?;
+        let manager = ObjectManagerProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .path("/org/zbus/ObjectBuilderTest")?
+            .build()
+            .await?;
+        let mut added = manager.receive_interfaces_added().await?;
+        let mut removed = manager.receive_interfaces_removed().await?;
+
+        let server = service.object_server();
+        assert!(
+            server
+                .object("/org/zbus/ObjectBuilderTest/dev0")?
+                .with(Device)
+                .with(Battery)
+                .register()
+                .await?
+        );
+        let signal = added.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.object_path(), "/org/zbus/ObjectBuilderTest/dev0");
+        let interfaces = args.interfaces_and_properties();
+        assert_eq!(interfaces.len(), 2);
+        assert_eq!(
+            interfaces["org.zbus.ObjectBuilderTest.Device"]["Model"],
+            "zbus".into()
+        );
+        assert_eq!(
+            interfaces["org.zbus.ObjectBuilderTest.Battery"]["Level"],
+            42u8.into()
+        );
+
+        // Nothing is registered if any of the interfaces already exists.
+        assert!(
+            server
+                .at("/org/zbus/ObjectBuilderTest/dev1", Device)
+                .await?
+        );
+        added.next().await.unwrap();
+        assert!(
+            !server
+                .object("/org/zbus/ObjectBuilderTest/dev1")?
+                .with(Battery)
+                .with(Device)
+                .register()
+                .await?
+        );
+        assert!(matches!(
+            server
+                .interface::<_, Battery>("/org/zbus/ObjectBuilderTest/dev1")
+                .await,
+            Err(Error::InterfaceNotFound)
+        ));
+
+        // Removal is atomic as well.
+        assert!(
+            server
+                .remove_object("/org/zbus/ObjectBuilderTest/dev0")
+                .await?
+        );
+        let signal = removed.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.object_path(), "/org/zbus/ObjectBuilderTest/dev0");
+        assert_eq!(args.interfaces().len(), 2);
+        assert!(matches!(
+            server
+                .remove_object("/org/zbus/ObjectBuilderTest/dev0")
+                .await,
+            Err(Error::InterfaceNotFound)
+        ));
+
+        // The other object is still there.
+        server
+            .interface::<_, Device>("/org/zbus/ObjectBuilderTest/dev1")
+            .await?;
+
+        // The root object is never destroyed.
+        assert!(server.at("/", Device).await?);
+        assert!(!server.remove_object("/").await?);
+        assert!(matches!(
+            server.interface::<_, Device>("/").await,
+            Err(Error::InterfaceNotFound)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn owned_object() {
+        block_on(test_owned_object()).unwrap();
+    }
+
+    async fn test_owned_object() -> Result<()> {
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/OwnedObjectTest", ObjectManager)?
+            .build()
+            .await?;
+        let client = connection::Builder::session()?.build().await?;
+        let manager = ObjectManagerProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .path("/org/zbus/OwnedObjectTest")?
+            .build()
+            .await?;
+        let mut removed = manager.receive_interfaces_removed().await?;
+
+        let server = service.object_server();
+        let owner = connection::Builder::session()?.build().await?;
+        let (tx, rx) = mpsc::channel();
+        assert!(
+            server
+                .object("/org/zbus/OwnedObjectTest/session0")?
+                .with(Device)
+                .with(Battery)
+                .owned_by(owner.unique_name().unwrap())?
+                .on_owner_vanished(move |path| tx.send(path).unwrap())
+                .register()
+                .await?
+        );
+        assert!(
+            server
+                .object("/org/zbus/OwnedObjectTest/session1")?
+                .with(Device)
+                .owned_by(owner.unique_name().unwrap())?
+                .register()
+                .await?
+        );
+
+        // Owned objects don't share their path with other interfaces.
+        assert!(
+            !server
+                .at("/org/zbus/OwnedObjectTest/session1", Battery)
+                .await?
+        );
+        assert!(
+            server
+                .at("/org/zbus/OwnedObjectTest/shared", Battery)
+                .await?
+        );
+        assert!(
+            !server
+                .object("/org/zbus/OwnedObjectTest/shared")?
+                .with(Device)
+                .owned_by(owner.unique_name().unwrap())?
+                .register()
+                .await?
+        );
+
+        // All the objects of the owner are removed once it disconnects.
+        let owner_name = owner.unique_name().unwrap().to_owned();
+        owner.close().await?;
+        let mut paths = vec![];
+        for _ in 0..2 {
+            let signal = removed.next().await.unwrap();
+            paths.push(signal.args()?.object_path().to_string());
+        }
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "/org/zbus/OwnedObjectTest/session0",
+                "/org/zbus/OwnedObjectTest/session1"
+            ]
+        );
+        assert_eq!(rx.recv().unwrap(), "/org/zbus/OwnedObjectTest/session0");
+        assert!(matches!(
+            server
+                .interface::<_, Device>("/org/zbus/OwnedObjectTest/session0")
+                .await,
+            Err(Error::InterfaceNotFound)
+        ));
+        assert!(
+            server
+                .interface::<_, Battery>("/org/zbus/OwnedObjectTest/shared")
+                .await
+                .is_ok()
+        );
+
+        // Objects can't be owned by a peer that's already gone.
+        let res = server
+            .object("/org/zbus/OwnedObjectTest/session2")?
+            .with(Device)
+            .owned_by(&owner_name)?
+            .register()
+            .await;
+        assert!(matches!(res, Err(Error::FDO(e)) if matches!(*e, fdo::Error::NameHasNoOwner(_))));
+        assert!(matches!(
+            server
+                .interface::<_, Device>("/org/zbus/OwnedObjectTest/session2")
+                .await,
+            Err(Error::InterfaceNotFound)
+        ));
+
+        Ok(())
+    }
+}
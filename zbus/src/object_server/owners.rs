@@ -0,0 +1,142 @@
+//! Tracking of the objects owned by peers.
+
+use futures_lite::StreamExt;
+use std::{collections::HashMap, fmt, sync::Mutex};
+use tracing::{debug, trace};
+use zbus_names::OwnedUniqueName;
+use zvariant::{ObjectPath, OwnedObjectPath};
+
+use crate::{ObjectServer, Result, Task, fdo::DBusProxy, object_server::BusyGuard};
+
+/// A callback called after an object is removed, as its owner vanished from the bus.
+pub(crate) type Cleanup = Box<dyn FnOnce(ObjectPath<'static>) + Send>;
+
+/// The objects owned by peers, per unique name of the owners.
+#[derive(Default)]
+pub(crate) struct Owners(Mutex<HashMap<OwnedUniqueName, Watch>>);
+
+/// The objects owned by a peer, and the task watching for it to vanish.
+struct Watch {
+    objects: HashMap<OwnedObjectPath, Option<Cleanup>>,
+    task: Task<()>,
+    // Objects held by peers keep the object server busy.
+    _busy: BusyGuard,
+}
+
+impl Owners {
+    /// Record that the object at `path` is owned by `owner`.
+    ///
+    /// The owner is watched from this point on, and all its objects are removed through `server`
+    /// once it vanishes.
+    pub(crate) async fn add(
+        &self,
+        server: &ObjectServer,
+        owner: OwnedUniqueName,
+        path: OwnedObjectPath,
+        cleanup: Option<Cleanup>,
+    ) -> Result<()> {
+        if let Some(watch) = self.0.lock().expect("lock poisoned").get_mut(&owner) {
+            watch.objects.insert(path, cleanup);
+
+            return Ok(());
+        }
+
+        let conn = server.connection();
+        let mut stream = DBusProxy::new(&conn)
+            .await?
+            .receive_name_owner_changed_with_args(&[(0, owner.as_str())])
+            .await?;
+        let mut owners = self.0.lock().expect("lock poisoned");
+        // The owner might have been added in the meantime.
+        if let Some(watch) = owners.get_mut(&owner) {
+            watch.objects.insert(path, cleanup);
+
+            return Ok(());
+        }
+
+        let task_name = format!("monitor_object_owner{{name={owner}}}");
+        let busy = server.busy();
+        let server = server.clone();
+        let name = owner.clone();
+        let task = conn.executor().spawn(
+            async move {
+                while let Some(signal) = stream.next().await {
+                    match signal.args() {
+                        Ok(args) if args.new_owner().is_none() => break,
+                        Ok(_) => (),
+                        Err(e) => debug!("Failed to parse `NameOwnerChanged` signal: {e}"),
+                    }
+                }
+                trace!("Owner `{name}` vanished, removing its objects");
+
+                if let Some(watch) = server.owners.take(&name) {
+                    remove_objects(&server, &name, watch.objects).await;
+                    // This is the current task.
+                    watch.task.detach();
+                }
+            },
+            &task_name,
+        );
+        owners.insert(
+            owner,
+            Watch {
+                objects: HashMap::from([(path, cleanup)]),
+                task,
+                _busy: busy,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Remove all the objects owned by `owner`, which vanished.
+    pub(crate) async fn vanished(&self, server: &ObjectServer, owner: &OwnedUniqueName) {
+        if let Some(watch) = self.take(owner) {
+            drop(watch.task);
+            remove_objects(server, owner, watch.objects).await;
+        }
+    }
+
+    /// Forget about the object at `path` owned by `owner`, as it was removed.
+    ///
+    /// The owner isn't watched anymore once it has no objects left.
+    pub(crate) fn forget(&self, owner: &OwnedUniqueName, path: &OwnedObjectPath) {
+        let mut owners = self.0.lock().expect("lock poisoned");
+        if let Some(watch) = owners.get_mut(owner) {
+            watch.objects.remove(path);
+            if watch.objects.is_empty() {
+                owners.remove(owner);
+            }
+        }
+    }
+
+    fn take(&self, owner: &OwnedUniqueName) -> Option<Watch> {
+        self.0.lock().expect("lock poisoned").remove(owner)
+    }
+}
+
+impl fmt::Debug for Owners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let owners = self.0.lock().expect("lock poisoned");
+
+        f.debug_set().entries(owners.keys()).finish()
+    }
+}
+
+async fn remove_objects(
+    server: &ObjectServer,
+    owner: &OwnedUniqueName,
+    objects: HashMap<OwnedObjectPath, Option<Cleanup>>,
+) {
+    for (path, cleanup) in objects {
+        // The object might have been removed, or even replaced, in the meantime.
+        match server.remove_object_of(&path, Some(owner)).await {
+            Ok(_) => {
+                if let Some(cleanup) = cleanup {
+                    cleanup(path.into_inner());
+                }
+            }
+            Err(e) => trace!("Not removing object `{path}` of `{owner}`: {e}"),
+        }
+    }
+}
@@ -1,6 +1,11 @@
-use zbus_names::{BusName, InterfaceName, MemberName};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+use zbus_names::{BusName, InterfaceName, MemberName, OwnedInterfaceName};
+use zvariant::{OwnedValue, Value};
 
-use crate::{Connection, Error, Result, zvariant::ObjectPath};
+use crate::{Connection, Error, Result, fdo::Properties, zvariant::ObjectPath};
 
 /// A signal emitter.
 ///
@@ -13,6 +18,7 @@ pub struct SignalEmitter<'s> {
     conn: Connection,
     path: ObjectPath<'s>,
     destination: Option<BusName<'s>>,
+    batch: Option<Arc<PropertiesBatch>>,
 }
 
 impl<'s> SignalEmitter<'s> {
@@ -27,6 +33,7 @@ impl<'s> SignalEmitter<'s> {
                 conn: conn.clone(),
                 path: p,
                 destination: None,
+                batch: None,
             })
             .map_err(Into::into)
     }
@@ -37,6 +44,7 @@ impl<'s> SignalEmitter<'s> {
             conn,
             path,
             destination: None,
+            batch: None,
         }
     }
 
@@ -60,6 +68,37 @@ impl<'s> SignalEmitter<'s> {
             .await
     }
 
+    /// Emit the `org.freedesktop.DBus.Properties.PropertiesChanged` signal.
+    ///
+    /// Unlike [`Properties::properties_changed`], this takes batching into account: if `self` was
+    /// passed by [`InterfaceRef::batch`], the changes are only recorded, to be emitted along with
+    /// the others of the batch. Once the batch is emitted, the changes are emitted right away
+    /// again. The `<property>_changed` and `<property>_invalidate` methods generated by the
+    /// [`interface`] macro use this method.
+    ///
+    /// [`InterfaceRef::batch`]: crate::object_server::InterfaceRef::batch
+    /// [`interface`]: crate::interface
+    pub async fn emit_properties_changed(
+        &self,
+        interface_name: InterfaceName<'_>,
+        changed_properties: HashMap<&str, Value<'_>>,
+        invalidated_properties: &[&str],
+    ) -> Result<()> {
+        if let Some(batch) = &self.batch {
+            if batch.record(&interface_name, &changed_properties, invalidated_properties)? {
+                return Ok(());
+            }
+        }
+
+        Properties::properties_changed(
+            self,
+            interface_name,
+            changed_properties,
+            invalidated_properties.into(),
+        )
+        .await
+    }
+
     /// Set the destination for the signal emission.
     ///
     /// Signals are typically broadcasted and thus don't have a destination. However, there are
@@ -92,6 +131,7 @@ impl<'s> SignalEmitter<'s> {
             conn: self.conn.clone(),
             path: self.path.to_owned(),
             destination: self.destination.as_ref().map(|d| d.to_owned()),
+            batch: self.batch.clone(),
         }
     }
 
@@ -101,6 +141,86 @@ impl<'s> SignalEmitter<'s> {
             conn: self.conn,
             path: self.path.into_owned(),
             destination: self.destination.map(|d| d.into_owned()),
+            batch: self.batch,
+        }
+    }
+
+    /// Record the property changes emitted through `self` in `batch`, instead of emitting them.
+    pub(crate) fn with_batch(mut self, batch: Arc<PropertiesBatch>) -> Self {
+        self.batch = Some(batch);
+
+        self
+    }
+}
+
+/// Property changes recorded through a batching [`SignalEmitter`], per interface.
+#[derive(Debug, Default)]
+pub(crate) struct PropertiesBatch(Mutex<BatchState>);
+
+#[derive(Debug, Default)]
+struct BatchState {
+    changes: BTreeMap<OwnedInterfaceName, PropertiesChanges>,
+    /// Whether the batch was emitted already, in which case nothing is recorded anymore.
+    emitted: bool,
+}
+
+#[derive(Debug, Default)]
+struct PropertiesChanges {
+    changed: HashMap<String, OwnedValue>,
+    invalidated: BTreeSet<String>,
+}
+
+impl PropertiesBatch {
+    /// Merge the changes with the previously recorded ones, the latest ones taking precedence.
+    ///
+    /// Returns `false` if the batch was emitted already, in which case nothing is recorded.
+    pub(crate) fn record(
+        &self,
+        interface: &InterfaceName<'_>,
+        changed: &HashMap<&str, Value<'_>>,
+        invalidated: &[&str],
+    ) -> Result<bool> {
+        let mut batch = self.0.lock().expect("lock poisoned");
+        if batch.emitted {
+            return Ok(false);
+        }
+        let changes = batch
+            .changes
+            .entry(interface.to_owned().into())
+            .or_default();
+        for (name, value) in changed {
+            changes.invalidated.remove(*name);
+            changes
+                .changed
+                .insert((*name).to_owned(), value.try_to_owned()?);
         }
+        for name in invalidated {
+            changes.changed.remove(*name);
+            changes.invalidated.insert((*name).to_owned());
+        }
+
+        Ok(true)
+    }
+
+    /// Emit the recorded changes through `emitter`, in a single signal per interface.
+    pub(crate) async fn emit(&self, emitter: &SignalEmitter<'_>) -> Result<()> {
+        let batch = {
+            let mut batch = self.0.lock().expect("lock poisoned");
+            batch.emitted = true;
+
+            std::mem::take(&mut batch.changes)
+        };
+        for (interface, changes) in batch {
+            let changed = changes
+                .changed
+                .iter()
+                .map(|(name, value)| Ok((name.as_str(), Value::try_from(value)?)))
+                .collect::<Result<_>>()?;
+            let invalidated: Vec<_> = changes.invalidated.iter().map(String::as_str).collect();
+            Properties::properties_changed(emitter, interface.into(), changed, invalidated.into())
+                .await?;
+        }
+
+        Ok(())
     }
 }
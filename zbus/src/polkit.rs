@@ -0,0 +1,566 @@
+//! Authorization through [polkit].
+//!
+//! This module provides a proxy for the `org.freedesktop.PolicyKit1.Authority` interface and
+//! [`Polkit`], a helper to authorize the method calls received by a service.
+//!
+//! [polkit]: https://www.freedesktop.org/software/polkit/docs/latest/
+
+use enumflags2::{BitFlags, bitflags};
+use futures_lite::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::debug;
+use zbus_names::{OwnedUniqueName, UniqueName};
+use zvariant::{OwnedValue, Str, Type};
+
+use crate::{
+    Connection, Result, Task, fdo,
+    message::{Flags, Header},
+    object_server::{AuthorizationRequest, Authorizer},
+    proxy,
+};
+
+/// How long polkit keeps temporary authorizations around, by default.
+const TEMPORARY_AUTHORIZATION_LIFETIME: Duration = Duration::from_secs(5 * 60);
+
+/// The detail of an [`AuthorizationResult`] holding the ID of the temporary authorization.
+const TEMPORARY_AUTHORIZATION_ID: &str = "polkit.temporary_authorization_id";
+
+/// The flags used by the [`AuthorityProxy::check_authorization`] method.
+#[bitflags]
+#[repr(u32)]
+#[derive(Type, Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum CheckAuthorizationFlags {
+    /// If the subject can obtain the authorization through authentication, and an authentication
+    /// agent is available, then attempt to do so. Note that this means the
+    /// [`AuthorityProxy::check_authorization`] call may block while the user is being asked to
+    /// authenticate.
+    AllowUserInteraction = 0x01,
+}
+
+/// The entity whose authorization is checked.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct Subject {
+    kind: String,
+    details: HashMap<String, OwnedValue>,
+}
+
+impl Subject {
+    /// The peer with the given unique name, on the bus the authority is on.
+    pub fn system_bus_name(name: &UniqueName<'_>) -> Self {
+        let details = HashMap::from([("name".to_string(), Str::from(name.as_str()).into())]);
+
+        Self {
+            kind: "system-bus-name".to_string(),
+            details,
+        }
+    }
+
+    /// The kind of the subject (e.g `system-bus-name` or `unix-process`).
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The details identifying the subject, whose keys depend on its [`Subject::kind`].
+    pub fn details(&self) -> &HashMap<String, OwnedValue> {
+        &self.details
+    }
+}
+
+/// The result of the [`AuthorityProxy::check_authorization`] method.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct AuthorizationResult {
+    is_authorized: bool,
+    is_challenge: bool,
+    details: HashMap<String, String>,
+}
+
+impl AuthorizationResult {
+    /// Create a new result.
+    pub fn new(is_authorized: bool, is_challenge: bool, details: HashMap<String, String>) -> Self {
+        Self {
+            is_authorized,
+            is_challenge,
+            details,
+        }
+    }
+
+    /// Whether the subject is authorized.
+    pub fn is_authorized(&self) -> bool {
+        self.is_authorized
+    }
+
+    /// Whether the subject could be authorized, if it authenticated.
+    pub fn is_challenge(&self) -> bool {
+        self.is_challenge
+    }
+
+    /// The details about the result (e.g `polkit.temporary_authorization_id`).
+    pub fn details(&self) -> &HashMap<String, String> {
+        &self.details
+    }
+
+    /// Whether the authorization is a temporary one, obtained by authenticating.
+    pub fn is_temporary(&self) -> bool {
+        self.details.contains_key(TEMPORARY_AUTHORIZATION_ID)
+    }
+}
+
+/// Proxy for the `org.freedesktop.PolicyKit1.Authority` interface.
+#[proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+pub trait Authority {
+    /// Check if `subject` is authorized to perform the action with the given ID.
+    ///
+    /// `cancellation_id` can be used to cancel the check through
+    /// [`AuthorityProxy::cancel_check_authorization`], if it isn't empty.
+    fn check_authorization(
+        &self,
+        subject: &Subject,
+        action_id: &str,
+        details: &HashMap<&str, &str>,
+        flags: BitFlags<CheckAuthorizationFlags>,
+        cancellation_id: &str,
+    ) -> Result<AuthorizationResult>;
+
+    /// Cancel an authorization check, started with the given `cancellation_id`.
+    fn cancel_check_authorization(&self, cancellation_id: &str) -> Result<()>;
+
+    /// Emitted when actions or authorizations change.
+    #[zbus(signal)]
+    fn changed(&self) -> Result<()>;
+
+    /// The name of the backend of the authority.
+    #[zbus(property)]
+    fn backend_name(&self) -> Result<String>;
+
+    /// The version of the backend of the authority.
+    #[zbus(property)]
+    fn backend_version(&self) -> Result<String>;
+
+    /// The features supported by the backend of the authority.
+    #[zbus(property)]
+    fn backend_features(&self) -> Result<u32>;
+}
+
+/// Authorize method calls through polkit.
+///
+/// [`Polkit::check`] checks if the sender of a method call is authorized to perform an action. The
+/// interactive authentication of the caller is allowed, if the caller allowed it for the call (see
+/// [`crate::proxy::MethodFlags::AllowInteractiveAuth`]).
+///
+/// Temporary authorizations, obtained by authenticating, are cached for as long as polkit keeps
+/// them (see [`Polkit::set_temporary_authorization_lifetime`]), unless the authority tells us that
+/// the authorizations changed in the meantime. They are only reused for checks of the same action
+/// with the same details.
+///
+/// `Polkit` also implements [`Authorizer`], checking the actions the methods are tied to.
+///
+/// # Example
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use std::collections::HashMap;
+/// use zbus::{connection, fdo, interface, message::Header, polkit::Polkit};
+///
+/// struct Service {
+///     polkit: Polkit,
+/// }
+///
+/// #[interface(name = "org.zbus.Service")]
+/// impl Service {
+///     async fn reboot(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+///         self.polkit
+///             .check("org.zbus.Service.reboot", &header, &HashMap::new())
+///             .await?;
+///
+///         // ...
+///         Ok(())
+///     }
+/// }
+///
+/// let conn = connection::Builder::system()?
+///     .name("org.zbus.Service")?
+///     .build()
+///     .await?;
+/// let polkit = Polkit::new(&conn).await?;
+/// conn.object_server()
+///     .at("/org/zbus/Service", Service { polkit })
+///     .await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Polkit(Arc<PolkitInner>);
+
+#[derive(Debug)]
+struct PolkitInner {
+    authority: AuthorityProxy<'static>,
+    authorizations: Arc<Mutex<TemporaryAuthorizations>>,
+    _changed_task: Task<()>,
+}
+
+/// The cached temporary authorizations.
+#[derive(Debug)]
+struct TemporaryAuthorizations {
+    /// The expiry of the authorizations.
+    expiries: HashMap<AuthorizationKey, Instant>,
+    /// How long the authorizations are kept.
+    lifetime: Duration,
+}
+
+/// What a temporary authorization was obtained for.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct AuthorizationKey {
+    sender: OwnedUniqueName,
+    action_id: String,
+    details: BTreeMap<String, String>,
+}
+
+impl Polkit {
+    /// Use the polkit authority on the given connection, which is expected to be on the system bus.
+    pub async fn new(conn: &Connection) -> Result<Self> {
+        Self::for_authority(AuthorityProxy::new(conn).await?).await
+    }
+
+    /// Use the given polkit authority.
+    pub async fn for_authority(authority: AuthorityProxy<'static>) -> Result<Self> {
+        let authorizations = Arc::new(Mutex::new(TemporaryAuthorizations {
+            expiries: HashMap::new(),
+            lifetime: TEMPORARY_AUTHORIZATION_LIFETIME,
+        }));
+        let mut changed = authority.receive_changed().await?;
+        let weak = Arc::downgrade(&authorizations);
+        let changed_task = authority.inner().connection().executor().spawn(
+            async move {
+                while changed.next().await.is_some() {
+                    let Some(authorizations) = weak.upgrade() else {
+                        break;
+                    };
+                    debug!("polkit authorizations changed, clearing the cache");
+                    authorizations
+                        .lock()
+                        .expect("lock poisoned")
+                        .expiries
+                        .clear();
+                }
+            },
+            "polkit changes",
+        );
+
+        Ok(Self(Arc::new(PolkitInner {
+            authority,
+            authorizations,
+            _changed_task: changed_task,
+        })))
+    }
+
+    /// The proxy to the polkit authority.
+    pub fn authority(&self) -> &AuthorityProxy<'static> {
+        &self.0.authority
+    }
+
+    /// How long temporary authorizations are cached.
+    pub fn temporary_authorization_lifetime(&self) -> Duration {
+        self.0
+            .authorizations
+            .lock()
+            .expect("lock poisoned")
+            .lifetime
+    }
+
+    /// Set how long temporary authorizations are cached.
+    ///
+    /// This should match the lifetime of the temporary authorizations of the polkit authority,
+    /// which defaults to 5 minutes. A zero `lifetime` disables the caching. Authorizations already
+    /// cached keep their expiry.
+    pub fn set_temporary_authorization_lifetime(&self, lifetime: Duration) {
+        self.0
+            .authorizations
+            .lock()
+            .expect("lock poisoned")
+            .lifetime = lifetime;
+    }
+
+    /// Check if the sender of the method call with the given `header` is authorized to perform
+    /// the action with the given ID.
+    ///
+    /// `details` are passed on to the authority, to be used in the authentication dialog.
+    ///
+    /// Returns [`fdo::Error::InteractiveAuthorizationRequired`] if the caller could be authorized
+    /// by authenticating but didn't allow the interactive authentication, and
+    /// [`fdo::Error::AccessDenied`] if the caller isn't authorized.
+    pub async fn check(
+        &self,
+        action_id: &str,
+        header: &Header<'_>,
+        details: &HashMap<&str, &str>,
+    ) -> fdo::Result<()> {
+        let sender = header
+            .sender()
+            .ok_or_else(|| fdo::Error::AccessDenied("Method call without a sender".to_string()))?;
+        let key = AuthorizationKey {
+            sender: OwnedUniqueName::from(sender.to_owned()),
+            action_id: action_id.to_string(),
+            details: details
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+        if self.is_temporarily_authorized(&key) {
+            return Ok(());
+        }
+
+        let mut flags = BitFlags::empty();
+        if header
+            .primary()
+            .flags()
+            .contains(Flags::AllowInteractiveAuth)
+        {
+            flags |= CheckAuthorizationFlags::AllowUserInteraction;
+        }
+        let result = self
+            .0
+            .authority
+            .check_authorization(
+                &Subject::system_bus_name(sender),
+                action_id,
+                details,
+                flags,
+                "",
+            )
+            .await?;
+
+        if result.is_authorized() {
+            if result.is_temporary() {
+                let mut authorizations = self.0.authorizations.lock().expect("lock poisoned");
+                let now = Instant::now();
+                let expiry = now + authorizations.lifetime;
+                authorizations.expiries.retain(|_, expiry| *expiry > now);
+                authorizations.expiries.insert(key, expiry);
+            }
+
+            Ok(())
+        } else if result.is_challenge()
+            && !flags.contains(CheckAuthorizationFlags::AllowUserInteraction)
+        {
+            Err(fdo::Error::InteractiveAuthorizationRequired(format!(
+                "Interactive authentication required for `{action_id}`"
+            )))
+        } else {
+            debug!("`{sender}` is not authorized for `{action_id}`");
+
+            Err(fdo::Error::AccessDenied(format!(
+                "Not authorized for `{action_id}`"
+            )))
+        }
+    }
+
+    fn is_temporarily_authorized(&self, key: &AuthorizationKey) -> bool {
+        self.0
+            .authorizations
+            .lock()
+            .expect("lock poisoned")
+            .expiries
+            .get(key)
+            .is_some_and(|expiry| *expiry > Instant::now())
+    }
+}
+
+#[async_trait::async_trait]
+impl Authorizer for Polkit {
+    async fn authorize(&self, request: &AuthorizationRequest<'_>) -> fdo::Result<bool> {
+        let Some(action) = request.action() else {
+            return Ok(true);
+        };
+
+        self.check(action, request.header(), &HashMap::new())
+            .await
+            .map(|_| true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use enumflags2::BitFlags;
+    use ntest::timeout;
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc,
+            atomic::{AtomicU32, Ordering},
+        },
+        time::Duration,
+    };
+    use test_log::test;
+
+    use super::{AuthorityProxy, AuthorizationResult, CheckAuthorizationFlags, Polkit, Subject};
+    use crate::{
+        Message, Result, abstractions::timeout::sleep, connection, fdo, interface,
+        object_server::SignalEmitter, proxy, utils::block_on,
+    };
+
+    /// A stand-in for the polkit authority.
+    struct Authority {
+        checks: Arc<AtomicU32>,
+    }
+
+    #[interface(name = "org.freedesktop.PolicyKit1.Authority")]
+    impl Authority {
+        fn check_authorization(
+            &self,
+            subject: Subject,
+            action_id: &str,
+            _details: HashMap<String, String>,
+            flags: BitFlags<CheckAuthorizationFlags>,
+            _cancellation_id: &str,
+        ) -> AuthorizationResult {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(subject.kind(), "system-bus-name");
+            assert!(subject.details().contains_key("name"));
+
+            match action_id {
+                "org.zbus.PolkitTest.allowed" => {
+                    AuthorizationResult::new(true, false, HashMap::new())
+                }
+                "org.zbus.PolkitTest.keep"
+                    if flags.contains(CheckAuthorizationFlags::AllowUserInteraction) =>
+                {
+                    let details = HashMap::from([(
+                        "polkit.temporary_authorization_id".to_string(),
+                        "tmpauthz0".to_string(),
+                    )]);
+
+                    AuthorizationResult::new(true, false, details)
+                }
+                "org.zbus.PolkitTest.keep" => AuthorizationResult::new(false, true, HashMap::new()),
+                _ => AuthorizationResult::new(false, false, HashMap::new()),
+            }
+        }
+
+        #[zbus(signal)]
+        async fn changed(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+    }
+
+    struct Service;
+
+    #[interface(name = "org.zbus.PolkitTest")]
+    impl Service {
+        #[zbus(authorize = "org.zbus.PolkitTest.allowed")]
+        fn allowed(&self) {}
+
+        #[zbus(authorize = "org.zbus.PolkitTest.denied")]
+        fn denied(&self) {}
+
+        #[zbus(authorize = "org.zbus.PolkitTest.keep")]
+        fn keep(&self) {}
+    }
+
+    #[proxy(
+        interface = "org.zbus.PolkitTest",
+        default_path = "/org/zbus/PolkitTest"
+    )]
+    trait Service {
+        fn allowed(&self) -> Result<()>;
+        fn denied(&self) -> Result<()>;
+        fn keep(&self) -> Result<()>;
+        #[zbus(name = "Keep", allow_interactive_auth)]
+        fn keep_interactive(&self) -> Result<()>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn polkit() {
+        block_on(test_polkit()).unwrap();
+    }
+
+    async fn test_polkit() -> Result<()> {
+        let checks = Arc::new(AtomicU32::new(0));
+        let authority = connection::Builder::session()?
+            .serve_at(
+                "/org/freedesktop/PolicyKit1/Authority",
+                Authority {
+                    checks: checks.clone(),
+                },
+            )?
+            .build()
+            .await?;
+        let service = connection::Builder::session()?
+            .serve_at("/org/zbus/PolkitTest", Service)?
+            .build()
+            .await?;
+        let authority_proxy = AuthorityProxy::builder(&service)
+            .destination(authority.unique_name().unwrap().to_owned())?
+            .build()
+            .await?;
+        let polkit = Polkit::for_authority(authority_proxy).await?;
+        service.object_server().set_authorizer(polkit.clone());
+
+        let client = connection::Builder::session()?.build().await?;
+        let proxy = ServiceProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .build()
+            .await?;
+        proxy.allowed().await?;
+        assert_error(proxy.denied().await, "AccessDenied");
+        assert_error(proxy.keep().await, "InteractiveAuthorizationRequired");
+        assert_eq!(checks.load(Ordering::SeqCst), 3);
+
+        // The temporary authorization is cached.
+        proxy.keep_interactive().await?;
+        proxy.keep().await?;
+        assert_eq!(checks.load(Ordering::SeqCst), 4);
+
+        // But only for the same details.
+        let msg = Message::method_call("/org/zbus/PolkitTest", "Keep")?
+            .sender(client.unique_name().unwrap())?
+            .build(&())?;
+        let details = HashMap::from([("path", "/etc/shadow")]);
+        let res = polkit
+            .check("org.zbus.PolkitTest.keep", &msg.header(), &details)
+            .await;
+        assert!(matches!(
+            res,
+            Err(fdo::Error::InteractiveAuthorizationRequired(_))
+        ));
+        assert_eq!(checks.load(Ordering::SeqCst), 5);
+        polkit
+            .check("org.zbus.PolkitTest.keep", &msg.header(), &HashMap::new())
+            .await?;
+        assert_eq!(checks.load(Ordering::SeqCst), 5);
+
+        // Until the authority tells us about changes.
+        SignalEmitter::new(&authority, "/org/freedesktop/PolicyKit1/Authority")?
+            .emit("org.freedesktop.PolicyKit1.Authority", "Changed", &())
+            .await?;
+        while proxy.keep().await.is_ok() {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        // Temporary authorizations aren't cached if their lifetime is zero.
+        polkit.set_temporary_authorization_lifetime(Duration::ZERO);
+        proxy.keep_interactive().await?;
+        assert_error(proxy.keep().await, "InteractiveAuthorizationRequired");
+
+        Ok(())
+    }
+
+    fn assert_error(res: Result<()>, name: &str) {
+        match res {
+            Err(crate::Error::MethodError(error_name, _, _)) => {
+                assert_eq!(
+                    error_name,
+                    format!("org.freedesktop.DBus.Error.{name}").as_str()
+                )
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+}
@@ -1,9 +1,17 @@
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use std::{
+    collections::HashSet,
+    marker::PhantomData,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
 use zbus_names::{BusName, InterfaceName};
 use zvariant::{ObjectPath, Str};
 
-use crate::{Connection, Error, Proxy, Result, proxy::ProxyInner};
+use crate::{
+    Connection, Error, Proxy, Result,
+    proxy::{PropertiesCache, ProxyInner},
+};
 
 /// The properties caching mode.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +37,8 @@ pub struct Builder<'a, T = ()> {
     proxy_type: PhantomData<T>,
     cache: CacheProperties,
     uncached_properties: Option<HashSet<Str<'a>>>,
+    method_timeout: Option<Duration>,
+    properties_cache: Option<Arc<PropertiesCache>>,
 }
 
 impl<T> Clone for Builder<'_, T> {
@@ -40,6 +50,8 @@ impl<T> Clone for Builder<'_, T> {
             interface: self.interface.clone(),
             cache: self.cache,
             uncached_properties: self.uncached_properties.clone(),
+            method_timeout: self.method_timeout,
+            properties_cache: self.properties_cache.clone(),
             proxy_type: PhantomData,
         }
     }
@@ -92,6 +104,23 @@ impl<'a, T> Builder<'a, T> {
         self
     }
 
+    /// Set a timeout for method calls through the proxy.
+    ///
+    /// This overrides the timeout of the connection (see
+    /// [`crate::connection::Builder::method_timeout`]). Method calls will return
+    /// [`Error::MethodTimeout`] if no reply is received in time.
+    #[must_use]
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.method_timeout = Some(timeout);
+        self
+    }
+
+    /// Use the given properties cache, kept updated by the caller.
+    pub(crate) fn properties_cache(mut self, cache: Arc<PropertiesCache>) -> Self {
+        self.properties_cache = Some(cache);
+        self
+    }
+
     pub(crate) fn build_internal(self) -> Result<Proxy<'a>> {
         let conn = self.conn;
         let destination = self
@@ -101,16 +130,23 @@ impl<'a, T> Builder<'a, T> {
         let interface = self.interface.ok_or(Error::MissingParameter("interface"))?;
         let cache = self.cache;
         let uncached_properties = self.uncached_properties.unwrap_or_default();
+        let method_timeout = self.method_timeout;
+
+        let mut inner = ProxyInner::new(
+            conn,
+            destination,
+            path,
+            interface,
+            cache,
+            uncached_properties,
+            method_timeout,
+        );
+        if let Some(cache) = self.properties_cache {
+            inner.property_cache = Some(OnceLock::from((cache, None)));
+        }
 
         Ok(Proxy {
-            inner: Arc::new(ProxyInner::new(
-                conn,
-                destination,
-                path,
-                interface,
-                cache,
-                uncached_properties,
-            )),
+            inner: Arc::new(inner),
         })
     }
 
@@ -153,6 +189,8 @@ where
             interface: T::INTERFACE.clone(),
             cache: CacheProperties::default(),
             uncached_properties: None,
+            method_timeout: None,
+            properties_cache: None,
             proxy_type: PhantomData,
         }
     }
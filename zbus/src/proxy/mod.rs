@@ -12,6 +12,7 @@ use std::{
     pin::Pin,
     sync::{Arc, OnceLock, RwLock, RwLockReadGuard},
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{Instrument, debug, info_span, instrument, trace};
 
@@ -95,12 +96,18 @@ pub(crate) struct ProxyInner<'a> {
     pub(crate) interface: InterfaceName<'a>,
 
     /// Cache of property values.
-    property_cache: Option<OnceLock<(Arc<PropertiesCache>, Task<()>)>>,
+    property_cache: Option<OnceLock<(Arc<PropertiesCache>, Option<CachingTask>)>>,
     /// Set of properties which do not get cached, by name.
     /// This overrides proxy-level caching behavior.
     uncached_properties: HashSet<Str<'a>>,
+    /// Timeout for method calls, overriding the one of the connection.
+    method_timeout: Option<Duration>,
 }
 
+/// The task keeping a properties cache updated, unless the cache is kept updated by whoever
+/// created it.
+type CachingTask = Task<()>;
+
 impl Drop for ProxyInnerStatic {
     fn drop(&mut self) {
         if let Some(rule) = self.dest_owner_change_match_rule.take() {
@@ -319,6 +326,34 @@ impl PropertiesCache {
         (cache, task)
     }
 
+    /// Create a cache that is kept updated by the caller, through [`PropertiesCache::update`].
+    pub(crate) fn new_unmanaged() -> Arc<Self> {
+        Arc::new(PropertiesCache {
+            values: Default::default(),
+            caching_result: RwLock::new(CachingResult::Cached { result: Ok(()) }),
+        })
+    }
+
+    /// Update a cache created through [`PropertiesCache::new_unmanaged`].
+    pub(crate) fn update(
+        &self,
+        changed: &HashMap<&str, Value<'_>>,
+        invalidated: &[&str],
+        interface: &InterfaceName<'_>,
+    ) {
+        self.update_cache(&HashSet::new(), changed, invalidated, interface);
+    }
+
+    /// The cached values.
+    pub(crate) fn values(&self) -> HashMap<String, OwnedValue> {
+        self.values
+            .read()
+            .expect("lock poisoned")
+            .iter()
+            .filter_map(|(name, entry)| Some((name.clone(), entry.value.clone()?)))
+            .collect()
+    }
+
     /// new() runs this in a task it spawns for initialization of properties cache.
     async fn init(
         &self,
@@ -490,6 +525,7 @@ impl<'a> ProxyInner<'a> {
         interface: InterfaceName<'a>,
         cache: CacheProperties,
         uncached_properties: HashSet<Str<'a>>,
+        method_timeout: Option<Duration>,
     ) -> Self {
         let property_cache = match cache {
             CacheProperties::Yes | CacheProperties::Lazily => Some(OnceLock::new()),
@@ -505,6 +541,7 @@ impl<'a> ProxyInner<'a> {
             interface,
             property_cache,
             uncached_properties,
+            method_timeout,
         }
     }
 
@@ -634,6 +671,16 @@ impl<'a> Proxy<'a> {
         &self.inner.interface
     }
 
+    /// The timeout for method calls through this proxy (if any).
+    ///
+    /// This is the timeout set through [`Builder::method_timeout`] if any, or the one of the
+    /// connection otherwise.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner
+            .method_timeout
+            .or_else(|| self.inner.inner_without_borrows.conn.method_timeout())
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](https://docs.rs/zbus_xml) crate for parsing the
@@ -697,8 +744,10 @@ impl<'a> Proxy<'a> {
                 .map(|s| s.to_owned())
                 .collect();
             let executor = self.connection().executor();
+            let (cache, task) =
+                PropertiesCache::new(proxy, interface, executor, uncached_properties);
 
-            PropertiesCache::new(proxy, interface, executor, uncached_properties)
+            (cache, Some(task))
         });
 
         Some(cache)
@@ -820,17 +869,12 @@ impl<'a> Proxy<'a> {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        self.inner
-            .inner_without_borrows
-            .conn
-            .call_method(
-                Some(&self.inner.destination),
-                self.inner.path.as_str(),
-                Some(&self.inner.interface),
-                method_name,
-                body,
-            )
-            .await
+        let reply = self
+            .call_method_raw(method_name, BitFlags::empty(), self.method_timeout(), body)
+            .await?;
+
+        // SAFETY: There is always a reply when `NoReplyExpected` isn't set.
+        Ok(reply.expect("no reply"))
     }
 
     /// Call a method and return the reply body.
@@ -850,6 +894,28 @@ impl<'a> Proxy<'a> {
         reply.body().deserialize()
     }
 
+    /// Call a method with the given timeout and return the reply body.
+    ///
+    /// This is the same as [`Proxy::call`], except that the given timeout overrides the one of the
+    /// proxy and the connection (see [`Proxy::method_timeout`]). [`Error::MethodTimeout`] is
+    /// returned if no reply is received in time.
+    pub async fn call_with_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<R>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        self.call_with_flags_and_timeout(method_name, BitFlags::empty(), timeout, body)
+            .await
+            .map(|reply| reply.expect("no reply"))
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -865,6 +931,44 @@ impl<'a> Proxy<'a> {
         flags: BitFlags<MethodFlags>,
         body: &B,
     ) -> Result<Option<R>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        self.call_with_flags_internal(method_name, flags, self.method_timeout(), body)
+            .await
+    }
+
+    /// Call a method with the given timeout and return the reply body, optionally supplying a set
+    /// of method flags to control the way the method call message is sent and handled.
+    ///
+    /// This combines [`Proxy::call_with_flags`] and [`Proxy::call_with_timeout`].
+    pub async fn call_with_flags_and_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        flags: BitFlags<MethodFlags>,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Option<R>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        self.call_with_flags_internal(method_name, flags, Some(timeout), body)
+            .await
+    }
+
+    async fn call_with_flags_internal<'m, M, B, R>(
+        &self,
+        method_name: M,
+        flags: BitFlags<MethodFlags>,
+        method_timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<Option<R>>
     where
         M: TryInto<MemberName<'m>>,
         M::Error: Into<Error>,
@@ -872,6 +976,27 @@ impl<'a> Proxy<'a> {
         R: for<'d> zvariant::DynamicDeserialize<'d>,
     {
         let flags = flags.iter().map(Flags::from).collect::<BitFlags<_>>();
+        match self
+            .call_method_raw(method_name, flags, method_timeout, body)
+            .await?
+        {
+            Some(reply) => reply.body().deserialize().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn call_method_raw<'m, M, B>(
+        &self,
+        method_name: M,
+        flags: BitFlags<Flags>,
+        method_timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<Option<Message>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
         match self
             .inner
             .inner_without_borrows
@@ -886,7 +1011,7 @@ impl<'a> Proxy<'a> {
             )
             .await?
         {
-            Some(reply) => reply.await?.body().deserialize().map(Some),
+            Some(reply) => reply.reply(method_timeout).await.map(Some),
             None => Ok(None),
         }
     }
@@ -1019,6 +1144,74 @@ impl<'a> Proxy<'a> {
             name: self.destination().clone(),
         })
     }
+
+    /// Wait for the destination to have an owner, and return its unique name.
+    ///
+    /// This returns right away if the destination already has an owner. Otherwise, it waits for
+    /// the destination to be acquired, without racing with the `NameOwnerChanged` signal.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::OwnerTimeout`] is returned if the destination still has no owner after `timeout`.
+    /// [`Error::Unsupported`] is returned on peer-to-peer connections.
+    pub async fn wait_for_owner(&self, timeout: Duration) -> Result<UniqueName<'static>> {
+        self.wait_for_owner_internal(timeout, false).await
+    }
+
+    /// Request the bus to start the destination service, and wait for it to have an owner.
+    ///
+    /// This is the same as [`Proxy::wait_for_owner`], except that the service is first activated
+    /// through `org.freedesktop.DBus.StartServiceByName`, if the destination is a well-known name.
+    /// Activation errors, e.g. if no service provides the name, are returned as is.
+    pub async fn start_and_wait_for_owner(&self, timeout: Duration) -> Result<UniqueName<'static>> {
+        self.wait_for_owner_internal(timeout, true).await
+    }
+
+    async fn wait_for_owner_internal(
+        &self,
+        duration: Duration,
+        start: bool,
+    ) -> Result<UniqueName<'static>> {
+        let conn = self.connection();
+        if !conn.is_bus() {
+            return Err(Error::Unsupported);
+        }
+        let destination = self.destination();
+
+        let wait = async {
+            use futures_lite::StreamExt;
+
+            // Subscribe first, so the name being acquired in between isn't missed.
+            let mut owner_changed = self.receive_owner_changed().await?;
+            let dbus_proxy = fdo::DBusProxy::builder(conn)
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?;
+            if let (true, BusName::WellKnown(name)) = (start, destination) {
+                dbus_proxy.start_service_by_name(name.clone(), 0).await?;
+            }
+            match dbus_proxy.get_name_owner(destination.clone()).await {
+                Ok(owner) => return Ok(owner.into_inner()),
+                Err(fdo::Error::NameHasNoOwner(_)) => (),
+                Err(e) => return Err(e.into()),
+            }
+            trace!("Waiting for `{destination}` to have an owner");
+
+            while let Some(owner) = owner_changed.next().await {
+                if let Some(owner) = owner {
+                    return Ok(owner);
+                }
+            }
+
+            Err(Error::InputOutput(
+                std::io::Error::new(std::io::ErrorKind::BrokenPipe, "connection closed").into(),
+            ))
+        };
+
+        crate::timeout::timeout(wait, duration)
+            .await
+            .unwrap_or_else(|| Err(Error::OwnerTimeout(destination.to_owned())))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -1555,4 +1748,73 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[timeout(15000)]
+    fn wait_for_owner() {
+        block_on(test_wait_for_owner()).unwrap();
+    }
+
+    async fn test_wait_for_owner() -> Result<()> {
+        #[proxy(
+            interface = "org.freedesktop.zbus.WaitForOwnerTest",
+            default_service = "org.freedesktop.zbus.WaitForOwnerTest",
+            default_path = "/org/freedesktop/zbus/WaitForOwnerTest"
+        )]
+        trait Test {
+            fn ping(&self) -> Result<()>;
+        }
+
+        let name = "org.freedesktop.zbus.WaitForOwnerTest";
+        let conn = Connection::session().await?;
+        let proxy = TestProxy::builder(&conn)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+
+        // Nobody owns the name.
+        assert_eq!(
+            proxy
+                .inner()
+                .wait_for_owner(Duration::from_millis(100))
+                .await,
+            Err(Error::OwnerTimeout(BusName::try_from(name)?))
+        );
+
+        // The name is acquired while waiting.
+        let service = Connection::session().await?;
+        let (owner, requested) = futures_util::join!(
+            proxy.inner().wait_for_owner(Duration::from_secs(10)),
+            async {
+                crate::timeout::sleep(Duration::from_millis(50)).await;
+                service.request_name(name).await
+            }
+        );
+        requested?;
+        assert_eq!(owner?, *service.unique_name().unwrap());
+
+        // The name is already owned.
+        assert_eq!(
+            proxy
+                .inner()
+                .wait_for_owner(Duration::from_secs(10))
+                .await?,
+            *service.unique_name().unwrap()
+        );
+
+        // Activation errors are reported as is.
+        let proxy: Proxy<'_> = Builder::new(&conn)
+            .destination("org.freedesktop.zbus.WaitForOwnerTest.NotActivatable")?
+            .path("/does/not/matter")?
+            .interface("does.not.matter")?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let res = proxy
+            .start_and_wait_for_owner(Duration::from_secs(10))
+            .await;
+        assert!(matches!(res, Err(Error::FDO(e)) if matches!(*e, fdo::Error::ServiceUnknown(_))));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,478 @@
+//! Helpers for testing D-Bus services.
+//!
+//! This module is only available with the `testing` cargo feature enabled.
+
+use std::{collections::BTreeMap, fmt};
+
+use zbus_xml::{
+    Annotation, Node,
+    diff::{Change, diff_interfaces},
+};
+pub use zbus_xml::{ArgDirection, PropertyAccess, diff::Member};
+use zvariant::Signature;
+
+use crate::object_server::Interface;
+
+const EMITS_CHANGED_SIGNAL_ANNOTATION: &str = "org.freedesktop.DBus.Property.EmitsChangedSignal";
+
+/// A difference between an interface implementation and its specification.
+///
+/// See [`check_conformance`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Difference {
+    /// The member is specified but not implemented.
+    Missing(Member),
+    /// The member is implemented but not specified.
+    Extra(Member),
+    /// The arguments of a method or a signal, in the given direction, differ.
+    Args {
+        member: Member,
+        direction: ArgDirection,
+        expected: Vec<Signature>,
+        actual: Vec<Signature>,
+    },
+    /// The type of a property differs.
+    PropertyType {
+        property: String,
+        expected: Signature,
+        actual: Signature,
+    },
+    /// The access of a property differs.
+    PropertyAccess {
+        property: String,
+        expected: PropertyAccess,
+        actual: PropertyAccess,
+    },
+    /// The value of an annotation differs. `None` means the annotation is absent.
+    Annotation {
+        member: Member,
+        name: String,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+    /// Any other change from the specification to the implementation.
+    Other(Change),
+}
+
+impl From<Change> for Difference {
+    fn from(change: Change) -> Self {
+        match change {
+            Change::InterfaceAdded(interface) => Difference::Extra(Member::Interface(interface)),
+            Change::InterfaceRemoved(interface) => {
+                Difference::Missing(Member::Interface(interface))
+            }
+            Change::MemberAdded { member, .. } => Difference::Extra(member),
+            Change::MemberRemoved { member, .. } => Difference::Missing(member),
+            Change::ArgsChanged {
+                member,
+                direction,
+                old,
+                new,
+                ..
+            } => Difference::Args {
+                member,
+                direction,
+                expected: old,
+                actual: new,
+            },
+            Change::PropertyTypeChanged {
+                property, old, new, ..
+            } => Difference::PropertyType {
+                property,
+                expected: old,
+                actual: new,
+            },
+            Change::PropertyAccessChanged {
+                property, old, new, ..
+            } => Difference::PropertyAccess {
+                property,
+                expected: old,
+                actual: new,
+            },
+            change => Difference::Other(change),
+        }
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::Missing(member) => write!(f, "{member} is not implemented"),
+            Difference::Extra(member) => write!(f, "{member} is not in the specification"),
+            Difference::Args {
+                member,
+                direction,
+                expected,
+                actual,
+            } => {
+                let direction = match direction {
+                    ArgDirection::In => "input",
+                    ArgDirection::Out => "output",
+                };
+                let signatures = |signatures: &[Signature]| -> String {
+                    signatures.iter().map(ToString::to_string).collect()
+                };
+                write!(
+                    f,
+                    "{member} has {direction} arguments `({})`, expected `({})`",
+                    signatures(actual),
+                    signatures(expected),
+                )
+            }
+            Difference::PropertyType {
+                property,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "property `{property}` has type `{actual}`, expected `{expected}`"
+            ),
+            Difference::PropertyAccess {
+                property,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "property `{property}` has access `{actual}`, expected `{expected}`"
+            ),
+            Difference::Annotation {
+                member,
+                name,
+                expected,
+                actual,
+            } => {
+                write!(f, "{member} has ")?;
+                match actual {
+                    Some(value) => write!(f, "annotation `{name}` = `{value}`")?,
+                    None => write!(f, "no annotation `{name}`")?,
+                }
+                match expected {
+                    Some(value) => write!(f, ", expected `{value}`"),
+                    None => write!(f, ", expected none"),
+                }
+            }
+            Difference::Other(change) => write!(f, "{change}"),
+        }
+    }
+}
+
+/// Compare the introspection data of `iface` against the interface of the same name in `spec`.
+///
+/// Argument names and documentation are not taken into account, only what is relevant to the
+/// peers of a service. An empty list means that `iface` conforms to `spec`.
+pub fn check_conformance<I: Interface>(iface: &I, spec: &Node<'_>) -> Vec<Difference> {
+    let mut xml = String::from("<node>");
+    iface.introspect_to_writer(&mut xml, 0);
+    xml.push_str("</node>");
+    let actual = Node::try_from(xml.as_str()).expect("Invalid introspection data");
+    let actual = &actual.interfaces()[0];
+
+    let name = I::name();
+    let Some(expected) = spec.interfaces().iter().find(|i| i.name() == name) else {
+        return vec![Difference::Extra(Member::Interface(name.to_string()))];
+    };
+
+    let mut diffs = vec![];
+    diff_annotations(
+        &mut diffs,
+        Member::Interface(name.to_string()),
+        expected.annotations(),
+        actual.annotations(),
+    );
+    diffs.extend(
+        diff_interfaces(expected, actual)
+            .into_iter()
+            .map(Difference::from),
+    );
+    diff_members_annotations(
+        &mut diffs,
+        expected.methods(),
+        actual.methods(),
+        |m| Member::Method(m.name().to_string()),
+        zbus_xml::Method::annotations,
+    );
+    diff_members_annotations(
+        &mut diffs,
+        expected.signals(),
+        actual.signals(),
+        |s| Member::Signal(s.name().to_string()),
+        zbus_xml::Signal::annotations,
+    );
+    diff_members_annotations(
+        &mut diffs,
+        expected.properties(),
+        actual.properties(),
+        |p| Member::Property(p.name().to_string()),
+        zbus_xml::Property::annotations,
+    );
+
+    diffs
+}
+
+/// Assert that `iface` conforms to its specification in the introspection XML `xml`.
+///
+/// # Panics
+///
+/// If `xml` can't be parsed or if [`check_conformance`] reports any difference, all of which are
+/// listed in the panic message.
+///
+/// # Example
+///
+/// ```
+/// use zbus::{interface, testing::assert_conforms};
+///
+/// struct Greeter;
+///
+/// #[interface(name = "org.zbus.Greeter1")]
+/// impl Greeter {
+///     fn say_hello(&self, name: &str) -> String {
+///         format!("Hello {name}!")
+///     }
+///
+///     #[zbus(property)]
+///     fn greeting_count(&self) -> u32 {
+///         0
+///     }
+/// }
+///
+/// assert_conforms(
+///     &Greeter,
+///     r#"
+///     <node>
+///       <interface name="org.zbus.Greeter1">
+///         <method name="SayHello">
+///           <arg name="name" type="s" direction="in"/>
+///           <arg type="s" direction="out"/>
+///         </method>
+///         <property name="GreetingCount" type="u" access="read"/>
+///       </interface>
+///     </node>
+///     "#,
+/// );
+/// ```
+#[track_caller]
+pub fn assert_conforms<I: Interface>(iface: &I, xml: &str) {
+    let spec = Node::try_from(xml).expect("Invalid introspection XML");
+    let diffs = check_conformance(iface, &spec);
+    if diffs.is_empty() {
+        return;
+    }
+
+    let mut msg = format!("`{}` does not conform to its specification:", I::name());
+    for diff in &diffs {
+        msg.push_str("\n  - ");
+        msg.push_str(&diff.to_string());
+    }
+    panic!("{msg}");
+}
+
+/// Compare the annotations of the members that are both in `expected` and `actual`.
+fn diff_members_annotations<T>(
+    diffs: &mut Vec<Difference>,
+    expected: &[T],
+    actual: &[T],
+    member: impl Fn(&T) -> Member,
+    annotations: impl Fn(&T) -> &[Annotation],
+) {
+    for actual in actual {
+        let name = member(actual);
+        if let Some(expected) = expected.iter().find(|e| member(e) == name) {
+            diff_annotations(diffs, name, annotations(expected), annotations(actual));
+        }
+    }
+}
+
+fn diff_annotations(
+    diffs: &mut Vec<Difference>,
+    member: Member,
+    expected: &[Annotation],
+    actual: &[Annotation],
+) {
+    let annotations = |annotations: &[Annotation]| -> BTreeMap<String, String> {
+        annotations
+            .iter()
+            // This is the default so it's the same whether or not it's specified.
+            .filter(|a| a.name() != EMITS_CHANGED_SIGNAL_ANNOTATION || a.value() != "true")
+            .map(|a| (a.name().to_string(), a.value().to_string()))
+            .collect()
+    };
+    let mut expected = annotations(expected);
+    let actual = annotations(actual);
+
+    for (name, actual) in actual {
+        let expected = expected.remove(&name);
+        if expected.as_ref() != Some(&actual) {
+            diffs.push(Difference::Annotation {
+                member: member.clone(),
+                name,
+                expected,
+                actual: Some(actual),
+            });
+        }
+    }
+    diffs.extend(
+        expected
+            .into_iter()
+            .map(|(name, expected)| Difference::Annotation {
+                member: member.clone(),
+                name,
+                expected: Some(expected),
+                actual: None,
+            }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::{interface, object_server::SignalEmitter};
+
+    struct Counter {
+        count: u32,
+    }
+
+    #[interface(name = "org.zbus.Counter1")]
+    impl Counter {
+        fn increment(&mut self, by: u32) -> u32 {
+            self.count += by;
+
+            self.count
+        }
+
+        fn reset(&mut self) {}
+
+        #[zbus(signal)]
+        async fn overflowed(emitter: &SignalEmitter<'_>, count: u32) -> crate::Result<()>;
+
+        #[zbus(property)]
+        fn count(&self) -> u32 {
+            self.count
+        }
+
+        #[zbus(property(emits_changed_signal = "const"))]
+        fn max(&self) -> u32 {
+            u32::MAX
+        }
+    }
+
+    const SPEC: &str = r#"
+        <node>
+          <interface name="org.zbus.Counter1">
+            <method name="Increment">
+              <arg name="by" type="u"/>
+              <arg name="count" type="u" direction="out"/>
+            </method>
+            <method name="Reset"/>
+            <signal name="Overflowed">
+              <arg name="count" type="u"/>
+            </signal>
+            <property name="Count" type="u" access="read">
+              <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="true"/>
+            </property>
+            <property name="Max" type="u" access="read">
+              <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="const"/>
+            </property>
+          </interface>
+        </node>
+    "#;
+
+    #[test]
+    #[timeout(15000)]
+    fn conforms() {
+        assert_conforms(&Counter { count: 0 }, SPEC);
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn differences() {
+        let spec = r#"
+            <node>
+              <interface name="org.zbus.Counter1">
+                <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+                <method name="Increment">
+                  <arg name="by" type="i" direction="in"/>
+                  <arg name="count" type="u" direction="out"/>
+                </method>
+                <method name="Decrement"/>
+                <signal name="Overflowed">
+                  <arg name="count" type="u"/>
+                  <arg name="by" type="u"/>
+                </signal>
+                <property name="Count" type="i" access="readwrite"/>
+                <property name="Max" type="u" access="read"/>
+              </interface>
+            </node>
+        "#;
+        let spec = Node::try_from(spec).unwrap();
+        let diffs = check_conformance(&Counter { count: 0 }, &spec);
+
+        let u = || Signature::U32;
+        assert_eq!(
+            diffs,
+            vec![
+                Difference::Annotation {
+                    member: Member::Interface("org.zbus.Counter1".into()),
+                    name: "org.freedesktop.DBus.Deprecated".into(),
+                    expected: Some("true".into()),
+                    actual: None,
+                },
+                Difference::Missing(Member::Method("Decrement".into())),
+                Difference::Args {
+                    member: Member::Method("Increment".into()),
+                    direction: ArgDirection::In,
+                    expected: vec![Signature::I32],
+                    actual: vec![u()],
+                },
+                Difference::Extra(Member::Method("Reset".into())),
+                Difference::Args {
+                    member: Member::Signal("Overflowed".into()),
+                    direction: ArgDirection::Out,
+                    expected: vec![u(), u()],
+                    actual: vec![u()],
+                },
+                Difference::PropertyType {
+                    property: "Count".into(),
+                    expected: Signature::I32,
+                    actual: u(),
+                },
+                Difference::PropertyAccess {
+                    property: "Count".into(),
+                    expected: PropertyAccess::ReadWrite,
+                    actual: PropertyAccess::Read,
+                },
+                Difference::Annotation {
+                    member: Member::Property("Max".into()),
+                    name: EMITS_CHANGED_SIGNAL_ANNOTATION.into(),
+                    expected: None,
+                    actual: Some("const".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            diffs[2].to_string(),
+            "method `Increment` has input arguments `(u)`, expected `(i)`"
+        );
+
+        let other = Node::try_from(r#"<node><interface name="org.zbus.Other"/></node>"#).unwrap();
+        assert_eq!(
+            check_conformance(&Counter { count: 0 }, &other),
+            vec![Difference::Extra(Member::Interface(
+                "org.zbus.Counter1".into()
+            ))],
+        );
+    }
+
+    #[test]
+    #[timeout(15000)]
+    #[should_panic(expected = "property `Count` has type `u`, expected `s`")]
+    fn does_not_conform() {
+        assert_conforms(
+            &Counter { count: 0 },
+            &SPEC.replace(r#"type="u" access"#, r#"type="s" access"#),
+        );
+    }
+}
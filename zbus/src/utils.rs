@@ -52,6 +52,14 @@ pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
         .block_on(future)
 }
 
+/// Compare two secrets, in a time only depending on their lengths.
+#[cfg(any(feature = "cookie-sha1", feature = "p2p"))]
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let diff = a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b));
+
+    a.len() == b.len() && std::hint::black_box(diff) == 0
+}
+
 // If we're running inside a Flatpak sandbox.
 pub(crate) fn is_flatpak() -> bool {
     std::env::var("FLATPAK_ID").is_ok()
@@ -442,11 +442,32 @@ pub async fn my_iface_test(conn: Connection, event: Event) -> zbus::Result<u32>
         "method timeout should be set"
     );
     match proxy.never_return().await {
-        Err(Error::InputOutput(e)) if e.kind() == std::io::ErrorKind::TimedOut => {}
-        r => panic!(
-            "Should produce InputOutput(TimedOut) error. Got {:?} instead",
-            r
-        ),
+        Err(Error::MethodTimeout) => {}
+        r => panic!("Should produce MethodTimeout error. Got {:?} instead", r),
+    };
+    match proxy.never_return_quickly().await {
+        Err(Error::MethodTimeout) => {}
+        r => panic!("Should produce MethodTimeout error. Got {:?} instead", r),
+    };
+    assert_eq!(proxy.inner().method_timeout(), conn.method_timeout());
+    let timeout_proxy = MyIfaceProxy::builder(&conn)
+        .destination("org.freedesktop.MyService")?
+        .path("/org/freedesktop/MyService")?
+        .method_timeout(std::time::Duration::from_secs(60))
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    assert_eq!(
+        timeout_proxy.inner().method_timeout(),
+        Some(std::time::Duration::from_secs(60))
+    );
+    match timeout_proxy
+        .inner()
+        .call_with_timeout::<_, _, ()>("NeverReturn", std::time::Duration::from_millis(20), &())
+        .await
+    {
+        Err(Error::MethodTimeout) => {}
+        r => panic!("Should produce MethodTimeout error. Got {:?} instead", r),
     };
 
     proxy.quit().await?;
@@ -51,6 +51,7 @@ def_attrs! {
             }
         },
         out_args [str],
+        authorize str,
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -61,7 +62,8 @@ def_attrs! {
                 object_vec none,
                 no_reply none,
                 no_autostart none,
-                allow_interactive_auth none
+                allow_interactive_auth none,
+                timeout str
             }
         }
     };
@@ -301,6 +303,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
     let mut get_all = quote!();
     let mut call_dispatch = quote!();
     let mut call_mut_dispatch = quote!();
+    let mut authorization_dispatch = quote!();
     let mut introspect = quote!();
     let mut generated_signals = quote!();
     let mut signals_trait_methods = quote!();
@@ -395,6 +398,19 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
             &doc_attrs,
             introspect_docs,
         )?;
+        if let Some(action) = &method_attrs.authorize {
+            if method_info.method_type != MethodType::Other {
+                return Err(Error::new_spanned(
+                    method,
+                    "`authorize` can only be specified on methods",
+                ));
+            }
+            let member_name = &method_info.member_name;
+            authorization_dispatch.extend(quote! {
+                #(#cfg_attrs)*
+                #member_name => ::std::option::Option::Some(#action),
+            });
+        }
         let attr_property = method_attrs.property;
         if let Some(prop_attrs) = &attr_property {
             let property: &mut Property = properties
@@ -739,11 +755,10 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                                 let mut changed = ::std::collections::HashMap::new();
                                 let value = <#zbus::zvariant::Value as ::std::convert::From<_>>::from(#prop_value_handled);
                                 changed.insert(#member_name, value);
-                                #zbus::fdo::Properties::properties_changed(
-                                    __zbus__signal_emitter,
+                                __zbus__signal_emitter.emit_properties_changed(
                                     #zbus::names::InterfaceName::from_static_str_unchecked(#iface_name),
                                     changed,
-                                    ::std::borrow::Cow::Borrowed(&[]),
+                                    &[],
                                 ).await
                             }
                         );
@@ -765,11 +780,10 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                                 &self,
                                 __zbus__signal_emitter: &#zbus::object_server::SignalEmitter<'_>,
                             ) -> #zbus::Result<()> {
-                                #zbus::fdo::Properties::properties_changed(
-                                    __zbus__signal_emitter,
+                                __zbus__signal_emitter.emit_properties_changed(
                                     #zbus::names::InterfaceName::from_static_str_unchecked(#iface_name),
                                     ::std::collections::HashMap::new(),
-                                    ::std::borrow::Cow::Borrowed(&[#member_name]),
+                                    &[#member_name],
                                 ).await
                             }
                         );
@@ -862,6 +876,21 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         }
     };
 
+    let authorization = if authorization_dispatch.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            fn authorization(
+                &self,
+                method: &#zbus::names::MemberName<'_>,
+            ) -> ::std::option::Option<&'static str> {
+                match method.as_str() {
+                    #authorization_dispatch
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    };
     let proxy = proxy.map(|proxy| proxy.r#gen()).transpose()?;
     let introspect_format_str = format!("{}<interface name=\"{iface_name}\">", "{:indent$}");
 
@@ -987,6 +1016,8 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                 }
                 ::std::writeln!(writer, r#"{:indent$}</interface>"#, "", indent = level).unwrap();
             }
+
+            #authorization
         }
 
         #proxy
@@ -1568,6 +1599,9 @@ impl Proxy {
             if attrs.allow_interactive_auth {
                 proxy_method_attrs.extend(quote! { allow_interactive_auth, });
             }
+            if let Some(timeout) = attrs.timeout {
+                proxy_method_attrs.extend(quote! { timeout = #timeout, });
+            }
         }
         let cfg_attrs = method_info.cfg_attrs;
         let doc_attrs = method_info.doc_attrs;
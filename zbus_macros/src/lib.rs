@@ -19,6 +19,8 @@ use syn::{
 mod error;
 mod iface;
 mod proxy;
+#[cfg(feature = "proxy-from-xml")]
+mod proxy_from_xml;
 mod utils;
 
 /// Attribute macro for defining D-Bus proxies (using [`zbus::Proxy`] and
@@ -30,6 +32,9 @@ mod utils;
 /// former doesn't take any argument and uses the default service name and path. The later allows
 /// you to specify non-default proxy arguments.
 ///
+/// To wait for the service to be available on the bus, use the underlying proxy, e.g.
+/// `proxy.inner().wait_for_owner(timeout)` (see [`zbus::Proxy::wait_for_owner`]).
+///
 /// The following attributes are supported:
 ///
 /// * `interface` - the name of the D-Bus interface this proxy is for.
@@ -89,6 +94,12 @@ mod utils;
 /// * `allow_interactive_auth` - declare a method call that is allowed to trigger an interactive
 ///   prompt for authorization or confirmation from the receiver.
 ///
+/// * `timeout` - the time to wait for the reply to this method call, overriding the timeout of
+///   the proxy and the connection (see [`zbus::Proxy::method_timeout`]). The value is an integer
+///   followed by one of the `ms`, `s`, `min` or `h` units, e.g. `#[zbus(timeout = "500ms")]`.
+///   If no reply is received in time, the method returns [`zbus::Error::MethodTimeout`]. Can't be
+///   combined with `no_reply`.
+///
 /// * `object` - methods or properties that return an [`ObjectPath`] can be annotated with the
 ///   `object` attribute to specify the proxy object to be constructed from the returned
 ///   [`ObjectPath`].
@@ -202,6 +213,9 @@ mod utils;
 ///
 /// [`zbus_polkit`]: https://docs.rs/zbus_polkit/1.0.0/zbus_polkit/policykit1/index.html
 /// [`zbus::Proxy`]: https://docs.rs/zbus/latest/zbus/proxy/struct.Proxy.html
+/// [`zbus::Proxy::wait_for_owner`]: https://docs.rs/zbus/latest/zbus/proxy/struct.Proxy.html#method.wait_for_owner
+/// [`zbus::Proxy::method_timeout`]: https://docs.rs/zbus/latest/zbus/proxy/struct.Proxy.html#method.method_timeout
+/// [`zbus::Error::MethodTimeout`]: https://docs.rs/zbus/latest/zbus/enum.Error.html#variant.MethodTimeout
 /// [`zbus::message::Message`]: https://docs.rs/zbus/latest/zbus/message/struct.Message.html
 /// [`zbus::proxy::PropertyStream`]: https://docs.rs/zbus/latest/zbus/proxy/struct.PropertyStream.html
 /// [`zbus::blocking::Proxy`]: https://docs.rs/zbus/latest/zbus/blocking/proxy/struct.Proxy.html
@@ -218,6 +232,59 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Function-like macro for generating a D-Bus proxy from a D-Bus XML interface description.
+///
+/// The macro reads the introspection XML file at build time and expands to the [`proxy`] trait
+/// that `zbus-xmlgen` would generate for the given interface, along with its proxy types. This
+/// keeps the proxy in sync with the XML file, as the crate gets rebuilt whenever the file changes.
+///
+/// This macro is only available with the `proxy-from-xml` cargo feature enabled.
+///
+/// The following attributes are supported:
+///
+/// * `file` - the path of the XML file, relative to the crate's root directory
+///   (`CARGO_MANIFEST_DIR`).
+///
+/// * `interface` - the name of the D-Bus interface to generate the proxy for.
+///
+/// * `default_service` - the default service the proxy should connect to.
+///
+/// * `default_path` - the default object path of the proxy.
+///
+/// * `crate` - specify the path to the `zbus` crate if it's renamed or re-exported.
+///
+/// As with `zbus-xmlgen`, defaults are assumed for `default_service` and `default_path` if either
+/// of them isn't specified. The trait is named after the last component of the interface name.
+/// The methods, signals and properties involving file descriptors are only generated for Unix
+/// targets.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// use zbus::{blocking::Connection, proxy_from_xml};
+///
+/// proxy_from_xml!(
+///     file = "tests/data/calculator.xml",
+///     interface = "org.freedesktop.zbus_macros.Calculator",
+///     default_service = "org.freedesktop.zbus_macros",
+///     default_path = "/org/freedesktop/zbus_macros/calculator",
+/// );
+///
+/// let connection = Connection::session()?;
+/// let proxy = CalculatorProxyBlocking::new(&connection)?;
+/// assert_eq!(proxy.add(1, 2)?, 3);
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+#[cfg(feature = "proxy-from-xml")]
+#[proc_macro]
+pub fn proxy_from_xml(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input with Punctuated<Meta, Token![,]>::parse_terminated);
+    proxy_from_xml::expand(args)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// Attribute macro for implementing a D-Bus interface.
 ///
 /// The macro must be applied on an `impl T`. All methods will be exported, either as methods,
@@ -285,6 +352,11 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// * `out_args` - When returning multiple values from a method, naming the out arguments become
 ///   important. You can use `out_args` to specify their names.
 ///
+/// * `authorize` - tie the method to the given action (e.g `"org.example.Service.reboot"`). The
+///   [`Authorizer`] set on the [`ObjectServer`] decides whether calls to the method are allowed,
+///   and calls are always denied if there is none. Only valid on methods, not on properties or
+///   signals.
+///
 /// * `proxy` - Use this to specify the [`macro@proxy`]-specific method sub-attributes (e.g
 ///   `object`). The common sub-attributes (e.g `name`) are automatically forworded to the
 ///   [`macro@proxy`] macro. Moreover, you can use `visibility` sub-attribute to specify the
@@ -390,6 +462,7 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// [`Connection::emit_signal()`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html#method.emit_signal
 /// [`SignalEmitter`]: https://docs.rs/zbus/latest/zbus/object_server/struct.SignalEmitter.html
 /// [`Interface`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Interface.html
+/// [`Authorizer`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Authorizer.html
 /// [dbus_emits_changed_signal]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
 #[proc_macro_attribute]
 pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -2,8 +2,8 @@ use crate::utils::{PropertyEmitsChangedSignal, parse_crate_path, pat_ident, type
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::{
-    Error, FnArg, Ident, ItemTrait, Meta, Path, ReturnType, Token, TraitItemFn, Visibility,
-    fold::Fold, parse_quote, parse_str, punctuated::Punctuated, spanned::Spanned,
+    Attribute, Error, FnArg, Ident, Item, ItemTrait, Meta, Path, ReturnType, Token, TraitItemFn,
+    Visibility, fold::Fold, parse_quote, parse_str, punctuated::Punctuated, spanned::Spanned,
 };
 use zvariant_utils::{case, def_attrs};
 
@@ -39,7 +39,8 @@ def_attrs! {
         object_vec none,
         no_reply none,
         no_autostart none,
-        allow_interactive_auth none
+        allow_interactive_auth none,
+        timeout str
     };
 }
 
@@ -222,6 +223,12 @@ pub fn create_proxy(
             let is_signal = method_attrs.signal;
             let is_property = property.is_some();
             let has_inputs = m.sig.inputs.len() > 1;
+            if method_attrs.timeout.is_some() && (is_signal || is_property) {
+                return Err(Error::new(
+                    m.span(),
+                    "`timeout` can only be used on method calls",
+                ));
+            }
 
             let dbus_member_name = method_attrs.name.clone().unwrap_or_else(|| {
                 case::pascal_or_camel_case(
@@ -562,6 +569,22 @@ fn gen_proxy_method_call(
         _ => None,
     };
 
+    let timeout = method_attrs
+        .timeout
+        .as_deref()
+        .map(|timeout| {
+            if method_attrs.no_reply {
+                return Err(Error::new(
+                    m.span(),
+                    "`timeout` can't be used with `no_reply`",
+                ));
+            }
+            let millis = parse_timeout(timeout).map_err(|e| Error::new(m.span(), e))?;
+
+            Ok(quote!(::std::time::Duration::from_millis(#millis)))
+        })
+        .transpose()?;
+
     let mut method = parse_str::<Ident>(rust_method_name)?;
     method.set_span(Span::call_site());
     let inputs = &m.sig.inputs;
@@ -620,12 +643,22 @@ fn gen_proxy_method_call(
                 .build()
                 #wait
         };
-        let method_call = quote! {
-            self.0.call(
-                #dbus_member_name,
-                &#zbus::zvariant::DynamicTuple((#(#args,)*)),
-            )
-            #wait?
+        let method_call = match &timeout {
+            Some(timeout) => quote! {
+                self.0.call_with_timeout(
+                    #dbus_member_name,
+                    #timeout,
+                    &#zbus::zvariant::DynamicTuple((#(#args,)*)),
+                )
+                #wait?
+            },
+            None => quote! {
+                self.0.call(
+                    #dbus_member_name,
+                    &#zbus::zvariant::DynamicTuple((#(#args,)*)),
+                )
+                #wait?
+            },
         };
         let body = if proxy_vec {
             quote! {
@@ -681,10 +714,23 @@ fn gen_proxy_method_call(
                     }
                 })
             } else {
+                let call = match &timeout {
+                    Some(timeout) => quote! {
+                        self.0.call_with_flags_and_timeout(
+                            #dbus_member_name,
+                            #method_flags,
+                            #timeout,
+                            #body,
+                        )
+                    },
+                    None => quote! {
+                        self.0.call_with_flags(#dbus_member_name, #method_flags, #body)
+                    },
+                };
                 Ok(quote! {
                     #(#other_attrs)*
                     pub #usage #signature {
-                        let reply = self.0.call_with_flags(#dbus_member_name, #method_flags, #body)#wait?;
+                        let reply = #call #wait?;
 
                         // SAFETY: This unwrap() cannot fail due to the guarantees in
                         // call_with_flags, which can only return Ok(None) if the
@@ -697,10 +743,16 @@ fn gen_proxy_method_call(
                 })
             }
         } else {
+            let call = match &timeout {
+                Some(timeout) => quote! {
+                    self.0.call_with_timeout(#dbus_member_name, #timeout, #body)
+                },
+                None => quote! { self.0.call(#dbus_member_name, #body) },
+            };
             Ok(quote! {
                 #(#other_attrs)*
                 pub #usage #signature {
-                    let reply = self.0.call(#dbus_member_name, #body)#wait?;
+                    let reply = #call #wait?;
                     ::std::result::Result::Ok(reply)
                 }
             })
@@ -708,6 +760,30 @@ fn gen_proxy_method_call(
     }
 }
 
+/// Parse a timeout of the form `<integer><unit>`, where the unit is one of `ms`, `s`, `min` or
+/// `h`, into milliseconds.
+fn parse_timeout(timeout: &str) -> Result<u64, String> {
+    let invalid = || {
+        format!(
+            "invalid timeout `{timeout}`, expected an integer followed by `ms`, `s`, `min` or `h`"
+        )
+    };
+    let unit_start = timeout
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (value, unit) = timeout.split_at(unit_start);
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    let factor = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "min" => 60_000,
+        "h" => 3_600_000,
+        _ => return Err(invalid()),
+    };
+
+    value.checked_mul(factor).ok_or_else(invalid)
+}
+
 fn gen_proxy_property(
     property_name: &str,
     rust_method_name: &str,
@@ -1255,6 +1331,35 @@ fn gen_proxy_signal(
 
         #args_impl
     };
+    // The types of the signal only exist under the same conditions as the signal itself.
+    let cfg_attrs: Vec<_> = other_attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .copied()
+        .collect();
+    let stream_types = with_attrs(stream_types, &cfg_attrs);
 
     (receive_signal, stream_types)
 }
+
+/// Add `attrs` to all the items of `tokens`.
+fn with_attrs(tokens: TokenStream, attrs: &[&Attribute]) -> TokenStream {
+    if attrs.is_empty() {
+        return tokens;
+    }
+    // Leave invalid code to the compiler, to report the errors.
+    let Ok(mut file) = syn::parse2::<syn::File>(tokens.clone()) else {
+        return tokens;
+    };
+
+    for item in &mut file.items {
+        let item_attrs = match item {
+            Item::Struct(s) => &mut s.attrs,
+            Item::Impl(i) => &mut i.attrs,
+            _ => continue,
+        };
+        item_attrs.extend(attrs.iter().map(|a| (*a).clone()));
+    }
+
+    file.into_token_stream()
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Error, ItemTrait, Meta, Token, punctuated::Punctuated};
+use zbus_xml::{
+    Node,
+    codegen::{self, Options},
+};
+use zvariant_utils::def_attrs;
+
+use crate::{
+    proxy,
+    utils::{parse_crate_path, zbus_path},
+};
+
+def_attrs! {
+    crate zbus;
+
+    pub MacroAttributes("macro") {
+        file str,
+        interface str,
+        default_path str,
+        default_service str,
+        crate_path str
+    };
+}
+
+pub fn expand(args: Punctuated<Meta, Token![,]>) -> Result<TokenStream, Error> {
+    let attrs = MacroAttributes::parse_nested_metas(args)?;
+    let call_site = Span::call_site();
+    let file = attrs
+        .file
+        .ok_or_else(|| Error::new(call_site, "`file` attribute is required"))?;
+    let iface_name = attrs
+        .interface
+        .ok_or_else(|| Error::new(call_site, "`interface` attribute is required"))?;
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| Error::new(call_site, "`CARGO_MANIFEST_DIR` is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(&file);
+    let xml = std::fs::read_to_string(&path).map_err(|e| {
+        Error::new(
+            call_site,
+            format!("Failed to read `{}`: {e}", path.display()),
+        )
+    })?;
+    let node = Node::from_reader(xml.as_bytes()).map_err(|e| {
+        Error::new(
+            call_site,
+            format!("Failed to parse `{}`: {e}", path.display()),
+        )
+    })?;
+    let iface = node
+        .interfaces()
+        .iter()
+        .find(|i| i.name() == iface_name.as_str())
+        .ok_or_else(|| {
+            Error::new(
+                call_site,
+                format!("Interface `{iface_name}` not found in `{file}`"),
+            )
+        })?;
+
+    let crate_path = parse_crate_path(attrs.crate_path.as_deref())?;
+    // The type of file descriptors only exists on Unix, whatever the host of the macro is.
+    let options = Options::default()
+        .crate_path(zbus_path(crate_path.as_ref()).to_string())
+        .cfg_unix_fds(true);
+    let mut source = String::new();
+    codegen::write_proxy(
+        &mut source,
+        iface,
+        attrs.default_service.as_deref(),
+        attrs.default_path.as_deref(),
+        &options,
+    )
+    .map_err(|e| {
+        Error::new(
+            call_site,
+            format!("Failed to generate the proxy for `{iface_name}`: {e}"),
+        )
+    })?;
+    let mut item: ItemTrait = syn::parse_str(&source)?;
+
+    // Feed the `proxy` attribute of the generated trait to the `proxy` macro.
+    let proxy_attr = item.attrs.remove(0);
+    let mut proxy_args =
+        proxy_attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+    if let Some(crate_path) = attrs.crate_path {
+        proxy_args.push(syn::parse_quote!(crate = #crate_path));
+    }
+    let proxy = proxy::expand(proxy_args, item)?;
+
+    // Ensure we're rebuilt whenever the file changes.
+    let path = path.to_string_lossy();
+    Ok(quote! {
+        const _: &[u8] = ::std::include_bytes!(#path);
+
+        #proxy
+    })
+}
@@ -15,6 +15,9 @@ mod param {
     trait ProxyParam {
         #[zbus(object = "super::test::Test")]
         fn some_method<T>(&self, test: &T);
+
+        #[zbus(object = "super::test::Test", timeout = "5s")]
+        fn some_method_with_timeout(&self);
     }
 }
 
@@ -43,6 +46,12 @@ mod test {
         /// A call returning an type that only implements DynamicDeserialize
         fn test_dyn_ret(&self) -> zbus::Result<OwnedStructure>;
 
+        #[zbus(timeout = "500ms")]
+        fn with_timeout(&self) -> zbus::Result<()>;
+
+        #[zbus(no_autostart, timeout = "2min")]
+        fn with_flags_and_timeout(&self, val: &str) -> zbus::Result<u32>;
+
         #[zbus(name = "CheckRENAMING")]
         fn check_renaming(&self) -> zbus::Result<Vec<u8>>;
 
@@ -466,7 +475,7 @@ fn test_proxy_object_list() {
     };
 
     fn check_return(list: Vec<ObjectListProxyBlocking<'_>>) {
-        for (correct, returned) in OBJECT_LIST.paths.iter().zip(list.into_iter()) {
+        for (correct, returned) in OBJECT_LIST.paths.iter().zip(list) {
             assert!(returned.inner().path() == correct);
         }
     }
@@ -490,3 +499,90 @@ fn test_proxy_object_list() {
     check_return(proxy.get_test_objects().unwrap());
     check_return(proxy.objects().unwrap());
 }
+
+#[test]
+fn test_proxy_from_xml() {
+    mod calculator {
+        zbus_macros::proxy_from_xml!(
+            file = "tests/data/calculator.xml",
+            interface = "org.freedesktop.zbus_macros.Calculator",
+        );
+    }
+    use calculator::CalculatorProxyBlocking;
+
+    struct Calculator {
+        total: i32,
+        label: String,
+    }
+
+    #[zbus_macros::interface(name = "org.freedesktop.zbus_macros.Calculator")]
+    impl Calculator {
+        fn add(&mut self, a: i32, b: i32) -> i32 {
+            self.total += a + b;
+
+            a + b
+        }
+
+        fn div_mod(&self, a: i32, b: i32) -> ((i32, i32),) {
+            ((a / b, a % b),)
+        }
+
+        fn clear(&mut self) {
+            self.total = 0;
+        }
+
+        #[zbus(signal)]
+        async fn cleared(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn total(&self) -> i32 {
+            self.total
+        }
+
+        #[zbus(property)]
+        fn label(&self) -> String {
+            self.label.clone()
+        }
+
+        #[zbus(property)]
+        fn set_label(&mut self, label: String) {
+            self.label = label;
+        }
+    }
+
+    let connection = zbus::blocking::connection::Builder::session()
+        .unwrap()
+        .serve_at(
+            "/org/freedesktop/zbus_macros/calculator",
+            Calculator {
+                total: 0,
+                label: String::new(),
+            },
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+    let destination = connection.unique_name().unwrap().clone();
+
+    let proxy = CalculatorProxyBlocking::builder(&connection)
+        .path("/org/freedesktop/zbus_macros/calculator")
+        .unwrap()
+        .destination(&destination)
+        .unwrap()
+        .cache_properties(CacheProperties::No)
+        .build()
+        .unwrap();
+
+    assert_eq!(proxy.add(1, 2).unwrap(), 3);
+    assert_eq!(proxy.div_mod(7, 2).unwrap(), ((3, 1),));
+    assert_eq!(proxy.total().unwrap(), 3);
+    proxy.set_label("sum").unwrap();
+    assert_eq!(proxy.label().unwrap(), "sum");
+    proxy.clear().unwrap();
+    assert_eq!(proxy.total().unwrap(), 0);
+    // The signal API is generated too.
+    let _ = proxy.receive_cleared();
+    // Members involving file descriptors only exist on Unix.
+    #[cfg(unix)]
+    let _ = proxy.receive_exported();
+}
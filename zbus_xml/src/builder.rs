@@ -0,0 +1,276 @@
+//! Builders for the introspection types, to create introspection data programmatically.
+
+use zbus_names::{InterfaceName, MemberName, PropertyName};
+
+use crate::{
+    Annotation, Arg, ArgDirection, Error, Interface, Method, Node, Property, PropertyAccess,
+    Result, Signal, Signature,
+};
+
+impl Annotation {
+    /// Create a new annotation.
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+fn signature<S>(ty: S) -> Result<Signature>
+where
+    S: TryInto<zvariant::Signature>,
+    S::Error: Into<Error>,
+{
+    ty.try_into().map(Signature).map_err(Into::into)
+}
+
+impl Arg {
+    /// Create a builder for an argument of type `ty`.
+    pub fn builder<S>(ty: S) -> Result<ArgBuilder>
+    where
+        S: TryInto<zvariant::Signature>,
+        S::Error: Into<Error>,
+    {
+        Ok(ArgBuilder(Arg {
+            name: None,
+            ty: signature(ty)?,
+            direction: None,
+            annotations: vec![],
+        }))
+    }
+}
+
+/// A builder for [`Arg`].
+#[derive(Debug, Clone)]
+pub struct ArgBuilder(Arg);
+
+impl ArgBuilder {
+    /// The argument name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.0.name = Some(name.into());
+        self
+    }
+
+    /// The argument direction.
+    pub fn direction(mut self, direction: ArgDirection) -> Self {
+        self.0.direction = Some(direction);
+        self
+    }
+
+    /// Add an annotation.
+    pub fn annotation(mut self, annotation: Annotation) -> Self {
+        self.0.annotations.push(annotation);
+        self
+    }
+
+    /// Build the argument.
+    pub fn build(self) -> Arg {
+        self.0
+    }
+}
+
+impl<'a> Method<'a> {
+    /// Create a builder for a method named `name`.
+    pub fn builder<N>(name: N) -> Result<MethodBuilder<'a>>
+    where
+        N: TryInto<MemberName<'a>>,
+        N::Error: Into<Error>,
+    {
+        Ok(MethodBuilder(Method {
+            name: name.try_into().map_err(Into::into)?,
+            args: vec![],
+            annotations: vec![],
+        }))
+    }
+}
+
+/// A builder for [`Method`].
+#[derive(Debug, Clone)]
+pub struct MethodBuilder<'a>(Method<'a>);
+
+impl<'a> MethodBuilder<'a> {
+    /// Add an argument.
+    pub fn arg(mut self, arg: Arg) -> Self {
+        self.0.args.push(arg);
+        self
+    }
+
+    /// Add an annotation.
+    pub fn annotation(mut self, annotation: Annotation) -> Self {
+        self.0.annotations.push(annotation);
+        self
+    }
+
+    /// Build the method.
+    pub fn build(self) -> Method<'a> {
+        self.0
+    }
+}
+
+impl<'a> Signal<'a> {
+    /// Create a builder for a signal named `name`.
+    pub fn builder<N>(name: N) -> Result<SignalBuilder<'a>>
+    where
+        N: TryInto<MemberName<'a>>,
+        N::Error: Into<Error>,
+    {
+        Ok(SignalBuilder(Signal {
+            name: name.try_into().map_err(Into::into)?,
+            args: vec![],
+            annotations: vec![],
+        }))
+    }
+}
+
+/// A builder for [`Signal`].
+#[derive(Debug, Clone)]
+pub struct SignalBuilder<'a>(Signal<'a>);
+
+impl<'a> SignalBuilder<'a> {
+    /// Add an argument.
+    pub fn arg(mut self, arg: Arg) -> Self {
+        self.0.args.push(arg);
+        self
+    }
+
+    /// Add an annotation.
+    pub fn annotation(mut self, annotation: Annotation) -> Self {
+        self.0.annotations.push(annotation);
+        self
+    }
+
+    /// Build the signal.
+    pub fn build(self) -> Signal<'a> {
+        self.0
+    }
+}
+
+impl<'a> Property<'a> {
+    /// Create a builder for a property named `name`, of type `ty`.
+    pub fn builder<N, S>(name: N, ty: S, access: PropertyAccess) -> Result<PropertyBuilder<'a>>
+    where
+        N: TryInto<PropertyName<'a>>,
+        N::Error: Into<Error>,
+        S: TryInto<zvariant::Signature>,
+        S::Error: Into<Error>,
+    {
+        Ok(PropertyBuilder(Property {
+            name: name.try_into().map_err(Into::into)?,
+            ty: signature(ty)?,
+            access,
+            annotations: vec![],
+        }))
+    }
+}
+
+/// A builder for [`Property`].
+#[derive(Debug, Clone)]
+pub struct PropertyBuilder<'a>(Property<'a>);
+
+impl<'a> PropertyBuilder<'a> {
+    /// Add an annotation.
+    pub fn annotation(mut self, annotation: Annotation) -> Self {
+        self.0.annotations.push(annotation);
+        self
+    }
+
+    /// Build the property.
+    pub fn build(self) -> Property<'a> {
+        self.0
+    }
+}
+
+impl<'a> Interface<'a> {
+    /// Create a builder for an interface named `name`.
+    pub fn builder<N>(name: N) -> Result<InterfaceBuilder<'a>>
+    where
+        N: TryInto<InterfaceName<'a>>,
+        N::Error: Into<Error>,
+    {
+        Ok(InterfaceBuilder(Interface {
+            name: name.try_into().map_err(Into::into)?,
+            methods: vec![],
+            properties: vec![],
+            signals: vec![],
+            annotations: vec![],
+        }))
+    }
+}
+
+/// A builder for [`Interface`].
+#[derive(Debug, Clone)]
+pub struct InterfaceBuilder<'a>(Interface<'a>);
+
+impl<'a> InterfaceBuilder<'a> {
+    /// Add a method.
+    pub fn method(mut self, method: Method<'a>) -> Self {
+        self.0.methods.push(method);
+        self
+    }
+
+    /// Add a signal.
+    pub fn signal(mut self, signal: Signal<'a>) -> Self {
+        self.0.signals.push(signal);
+        self
+    }
+
+    /// Add a property.
+    pub fn property(mut self, property: Property<'a>) -> Self {
+        self.0.properties.push(property);
+        self
+    }
+
+    /// Add an annotation.
+    pub fn annotation(mut self, annotation: Annotation) -> Self {
+        self.0.annotations.push(annotation);
+        self
+    }
+
+    /// Build the interface.
+    pub fn build(self) -> Interface<'a> {
+        self.0
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Create a builder for a node.
+    pub fn builder() -> NodeBuilder<'a> {
+        NodeBuilder(Node {
+            name: None,
+            interfaces: vec![],
+            nodes: vec![],
+        })
+    }
+}
+
+/// A builder for [`Node`].
+#[derive(Debug, Clone)]
+pub struct NodeBuilder<'a>(Node<'a>);
+
+impl<'a> NodeBuilder<'a> {
+    /// The node name.
+    ///
+    /// This is typically the path of a child node, relative to its parent.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.0.name = Some(name.into());
+        self
+    }
+
+    /// Add an interface.
+    pub fn interface(mut self, interface: Interface<'a>) -> Self {
+        self.0.interfaces.push(interface);
+        self
+    }
+
+    /// Add a child node.
+    pub fn node(mut self, node: Node<'a>) -> Self {
+        self.0.nodes.push(node);
+        self
+    }
+
+    /// Build the node.
+    pub fn build(self) -> Node<'a> {
+        self.0
+    }
+}
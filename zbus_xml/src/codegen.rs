@@ -0,0 +1,826 @@
+//! Rust code generation for D-Bus interfaces.
+//!
+//! [`write_proxy`] generates a `#[proxy]` trait and [`write_interface`] a struct with an
+//! `#[interface]` implementation skeleton for an [`Interface`]. This is what both `zbus-xmlgen` and
+//! the `proxy_from_xml` macro of zbus use, so that they generate the same code.
+//!
+//! This module is only available with the `codegen` feature.
+
+use snakecase::ascii::to_snakecase;
+use std::fmt::{self, Write};
+use zvariant::Signature;
+
+use crate::{Annotation, Arg, ArgDirection, Interface, Method, Property, Signal};
+
+/// The annotation to name the struct type generated for an argument or a property, when named
+/// structs are enabled.
+pub const STRUCT_NAME_ANNOTATION: &str = "org.zbus.StructName";
+
+/// Options for the generated code.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Options {
+    /// The path of the zbus crate in the generated code, `zbus` by default.
+    pub crate_path: String,
+    /// Generate named structs for struct signatures, instead of tuples.
+    ///
+    /// The structs are named after the [`STRUCT_NAME_ANNOTATION`] annotation of the argument or
+    /// property, if any, or after its name otherwise.
+    pub named_structs: bool,
+    /// Only generate the proxy members involving file descriptors for Unix targets.
+    ///
+    /// File descriptors can only be passed on Unix, so the types for them don't exist on other
+    /// targets.
+    pub cfg_unix_fds: bool,
+}
+
+impl Options {
+    /// Set the path of the zbus crate in the generated code.
+    pub fn crate_path(mut self, crate_path: impl Into<String>) -> Self {
+        self.crate_path = crate_path.into();
+
+        self
+    }
+
+    /// Generate named structs for struct signatures, instead of tuples.
+    pub fn named_structs(mut self, named_structs: bool) -> Self {
+        self.named_structs = named_structs;
+
+        self
+    }
+
+    /// Only generate the proxy members involving file descriptors for Unix targets.
+    pub fn cfg_unix_fds(mut self, cfg_unix_fds: bool) -> Self {
+        self.cfg_unix_fds = cfg_unix_fds;
+
+        self
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            crate_path: "zbus".to_string(),
+            named_structs: false,
+            cfg_unix_fds: false,
+        }
+    }
+}
+
+/// The error type of the code generation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// There is no Rust type for the signature.
+    UnsupportedSignature(String),
+    /// Writing the generated code failed.
+    Fmt(fmt::Error),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::UnsupportedSignature(_) => None,
+            Error::Fmt(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnsupportedSignature(s) => write!(f, "Unsupported signature: `{s}`"),
+            Error::Fmt(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(val: fmt::Error) -> Self {
+        Error::Fmt(val)
+    }
+}
+
+/// Alias for a `Result` with the error type [`codegen::Error`](Error).
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Write a `#[proxy]` trait for `iface`.
+///
+/// Unless both `service` and `path` are given, the proxy assumes defaults for them.
+pub fn write_proxy<W: Write>(
+    w: &mut W,
+    iface: &Interface<'_>,
+    service: Option<&str>,
+    path: Option<&str>,
+    options: &Options,
+) -> Result<()> {
+    let zbus = options.crate_path.as_str();
+    let idx = iface.name().rfind('.').unwrap() + 1;
+    let name = &iface.name()[idx..];
+
+    write!(w, "#[proxy(interface = \"{}\"", iface.name())?;
+    if let Some(service) = service {
+        write!(w, ", default_service = \"{service}\"")?;
+    }
+    if let Some(path) = path {
+        write!(w, ", default_path = \"{path}\"")?;
+    }
+    if path.is_none() || service.is_none() {
+        write!(w, ", assume_defaults = true")?;
+    }
+    writeln!(w, ")]")?;
+    writeln!(w, "pub trait {name} {{")?;
+
+    let mut types = Types::new(options);
+
+    let mut methods = iface.methods().to_vec();
+    methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+    for m in &methods {
+        let (inputs, output) = types.inputs_output_from_args(m)?;
+        let name = to_identifier(&to_snakecase(m.name().as_str()));
+        writeln!(w)?;
+        writeln!(w, "    /// {} method", m.name())?;
+        cfg_unix_fds(w, options, m.args().iter().map(|a| a.ty().inner()))?;
+        if pascal_case(&name) != m.name().as_str() {
+            writeln!(w, "    #[zbus(name = \"{}\")]", m.name())?;
+        }
+        hide_clippy_lints(w, m)?;
+        writeln!(w, "    fn {name}({inputs}){output};")?;
+    }
+
+    let mut signals = iface.signals().to_vec();
+    signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+    for signal in &signals {
+        let args = types.signal_args("&self", signal)?;
+        let name = to_identifier(&to_snakecase(signal.name().as_str()));
+        writeln!(w)?;
+        writeln!(w, "    /// {} signal", signal.name())?;
+        cfg_unix_fds(w, options, signal.args().iter().map(|a| a.ty().inner()))?;
+        if pascal_case(&name) != signal.name().as_str() {
+            writeln!(w, "    #[zbus(signal, name = \"{}\")]", signal.name())?;
+        } else {
+            writeln!(w, "    #[zbus(signal)]")?;
+        }
+        writeln!(w, "    fn {name}({args}) -> {zbus}::Result<()>;",)?;
+    }
+
+    let mut props = iface.properties().to_vec();
+    props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+    for p in props {
+        let name = to_identifier(&to_snakecase(p.name().as_str()));
+        let fn_attribute = if pascal_case(&name) != p.name().as_str() {
+            format!("    #[zbus(property, name = \"{}\")]", p.name())
+        } else {
+            "    #[zbus(property)]".to_string()
+        };
+
+        writeln!(w)?;
+        writeln!(w, "    /// {} property", p.name())?;
+        if p.access().read() {
+            cfg_unix_fds(w, options, [p.ty().inner()])?;
+            writeln!(w, "{fn_attribute}")?;
+            let output = types.property_type(&p, false, false)?;
+            hide_clippy_type_complexity_lint(w, p.ty())?;
+            writeln!(w, "    fn {name}(&self) -> {zbus}::Result<{output}>;",)?;
+        }
+
+        if p.access().write() {
+            cfg_unix_fds(w, options, [p.ty().inner()])?;
+            writeln!(w, "{fn_attribute}")?;
+            let input = types.property_type(&p, true, true)?;
+            writeln!(
+                w,
+                "    fn set_{name}(&self, value: {input}) -> {zbus}::Result<()>;",
+            )?;
+        }
+    }
+    writeln!(w, "}}")?;
+
+    types.write_structs(w)
+}
+
+/// Write a struct and an `#[interface]` implementation skeleton for `iface`.
+///
+/// All methods and property accessors have `todo!()` bodies.
+pub fn write_interface<W: Write>(
+    w: &mut W,
+    iface: &Interface<'_>,
+    options: &Options,
+) -> Result<()> {
+    let zbus = options.crate_path.as_str();
+    let idx = iface.name().rfind('.').unwrap() + 1;
+    let name = &iface.name()[idx..];
+
+    writeln!(w, "pub struct {name};")?;
+    writeln!(w)?;
+    writeln!(w, "#[interface(name = \"{}\")]", iface.name())?;
+    writeln!(w, "impl {name} {{")?;
+
+    let mut types = Types::new(options);
+
+    let mut methods = iface.methods().to_vec();
+    methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+    for m in &methods {
+        let inputs = types.server_method_inputs(m)?;
+        let output = types.output_type(m)?;
+        let name = to_identifier(&to_snakecase(m.name().as_str()));
+        let mut attrs = vec![];
+        if pascal_case(&name) != m.name().as_str() {
+            attrs.push(format!("name = \"{}\"", m.name()));
+        }
+        let out_args: Option<Vec<_>> = m
+            .args()
+            .iter()
+            .filter(|a| a.direction() == Some(ArgDirection::Out))
+            .map(|a| a.name().map(|name| format!("\"{name}\"")))
+            .collect();
+        if let Some(out_args) = out_args.filter(|a| !a.is_empty()) {
+            attrs.push(format!("out_args({})", out_args.join(", ")));
+        }
+
+        writeln!(w)?;
+        writeln!(w, "    /// {} method", m.name())?;
+        if !attrs.is_empty() {
+            writeln!(w, "    #[zbus({})]", attrs.join(", "))?;
+        }
+        hide_clippy_lints(w, m)?;
+        writeln!(
+            w,
+            "    fn {name}({inputs}) -> {zbus}::fdo::Result<{output}> {{ todo!() }}"
+        )?;
+    }
+
+    let mut signals = iface.signals().to_vec();
+    signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+    for signal in &signals {
+        let receiver = format!("emitter: &{zbus}::object_server::SignalEmitter<'_>");
+        let args = types.signal_args(&receiver, signal)?;
+        let name = to_identifier(&to_snakecase(signal.name().as_str()));
+        writeln!(w)?;
+        writeln!(w, "    /// {} signal", signal.name())?;
+        if pascal_case(&name) != signal.name().as_str() {
+            writeln!(w, "    #[zbus(signal, name = \"{}\")]", signal.name())?;
+        } else {
+            writeln!(w, "    #[zbus(signal)]")?;
+        }
+        writeln!(w, "    async fn {name}({args}) -> {zbus}::Result<()>;")?;
+    }
+
+    let mut props = iface.properties().to_vec();
+    props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+    for p in props {
+        let name = to_identifier(&to_snakecase(p.name().as_str()));
+        let name_attr = if pascal_case(&name) != p.name().as_str() {
+            format!(", name = \"{}\"", p.name())
+        } else {
+            String::new()
+        };
+        let ty = types.property_type(&p, false, false)?;
+
+        writeln!(w)?;
+        writeln!(w, "    /// {} property", p.name())?;
+        if p.access().read() {
+            let emits_changed_signal = p
+                .annotations()
+                .iter()
+                .find(|a| a.name() == "org.freedesktop.DBus.Property.EmitsChangedSignal")
+                .map(|a| a.value())
+                .filter(|value| *value != "true");
+            match emits_changed_signal {
+                Some(value) => writeln!(
+                    w,
+                    "    #[zbus(property(emits_changed_signal = \"{value}\"){name_attr})]"
+                )?,
+                None => writeln!(w, "    #[zbus(property{name_attr})]")?,
+            }
+            hide_clippy_type_complexity_lint(w, p.ty())?;
+            writeln!(
+                w,
+                "    fn {name}(&self) -> {zbus}::fdo::Result<{ty}> {{ todo!() }}"
+            )?;
+        }
+
+        if p.access().write() {
+            writeln!(w, "    #[zbus(property{name_attr})]")?;
+            hide_clippy_type_complexity_lint(w, p.ty())?;
+            writeln!(
+                w,
+                "    fn set_{name}(&mut self, value: {ty}) -> {zbus}::fdo::Result<()> {{ todo!() }}"
+            )?;
+        }
+    }
+    writeln!(w, "}}")?;
+
+    types.write_structs(w)
+}
+
+/// Convert a `snake_case` identifier to `PascalCase`.
+// This function is the same as zbus_macros::utils::pascal_case
+pub fn pascal_case(s: &str) -> String {
+    let mut pascal = String::new();
+    let mut capitalize = true;
+    for ch in s.chars() {
+        if ch == '_' {
+            capitalize = true;
+        } else if capitalize {
+            pascal.push(ch.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            pascal.push(ch);
+        }
+    }
+    pascal
+}
+
+/// Only generate the following member for Unix targets, if it involves file descriptors.
+fn cfg_unix_fds<'s, W: Write>(
+    w: &mut W,
+    options: &Options,
+    signatures: impl IntoIterator<Item = &'s Signature>,
+) -> fmt::Result {
+    if options.cfg_unix_fds && signatures.into_iter().any(has_fd) {
+        writeln!(w, "    #[cfg(unix)]")?;
+    }
+
+    Ok(())
+}
+
+fn hide_clippy_lints<W: Write>(write: &mut W, method: &Method<'_>) -> fmt::Result {
+    // check for <https://rust-lang.github.io/rust-clippy/master/index.html#/too_many_arguments>
+    // triggers when a functions has at least 7 paramters
+    if method.args().len() >= 7 {
+        writeln!(write, "    #[allow(clippy::too_many_arguments)]")?;
+    }
+
+    // check for <https://rust-lang.github.io/rust-clippy/master/index.html#/type_complexity>
+    for arg in method.args() {
+        let signature = arg.ty();
+        hide_clippy_type_complexity_lint(write, signature)?;
+    }
+
+    Ok(())
+}
+
+fn hide_clippy_type_complexity_lint<W: Write>(write: &mut W, signature: &Signature) -> fmt::Result {
+    let complexity = estimate_type_complexity(signature);
+    if complexity >= 1700 {
+        writeln!(write, "    #[allow(clippy::type_complexity)]")?;
+    }
+    Ok(())
+}
+
+/// The Rust types of the generated code.
+struct Types<'o> {
+    zbus: &'o str,
+    /// The named structs, if enabled.
+    structs: Option<NamedStructs>,
+}
+
+impl<'o> Types<'o> {
+    fn new(options: &'o Options) -> Self {
+        Self {
+            zbus: &options.crate_path,
+            structs: options.named_structs.then(NamedStructs::default),
+        }
+    }
+
+    fn inputs_output_from_args(&mut self, method: &Method<'_>) -> Result<(String, String)> {
+        let mut inputs = vec!["&self".to_string()];
+        let mut n = 0;
+        let mut gen_name = || {
+            n += 1;
+            format!("arg_{n}")
+        };
+
+        for (i, a) in method.args().iter().enumerate() {
+            if let None | Some(ArgDirection::In) = a.direction() {
+                let ty = self.arg_type(&method.name(), i, a, true, true)?;
+                let arg = if let Some(name) = a.name() {
+                    to_identifier(name)
+                } else {
+                    gen_name()
+                };
+                inputs.push(format!("{arg}: {ty}"));
+            }
+        }
+
+        let output = self.output_type(method)?;
+
+        Ok((
+            inputs.join(", "),
+            format!(" -> {}::Result<{output}>", self.zbus),
+        ))
+    }
+
+    /// The Rust type for the output arguments of `method`.
+    fn output_type(&mut self, method: &Method<'_>) -> Result<String> {
+        let mut output = vec![];
+        for (i, a) in method.args().iter().enumerate() {
+            if a.direction() != Some(ArgDirection::Out) {
+                continue;
+            }
+
+            output.push(OutputArg {
+                ty: self.arg_type(&method.name(), i, a, false, false)?,
+                // Named structs are not tuples, so they don't need any special treatment.
+                is_struct: self.structs.is_none()
+                    && matches!(a.ty().inner(), Signature::Structure(_)),
+            });
+        }
+
+        let ty = match &output[..] {
+            [] => "()".to_string(),
+            [
+                OutputArg {
+                    ty,
+                    is_struct: true,
+                },
+            ] => {
+                // If there's a single output argument and it is a struct type, we need to wrap it
+                // in a tuple to distinguish it from multiple return values
+                format!("({ty},)")
+            }
+            [
+                OutputArg {
+                    ty,
+                    is_struct: false,
+                },
+            ] => ty.clone(),
+            multiple => {
+                let types = multiple
+                    .iter()
+                    .map(|arg| arg.ty.as_str())
+                    .collect::<Vec<_>>();
+                format!("({})", types.join(", "))
+            }
+        };
+
+        Ok(ty)
+    }
+
+    /// The input arguments of an `#[interface]` method, as owned types.
+    fn server_method_inputs(&mut self, method: &Method<'_>) -> Result<String> {
+        let mut inputs = vec!["&self".to_string()];
+        let mut n = 0;
+        let mut gen_name = || {
+            n += 1;
+            format!("arg_{n}")
+        };
+
+        for (i, a) in method.args().iter().enumerate() {
+            if let None | Some(ArgDirection::In) = a.direction() {
+                let ty = self.arg_type(&method.name(), i, a, false, false)?;
+                let arg = if let Some(name) = a.name() {
+                    to_identifier(name)
+                } else {
+                    gen_name()
+                };
+                inputs.push(format!("{arg}: {ty}"));
+            }
+        }
+
+        Ok(inputs.join(", "))
+    }
+
+    fn signal_args(&mut self, receiver: &str, signal: &Signal<'_>) -> Result<String> {
+        let mut inputs = vec![receiver.to_string()];
+        let mut n = 0;
+        let mut gen_name = || {
+            n += 1;
+            format!("arg_{n}")
+        };
+
+        for (i, a) in signal.args().iter().enumerate() {
+            let ty = self.arg_type(&signal.name(), i, a, true, false)?;
+            let arg = if let Some(name) = a.name() {
+                to_identifier(name)
+            } else {
+                gen_name()
+            };
+            inputs.push(format!("{arg}: {ty}"));
+        }
+
+        Ok(inputs.join(", "))
+    }
+
+    /// The Rust type of the `n`th argument of `member`.
+    fn arg_type(
+        &mut self,
+        member: &str,
+        n: usize,
+        arg: &Arg,
+        input: bool,
+        as_ref: bool,
+    ) -> Result<String> {
+        match &mut self.structs {
+            Some(structs) => {
+                let name = struct_name(member, arg.name(), arg.annotations(), n);
+
+                rust_type(self.zbus, arg.ty(), input, as_ref, Some((structs, &name)))
+            }
+            None => rust_type(self.zbus, arg.ty(), input, as_ref, None),
+        }
+    }
+
+    /// The Rust type of `property`.
+    fn property_type(
+        &mut self,
+        property: &Property<'_>,
+        input: bool,
+        as_ref: bool,
+    ) -> Result<String> {
+        match &mut self.structs {
+            Some(structs) => {
+                let member = property.name();
+                let name = struct_name(&member, Some(&member), property.annotations(), 0);
+                // Property values are converted into a `Value`, which requires owned structs.
+                let as_ref = as_ref && !matches!(property.ty().inner(), Signature::Structure(_));
+
+                rust_type(
+                    self.zbus,
+                    property.ty(),
+                    input,
+                    as_ref,
+                    Some((structs, &name)),
+                )
+            }
+            None => rust_type(self.zbus, property.ty(), input, as_ref, None),
+        }
+    }
+
+    fn write_structs<W: Write>(&self, w: &mut W) -> Result<()> {
+        match &self.structs {
+            Some(structs) => Ok(structs.write(w, self.zbus)?),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OutputArg {
+    ty: String,
+    is_struct: bool,
+}
+
+/// Type names that generated structs must not shadow.
+static RESERVED_TYPE_NAMES: &[&str] = &[
+    "Box", "HashMap", "Option", "Result", "Self", "String", "Vec",
+];
+
+/// The name of the struct generated for the argument (or property) `name` of `member`.
+///
+/// The [`STRUCT_NAME_ANNOTATION`] annotation takes precedence, if present. Unnamed arguments are
+/// named after their position.
+fn struct_name(member: &str, name: Option<&str>, annotations: &[Annotation], n: usize) -> String {
+    if let Some(annotation) = annotations
+        .iter()
+        .find(|a| a.name() == STRUCT_NAME_ANNOTATION)
+    {
+        return annotation.value().to_string();
+    }
+
+    let name = match name {
+        Some(name) => pascal_case(&to_snakecase(name).replace('-', "_")),
+        None => format!("{member}Arg{n}"),
+    };
+    if RESERVED_TYPE_NAMES.contains(&name.as_str()) {
+        format!("{member}{name}")
+    } else {
+        name
+    }
+}
+
+/// The named structs generated for the struct signatures of an interface.
+#[derive(Debug, Default)]
+struct NamedStructs(Vec<NamedStruct>);
+
+#[derive(Debug)]
+struct NamedStruct {
+    name: String,
+    signature: Signature,
+    fields: Vec<String>,
+}
+
+impl NamedStructs {
+    /// The name of the struct for `signature`, generating it under `name` if needed.
+    ///
+    /// If a different struct was already generated under `name`, a numeric suffix is appended.
+    fn get_or_insert(&mut self, zbus: &str, name: &str, signature: &Signature) -> Result<String> {
+        let mut unique_name = name.to_string();
+        let mut n = 1;
+        while let Some(s) = self.0.iter().find(|s| s.name == unique_name) {
+            if s.signature == *signature {
+                return Ok(unique_name);
+            }
+            n += 1;
+            unique_name = format!("{name}{n}");
+        }
+
+        let Signature::Structure(fields) = signature else {
+            return Err(Error::UnsupportedSignature(signature.to_string()));
+        };
+        // Register the struct before its fields, as these may contain structs too.
+        let idx = self.0.len();
+        self.0.push(NamedStruct {
+            name: unique_name.clone(),
+            signature: signature.clone(),
+            fields: vec![],
+        });
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let field_name = format!("{unique_name}Field{i}");
+
+                rust_type(zbus, f, false, false, Some((self, &field_name)))
+            })
+            .collect::<Result<_>>()?;
+        self.0[idx].fields = fields;
+
+        Ok(unique_name)
+    }
+
+    fn write<W: Write>(&self, w: &mut W, zbus: &str) -> fmt::Result {
+        for s in &self.0 {
+            writeln!(w)?;
+            writeln!(w, "/// D-Bus struct with signature `{}`.", s.signature)?;
+            // File descriptors can't be cloned.
+            let clone = if s.signature.to_string().contains('h') {
+                ""
+            } else {
+                "Clone, "
+            };
+            writeln!(
+                w,
+                "#[derive({clone}Debug, serde::Deserialize, serde::Serialize, {zbus}::zvariant::Type, \
+                 {zbus}::zvariant::Value, {zbus}::zvariant::OwnedValue)]"
+            )?;
+            writeln!(w, "pub struct {} {{", s.name)?;
+            for (i, field) in s.fields.iter().enumerate() {
+                writeln!(w, "    pub field_{i}: {field},")?;
+            }
+            writeln!(w, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The Rust type for `signature`.
+///
+/// If `structs` is set, struct types are named after the given name, instead of being tuples.
+fn rust_type(
+    zbus: &str,
+    signature: &Signature,
+    input: bool,
+    as_ref: bool,
+    structs: Option<(&mut NamedStructs, &str)>,
+) -> Result<String> {
+    let ty = match signature {
+        Signature::Unit => "".into(),
+        Signature::U8 => "u8".into(),
+        Signature::Bool => "bool".into(),
+        Signature::I16 => "i16".into(),
+        Signature::U16 => "u16".into(),
+        Signature::I32 => "i32".into(),
+        Signature::U32 => "u32".into(),
+        Signature::I64 => "i64".into(),
+        Signature::U64 => "u64".into(),
+        Signature::F64 => "f64".into(),
+        #[cfg(unix)]
+        Signature::Fd if input => format!("{zbus}::zvariant::Fd<'_>"),
+        #[cfg(unix)]
+        Signature::Fd => format!("{zbus}::zvariant::OwnedFd"),
+        Signature::Str if input || as_ref => "&str".into(),
+        Signature::Str => "String".into(),
+        Signature::ObjectPath if input => {
+            if as_ref {
+                format!("&{zbus}::zvariant::ObjectPath<'_>")
+            } else {
+                format!("{zbus}::zvariant::ObjectPath<'_>")
+            }
+        }
+        Signature::ObjectPath => format!("{zbus}::zvariant::OwnedObjectPath"),
+        Signature::Signature if input => {
+            if as_ref {
+                format!("&{zbus}::zvariant::Signature<'_>")
+            } else {
+                format!("{zbus}::zvariant::Signature<'_>")
+            }
+        }
+        Signature::Signature => format!("{zbus}::zvariant::OwnedSignature"),
+        Signature::Variant if input => {
+            if as_ref {
+                format!("&{zbus}::zvariant::Value<'_>")
+            } else {
+                format!("{zbus}::zvariant::Value<'_>")
+            }
+        }
+        Signature::Variant => format!("{zbus}::zvariant::OwnedValue"),
+        Signature::Array(child) => {
+            // Slices of named structs don't need references.
+            let child_as_ref =
+                as_ref && (structs.is_none() || !matches!(**child, Signature::Structure(_)));
+            let child_ty = rust_type(zbus, child, input, child_as_ref, structs)?;
+            if input && as_ref {
+                format!("&[{child_ty}]")
+            } else {
+                format!("Vec<{child_ty}>")
+            }
+        }
+        Signature::Dict { key, value } => {
+            let key_ty = rust_type(zbus, key, input, as_ref, None)?;
+            let value_ty = rust_type(zbus, value, input, as_ref, structs)?;
+
+            format!("std::collections::HashMap<{key_ty}, {value_ty}>")
+        }
+        Signature::Structure(fields) => {
+            if let Some((structs, name)) = structs {
+                let name = structs.get_or_insert(zbus, name, signature)?;
+
+                return Ok(format!("{}{name}", if as_ref { "&" } else { "" }));
+            }
+
+            let fields = fields
+                .iter()
+                .map(|f| rust_type(zbus, f, input, as_ref, None))
+                .collect::<Result<Vec<_>>>()?;
+
+            if fields.len() > 1 {
+                format!("{}({})", if as_ref { "&" } else { "" }, fields.join(", "))
+            } else {
+                format!("{}({},)", if as_ref { "&" } else { "" }, fields[0])
+            }
+        }
+        #[allow(unreachable_patterns)]
+        _ => return Err(Error::UnsupportedSignature(signature.to_string())),
+    };
+
+    Ok(ty)
+}
+
+/// If `signature` contains file descriptors.
+fn has_fd(signature: &Signature) -> bool {
+    match signature {
+        #[cfg(unix)]
+        Signature::Fd => true,
+        Signature::Array(child) => has_fd(child),
+        Signature::Dict { key, value } => has_fd(key) || has_fd(value),
+        Signature::Structure(fields) => fields.iter().any(has_fd),
+        _ => false,
+    }
+}
+
+static KWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "union", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+fn to_identifier(id: &str) -> String {
+    if KWORDS.contains(&id) {
+        format!("{id}_")
+    } else {
+        id.replace('-', "_")
+    }
+}
+
+fn estimate_type_complexity(signature: &Signature) -> u32 {
+    let mut score = 0;
+
+    match signature {
+        Signature::Unit => (),
+        Signature::U8
+        | Signature::Bool
+        | Signature::I16
+        | Signature::U16
+        | Signature::I32
+        | Signature::U32
+        | Signature::I64
+        | Signature::U64
+        | Signature::F64
+        | Signature::Str => score += 1,
+        #[cfg(unix)]
+        Signature::Fd => score += 10,
+        Signature::ObjectPath | Signature::Signature | Signature::Variant => score += 10,
+        Signature::Array(child) => score += 5 * estimate_type_complexity(child),
+        Signature::Dict { key, value } => {
+            score *= 10 + 50;
+            score += 5 * estimate_type_complexity(key);
+            score += 5 * estimate_type_complexity(value);
+        }
+        Signature::Structure(fields) => {
+            score += 50;
+            for field in fields.iter() {
+                score += 5 * estimate_type_complexity(field);
+            }
+        }
+        // Unsupported signatures are rejected by `rust_type`.
+        #[allow(unreachable_patterns)]
+        _ => (),
+    }
+
+    score
+}
@@ -0,0 +1,351 @@
+//! Comparison of D-Bus interface versions, for API compatibility checks.
+//!
+//! [`diff_nodes`] and [`diff_interfaces`] list the [`Change`]s between two versions of the same
+//! interfaces, each of which is either [`Compatibility::Additive`] (existing clients are not
+//! affected) or [`Compatibility::Breaking`].
+//!
+//! ```
+//! use zbus_xml::{
+//!     Node,
+//!     diff::{Change, Compatibility, Member, diff_nodes},
+//! };
+//!
+//! let old = Node::try_from(
+//!     r#"<node><interface name="org.zbus.Greeter1">
+//!       <method name="SayHello"><arg type="s" direction="in"/></method>
+//!       <property name="Greeting" type="s" access="readwrite"/>
+//!     </interface></node>"#,
+//! )?;
+//! let new = Node::try_from(
+//!     r#"<node><interface name="org.zbus.Greeter1">
+//!       <method name="SayHello"><arg type="s" direction="in"/></method>
+//!       <method name="SayBye"/>
+//!       <property name="Greeting" type="s" access="read"/>
+//!     </interface></node>"#,
+//! )?;
+//!
+//! let changes = diff_nodes(&old, &new);
+//! assert_eq!(changes.len(), 2);
+//! assert_eq!(
+//!     changes[0],
+//!     Change::MemberAdded {
+//!         interface: "org.zbus.Greeter1".into(),
+//!         member: Member::Method("SayBye".into()),
+//!     },
+//! );
+//! assert_eq!(changes[0].compatibility(), Compatibility::Additive);
+//! assert_eq!(changes[1].compatibility(), Compatibility::Breaking);
+//! # Ok::<(), zbus_xml::Error>(())
+//! ```
+
+use std::{collections::BTreeMap, fmt};
+
+use zvariant::Signature;
+
+use crate::{Arg, ArgDirection, Interface, Node, PropertyAccess};
+
+/// Whether a [`Change`] affects existing clients of an interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Compatibility {
+    /// The change only extends the interface. Existing clients are not affected.
+    Additive,
+    /// The change can break existing clients.
+    Breaking,
+}
+
+/// An interface, or a member of it.
+///
+/// [`Change`]s are always about members. The interface itself is there for other comparisons of
+/// interfaces, e.g. of their annotations.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Member {
+    Interface(String),
+    Method(String),
+    Signal(String),
+    Property(String),
+}
+
+impl fmt::Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Member::Interface(name) => write!(f, "interface `{name}`"),
+            Member::Method(name) => write!(f, "method `{name}`"),
+            Member::Signal(name) => write!(f, "signal `{name}`"),
+            Member::Property(name) => write!(f, "property `{name}`"),
+        }
+    }
+}
+
+/// A change between two versions of an interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Change {
+    /// A new interface.
+    InterfaceAdded(String),
+    /// An interface was removed.
+    InterfaceRemoved(String),
+    /// A new member.
+    MemberAdded { interface: String, member: Member },
+    /// A member was removed.
+    MemberRemoved { interface: String, member: Member },
+    /// The arguments of a method or a signal, in the given direction, changed.
+    ///
+    /// Signal arguments are always [`ArgDirection::Out`].
+    ArgsChanged {
+        interface: String,
+        member: Member,
+        direction: ArgDirection,
+        old: Vec<Signature>,
+        new: Vec<Signature>,
+    },
+    /// The type of a property changed.
+    PropertyTypeChanged {
+        interface: String,
+        property: String,
+        old: Signature,
+        new: Signature,
+    },
+    /// The access of a property changed.
+    PropertyAccessChanged {
+        interface: String,
+        property: String,
+        old: PropertyAccess,
+        new: PropertyAccess,
+    },
+}
+
+impl Change {
+    /// Whether this change affects existing clients.
+    ///
+    /// Adding interfaces and members, and making properties writable or readable in addition to
+    /// their current access, is additive. Anything else is breaking.
+    pub fn compatibility(&self) -> Compatibility {
+        match self {
+            Change::InterfaceAdded(_) | Change::MemberAdded { .. } => Compatibility::Additive,
+            Change::PropertyAccessChanged { old, new, .. }
+                if (!old.read() || new.read()) && (!old.write() || new.write()) =>
+            {
+                Compatibility::Additive
+            }
+            _ => Compatibility::Breaking,
+        }
+    }
+
+    /// Shorthand for `self.compatibility() == Compatibility::Breaking`.
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility() == Compatibility::Breaking
+    }
+
+    /// The name of the interface this change is about.
+    pub fn interface(&self) -> &str {
+        match self {
+            Change::InterfaceAdded(interface)
+            | Change::InterfaceRemoved(interface)
+            | Change::MemberAdded { interface, .. }
+            | Change::MemberRemoved { interface, .. }
+            | Change::ArgsChanged { interface, .. }
+            | Change::PropertyTypeChanged { interface, .. }
+            | Change::PropertyAccessChanged { interface, .. } => interface,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::InterfaceAdded(interface) => write!(f, "interface `{interface}` added"),
+            Change::InterfaceRemoved(interface) => write!(f, "interface `{interface}` removed"),
+            Change::MemberAdded { interface, member } => {
+                write!(f, "{interface}: {member} added")
+            }
+            Change::MemberRemoved { interface, member } => {
+                write!(f, "{interface}: {member} removed")
+            }
+            Change::ArgsChanged {
+                interface,
+                member,
+                direction,
+                old,
+                new,
+            } => {
+                let direction = match direction {
+                    ArgDirection::In => "input",
+                    ArgDirection::Out => "output",
+                };
+                write!(
+                    f,
+                    "{interface}: {direction} arguments of {member} changed from `{}` to `{}`",
+                    Signatures(old),
+                    Signatures(new),
+                )
+            }
+            Change::PropertyTypeChanged {
+                interface,
+                property,
+                old,
+                new,
+            } => write!(
+                f,
+                "{interface}: type of property `{property}` changed from `{old}` to `{new}`"
+            ),
+            Change::PropertyAccessChanged {
+                interface,
+                property,
+                old,
+                new,
+            } => write!(
+                f,
+                "{interface}: access of property `{property}` changed from `{old}` to `{new}`"
+            ),
+        }
+    }
+}
+
+struct Signatures<'s>(&'s [Signature]);
+
+impl fmt::Display for Signatures<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for signature in self.0 {
+            write!(f, "{signature}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// The changes between the interfaces of the `old` and `new` versions of a node.
+///
+/// Only the interfaces of the nodes themselves are compared, not those of their children.
+pub fn diff_nodes(old: &Node<'_>, new: &Node<'_>) -> Vec<Change> {
+    let old = by_name(old.interfaces(), |i| i.name().to_string());
+    let mut new = by_name(new.interfaces(), |i| i.name().to_string());
+
+    let mut changes = vec![];
+    for (name, old) in old {
+        match new.remove(&name) {
+            Some(new) => changes.extend(diff_interfaces(old, new)),
+            None => changes.push(Change::InterfaceRemoved(name)),
+        }
+    }
+    changes.extend(new.into_keys().map(Change::InterfaceAdded));
+
+    changes
+}
+
+/// The changes between the `old` and `new` versions of an interface.
+///
+/// The interface names are not compared. Argument names, annotations and the order of members
+/// are not taken into account either, as they don't affect clients.
+pub fn diff_interfaces(old: &Interface<'_>, new: &Interface<'_>) -> Vec<Change> {
+    let interface = old.name().to_string();
+    let mut changes = vec![];
+
+    diff_members(
+        &mut changes,
+        &interface,
+        by_name(old.methods(), |m| m.name().to_string()),
+        by_name(new.methods(), |m| m.name().to_string()),
+        Member::Method,
+        |changes, member, old, new| {
+            for direction in [ArgDirection::In, ArgDirection::Out] {
+                // Method arguments are inputs, unless specified otherwise.
+                let signatures = |args: &[Arg]| -> Vec<Signature> {
+                    args.iter()
+                        .filter(|a| a.direction().unwrap_or(ArgDirection::In) == direction)
+                        .map(|a| a.ty().inner().clone())
+                        .collect()
+                };
+                let (old, new) = (signatures(old.args()), signatures(new.args()));
+                if old != new {
+                    changes.push(Change::ArgsChanged {
+                        interface: interface.clone(),
+                        member: member.clone(),
+                        direction,
+                        old,
+                        new,
+                    });
+                }
+            }
+        },
+    );
+
+    diff_members(
+        &mut changes,
+        &interface,
+        by_name(old.signals(), |s| s.name().to_string()),
+        by_name(new.signals(), |s| s.name().to_string()),
+        Member::Signal,
+        |changes, member, old, new| {
+            let signatures = |args: &[Arg]| -> Vec<Signature> {
+                args.iter().map(|a| a.ty().inner().clone()).collect()
+            };
+            let (old, new) = (signatures(old.args()), signatures(new.args()));
+            if old != new {
+                changes.push(Change::ArgsChanged {
+                    interface: interface.clone(),
+                    member,
+                    direction: ArgDirection::Out,
+                    old,
+                    new,
+                });
+            }
+        },
+    );
+
+    diff_members(
+        &mut changes,
+        &interface,
+        by_name(old.properties(), |p| p.name().to_string()),
+        by_name(new.properties(), |p| p.name().to_string()),
+        Member::Property,
+        |changes, _, old, new| {
+            let property = old.name().to_string();
+            if old.ty() != new.ty() {
+                changes.push(Change::PropertyTypeChanged {
+                    interface: interface.clone(),
+                    property: property.clone(),
+                    old: old.ty().inner().clone(),
+                    new: new.ty().inner().clone(),
+                });
+            }
+            if old.access() != new.access() {
+                changes.push(Change::PropertyAccessChanged {
+                    interface: interface.clone(),
+                    property,
+                    old: old.access(),
+                    new: new.access(),
+                });
+            }
+        },
+    );
+
+    changes
+}
+
+fn by_name<T>(items: &[T], name: impl Fn(&T) -> String) -> BTreeMap<String, &T> {
+    items.iter().map(|item| (name(item), item)).collect()
+}
+
+fn diff_members<T>(
+    changes: &mut Vec<Change>,
+    interface: &str,
+    old: BTreeMap<String, &T>,
+    mut new: BTreeMap<String, &T>,
+    member: fn(String) -> Member,
+    mut diff: impl FnMut(&mut Vec<Change>, Member, &T, &T),
+) {
+    for (name, old) in old {
+        match new.remove(&name) {
+            Some(new) => diff(changes, member(name), old, new),
+            None => changes.push(Change::MemberRemoved {
+                interface: interface.to_string(),
+                member: member(name),
+            }),
+        }
+    }
+    changes.extend(new.into_keys().map(|name| Change::MemberAdded {
+        interface: interface.to_string(),
+        member: member(name),
+    }));
+}
@@ -13,6 +13,8 @@ pub enum Error {
     QuickXml(DeError),
     /// An XML serialization error from quick_xml
     QuickXmlSer(SeError),
+    /// Invalid D-Bus name.
+    Names(zbus_names::Error),
 }
 
 impl PartialEq for Error {
@@ -21,6 +23,7 @@ impl PartialEq for Error {
             (Self::Variant(s), Self::Variant(o)) => s == o,
             (Self::QuickXml(_), Self::QuickXml(_)) => false,
             (Self::QuickXmlSer(_), Self::QuickXmlSer(_)) => false,
+            (Self::Names(s), Self::Names(o)) => s == o,
             (_, _) => false,
         }
     }
@@ -32,6 +35,7 @@ impl error::Error for Error {
             Error::Variant(e) => Some(e),
             Error::QuickXml(e) => Some(e),
             Error::QuickXmlSer(e) => Some(e),
+            Error::Names(e) => Some(e),
         }
     }
 }
@@ -42,6 +46,7 @@ impl fmt::Display for Error {
             Error::Variant(e) => write!(f, "{e}"),
             Error::QuickXml(e) => write!(f, "XML error: {e}"),
             Error::QuickXmlSer(e) => write!(f, "XML serialization error: {e}"),
+            Error::Names(e) => write!(f, "{e}"),
         }
     }
 }
@@ -52,6 +57,18 @@ impl From<VariantError> for Error {
     }
 }
 
+impl From<zvariant::signature::Error> for Error {
+    fn from(val: zvariant::signature::Error) -> Self {
+        Error::Variant(VariantError::SignatureParse(val))
+    }
+}
+
+impl From<zbus_names::Error> for Error {
+    fn from(val: zbus_names::Error) -> Self {
+        Error::Names(val)
+    }
+}
+
 impl From<DeError> for Error {
     fn from(val: DeError) -> Self {
         Error::QuickXml(val)
@@ -14,6 +14,14 @@
 mod error;
 pub use error::{Error, Result};
 
+mod builder;
+pub use builder::{
+    ArgBuilder, InterfaceBuilder, MethodBuilder, NodeBuilder, PropertyBuilder, SignalBuilder,
+};
+#[cfg(feature = "codegen")]
+pub mod codegen;
+pub mod diff;
+
 use quick_xml::{de::Deserializer, se::to_writer};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -56,11 +64,11 @@ pub enum ArgDirection {
 /// An argument
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Arg {
-    #[serde(rename = "@name")]
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
     name: Option<String>,
     #[serde(rename = "@type")]
     ty: Signature,
-    #[serde(rename = "@direction")]
+    #[serde(rename = "@direction", skip_serializing_if = "Option::is_none")]
     direction: Option<ArgDirection>,
     #[serde(rename = "annotation", default)]
     annotations: Vec<Annotation>,
@@ -166,6 +174,19 @@ impl PropertyAccess {
     }
 }
 
+impl std::fmt::Display for PropertyAccess {
+    /// The access as in introspection XML, e.g. `readwrite`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let access = match self {
+            PropertyAccess::Read => "read",
+            PropertyAccess::Write => "write",
+            PropertyAccess::ReadWrite => "readwrite",
+        };
+
+        f.write_str(access)
+    }
+}
+
 /// A property
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Property<'a> {
@@ -244,12 +265,32 @@ impl<'a> Interface<'a> {
     pub fn annotations(&self) -> &[Annotation] {
         &self.annotations
     }
+
+    /// Returns a mutable reference to the interface methods.
+    pub fn methods_mut(&mut self) -> &mut Vec<Method<'a>> {
+        &mut self.methods
+    }
+
+    /// Returns a mutable reference to the interface signals.
+    pub fn signals_mut(&mut self) -> &mut Vec<Signal<'a>> {
+        &mut self.signals
+    }
+
+    /// Returns a mutable reference to the interface properties.
+    pub fn properties_mut(&mut self) -> &mut Vec<Property<'a>> {
+        &mut self.properties
+    }
+
+    /// Returns a mutable reference to the associated annotations.
+    pub fn annotations_mut(&mut self) -> &mut Vec<Annotation> {
+        &mut self.annotations
+    }
 }
 
 /// An introspection tree node (typically the root of the XML document).
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Node<'a> {
-    #[serde(rename = "@name")]
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
     name: Option<String>,
 
     #[serde(rename = "interface", default, borrow)]
@@ -299,6 +340,16 @@ impl<'a> Node<'a> {
     pub fn interfaces(&self) -> &[Interface<'a>] {
         &self.interfaces
     }
+
+    /// Returns a mutable reference to the children nodes.
+    pub fn nodes_mut(&mut self) -> &mut Vec<Node<'a>> {
+        &mut self.nodes
+    }
+
+    /// Returns a mutable reference to the interfaces on this node.
+    pub fn interfaces_mut(&mut self) -> &mut Vec<Interface<'a>> {
+        &mut self.interfaces
+    }
 }
 
 impl<'a> TryFrom<&'a str> for Node<'a> {
@@ -36,3 +36,197 @@ fn invalid_arg_type() {
         Err(zbus_xml::Error::QuickXml(DeError::Custom(_)))
     ));
 }
+
+#[test]
+fn diff() -> Result<(), Box<dyn Error>> {
+    use zbus_xml::{
+        PropertyAccess,
+        diff::{Change, Compatibility, Member, diff_nodes},
+    };
+    use zvariant::Signature;
+
+    let old = Node::try_from(include_str!("data/diff_old.xml"))?;
+    let new = Node::try_from(include_str!("data/diff_new.xml"))?;
+    let changes = diff_nodes(&old, &new);
+
+    let interface = || "org.zbus.Counter1".to_string();
+    assert_eq!(
+        changes,
+        vec![
+            Change::MemberRemoved {
+                interface: interface(),
+                member: Member::Method("Reset".into()),
+            },
+            Change::ArgsChanged {
+                interface: interface(),
+                member: Member::Method("Snapshot".into()),
+                direction: ArgDirection::Out,
+                old: vec![Signature::array(Signature::U32)],
+                new: vec![Signature::array(Signature::U64)],
+            },
+            Change::MemberAdded {
+                interface: interface(),
+                member: Member::Method("Decrement".into()),
+            },
+            Change::ArgsChanged {
+                interface: interface(),
+                member: Member::Signal("Overflowed".into()),
+                direction: ArgDirection::Out,
+                old: vec![Signature::U32],
+                new: vec![Signature::U32, Signature::U32],
+            },
+            Change::PropertyAccessChanged {
+                interface: interface(),
+                property: "Label".into(),
+                old: PropertyAccess::ReadWrite,
+                new: PropertyAccess::Read,
+            },
+            Change::PropertyTypeChanged {
+                interface: interface(),
+                property: "Max".into(),
+                old: Signature::U32,
+                new: Signature::U64,
+            },
+            Change::PropertyAccessChanged {
+                interface: interface(),
+                property: "Step".into(),
+                old: PropertyAccess::Read,
+                new: PropertyAccess::ReadWrite,
+            },
+            Change::MemberAdded {
+                interface: interface(),
+                member: Member::Property("Min".into()),
+            },
+            Change::InterfaceRemoved("org.zbus.Legacy1".into()),
+            Change::InterfaceAdded("org.zbus.Counter2".into()),
+        ]
+    );
+
+    let compatibility: Vec<_> = changes.iter().map(Change::compatibility).collect();
+    assert_eq!(
+        compatibility,
+        [
+            Compatibility::Breaking,
+            Compatibility::Breaking,
+            Compatibility::Additive,
+            Compatibility::Breaking,
+            Compatibility::Breaking,
+            Compatibility::Breaking,
+            Compatibility::Additive,
+            Compatibility::Additive,
+            Compatibility::Breaking,
+            Compatibility::Additive,
+        ]
+    );
+    assert_eq!(
+        changes[1].to_string(),
+        "org.zbus.Counter1: output arguments of method `Snapshot` changed from `(au)` to `(at)`"
+    );
+    assert!(diff_nodes(&old, &old).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn builders() -> Result<(), Box<dyn Error>> {
+    use zbus_xml::{Annotation, Arg, Interface, Method, Property, PropertyAccess, Signal};
+
+    let iface = Interface::builder("org.zbus.Counter1")?
+        .method(
+            Method::builder("Increment")?
+                .arg(
+                    Arg::builder("u")?
+                        .name("by")
+                        .direction(ArgDirection::In)
+                        .build(),
+                )
+                .arg(Arg::builder("u")?.direction(ArgDirection::Out).build())
+                .annotation(Annotation::new("org.freedesktop.DBus.Deprecated", "true"))
+                .build(),
+        )
+        .signal(
+            Signal::builder("Overflowed")?
+                .arg(Arg::builder("u")?.name("count").build())
+                .build(),
+        )
+        .property(
+            Property::builder("Count", "u", PropertyAccess::Read)?
+                .annotation(Annotation::new(
+                    "org.freedesktop.DBus.Property.EmitsChangedSignal",
+                    "const",
+                ))
+                .build(),
+        )
+        .build();
+    let mut node = Node::builder()
+        .interface(iface)
+        .node(Node::builder().name("child").build())
+        .build();
+
+    // Round-trip through XML.
+    let mut xml = Vec::new();
+    node.to_writer(&mut xml)?;
+    let xml = String::from_utf8(xml)?;
+    let parsed = Node::try_from(xml.as_str())?;
+    assert_eq!(parsed, node);
+
+    let iface = &parsed.interfaces()[0];
+    assert_eq!(iface.name(), "org.zbus.Counter1");
+    let method = &iface.methods()[0];
+    assert_eq!(method.args()[0].name(), Some("by"));
+    assert_eq!(method.args()[1].direction(), Some(ArgDirection::Out));
+    assert_eq!(method.annotations()[0].value(), "true");
+    assert_eq!(iface.signals()[0].args()[0].ty(), "u");
+    assert_eq!(iface.properties()[0].access(), PropertyAccess::Read);
+    assert_eq!(parsed.nodes()[0].name(), Some("child"));
+
+    // Filtering & merging.
+    node.interfaces_mut()[0]
+        .methods_mut()
+        .retain(|m| m.name() != "Increment");
+    node.interfaces_mut()
+        .push(Interface::builder("org.zbus.Other1")?.build());
+    node.nodes_mut().clear();
+    assert!(node.interfaces()[0].methods().is_empty());
+    assert_eq!(node.interfaces()[1].name(), "org.zbus.Other1");
+    assert!(node.nodes().is_empty());
+
+    assert!(Method::builder("Not a member").is_err());
+    assert!(Arg::builder("z").is_err());
+
+    Ok(())
+}
+
+// File descriptors are only supported on Unix.
+#[cfg(all(feature = "codegen", unix))]
+#[test]
+fn codegen() -> Result<(), Box<dyn Error>> {
+    use zbus_xml::codegen::{Options, write_proxy};
+
+    let node = Node::try_from(
+        r#"<node><interface name="org.zbus.Exporter1">
+          <method name="Export"><arg name="fd" type="h" direction="in"/></method>
+          <method name="Count"><arg type="u" direction="out"/></method>
+        </interface></node>"#,
+    )?;
+    let iface = &node.interfaces()[0];
+
+    let mut proxy = String::new();
+    write_proxy(&mut proxy, iface, None, None, &Options::default())?;
+    assert!(proxy.contains("fn count(&self) -> zbus::Result<u32>;"));
+    assert!(!proxy.contains("#[cfg(unix)]"));
+
+    let options = Options::default()
+        .crate_path("::my_zbus")
+        .cfg_unix_fds(true);
+    let mut proxy = String::new();
+    write_proxy(&mut proxy, iface, None, None, &options)?;
+    assert!(proxy.contains("fn count(&self) -> ::my_zbus::Result<u32>;"));
+    // Only the member involving file descriptors is Unix-specific.
+    assert_eq!(proxy.matches("#[cfg(unix)]").count(), 1);
+    assert!(proxy.contains(
+        "#[cfg(unix)]\n    fn export(&self, fd: ::my_zbus::zvariant::Fd<'_>) -> ::my_zbus::Result<()>;"
+    ));
+
+    Ok(())
+}
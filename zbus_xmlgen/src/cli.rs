@@ -13,6 +13,17 @@ pub struct Args {
     /// be saved to that file. Use '-' to print the output to stdout.
     #[clap(short, long, allow_hyphen_values = true, global = true)]
     pub output: Option<String>,
+
+    /// Generate a struct and an `#[interface]` implementation skeleton for each interface, to
+    /// serve them, instead of a client proxy.
+    #[clap(long, global = true)]
+    pub server: bool,
+
+    /// Generate named structs for struct signatures, instead of tuples. The structs are named
+    /// after the `org.zbus.StructName` annotation of the argument or property, if any, or after
+    /// its name otherwise.
+    #[clap(long, global = true)]
+    pub named_structs: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -35,6 +46,11 @@ pub enum Command {
         object_path: String,
     },
 
+    /// Compare two versions of the interfaces in the specified files and report the changes,
+    /// failing if any of them is breaking.
+    #[clap()]
+    Diff { old: PathBuf, new: PathBuf },
+
     /// Generate code for interfaces from the specified address.
     #[clap()]
     Address {
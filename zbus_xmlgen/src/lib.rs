@@ -1,15 +1,17 @@
-use snakecase::ascii::to_snakecase;
 use std::{
     error::Error,
     fmt::{Display, Formatter, Write},
     process::{Command, Stdio},
 };
 
-use zbus::{
-    names::BusName,
-    zvariant::{ObjectPath, Signature},
+use zbus::{names::BusName, zvariant::ObjectPath};
+use zbus_xml::{
+    Interface,
+    codegen::{self, Options},
+    diff::{Change, Compatibility},
 };
-use zbus_xml::{Arg, ArgDirection, Interface};
+
+pub use zbus_xml::codegen::{STRUCT_NAME_ANNOTATION, pascal_case};
 
 pub fn write_interfaces(
     interfaces: &[Interface<'_>],
@@ -19,11 +21,37 @@ pub fn write_interfaces(
     input_src: &str,
     cargo_bin_name: &str,
     cargo_bin_version: &str,
+) -> Result<String, Box<dyn Error + 'static>> {
+    let options = WriteOptions {
+        service,
+        path,
+        ..WriteOptions::default()
+    };
+
+    write_interfaces_with(
+        interfaces,
+        standard_interfaces,
+        &options,
+        input_src,
+        cargo_bin_name,
+        cargo_bin_version,
+    )
+}
+
+/// Same as [`write_interfaces`] but with the given `options`.
+pub fn write_interfaces_with(
+    interfaces: &[Interface<'_>],
+    standard_interfaces: &[Interface<'_>],
+    options: &WriteOptions<'_>,
+    input_src: &str,
+    cargo_bin_name: &str,
+    cargo_bin_version: &str,
 ) -> Result<String, Box<dyn Error + 'static>> {
     let mut unformatted = String::new();
 
     write_doc_header(
         &mut unformatted,
+        Mode::Proxy,
         interfaces,
         standard_interfaces,
         input_src,
@@ -31,49 +59,167 @@ pub fn write_interfaces(
         cargo_bin_version,
     )?;
 
+    let service = options.service.as_ref().map(|s| s.as_str());
+    let path = options.path.as_ref().map(|p| p.as_str());
+    let codegen_options = options.codegen_options();
     for interface in interfaces {
-        let r#gen = GenTrait {
-            interface,
-            service: service.as_ref(),
-            path: path.as_ref(),
-            format: false,
-        };
+        codegen::write_proxy(&mut unformatted, interface, service, path, &codegen_options)?;
+    }
+
+    Ok(format_output(unformatted))
+}
+
+/// Same as [`write_interfaces_with`] but generates a struct and an `#[interface]` implementation
+/// skeleton for each interface, to serve them.
+///
+/// The service and path of `options` are ignored.
+pub fn write_server_interfaces(
+    interfaces: &[Interface<'_>],
+    standard_interfaces: &[Interface<'_>],
+    options: &WriteOptions<'_>,
+    input_src: &str,
+    cargo_bin_name: &str,
+    cargo_bin_version: &str,
+) -> Result<String, Box<dyn Error + 'static>> {
+    let mut unformatted = String::new();
+
+    write_doc_header(
+        &mut unformatted,
+        Mode::Server,
+        interfaces,
+        standard_interfaces,
+        input_src,
+        cargo_bin_name,
+        cargo_bin_version,
+    )?;
 
-        write!(unformatted, "{gen}")?;
+    let codegen_options = options.codegen_options();
+    for interface in interfaces {
+        codegen::write_interface(&mut unformatted, interface, &codegen_options)?;
+    }
+
+    Ok(format_output(unformatted))
+}
+
+/// Options for [`write_interfaces_with`] and [`write_server_interfaces`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct WriteOptions<'a> {
+    /// The default service of the generated proxies.
+    pub service: Option<BusName<'a>>,
+    /// The default path of the generated proxies.
+    pub path: Option<ObjectPath<'a>>,
+    /// Generate named structs for struct signatures, instead of tuples.
+    ///
+    /// The structs are named after the [`STRUCT_NAME_ANNOTATION`] annotation of the argument or
+    /// property, if any, or after its name otherwise.
+    pub named_structs: bool,
+}
+
+impl<'a> WriteOptions<'a> {
+    /// Set the default service of the generated proxies.
+    pub fn service(mut self, service: Option<BusName<'a>>) -> Self {
+        self.service = service;
+
+        self
+    }
+
+    /// Set the default path of the generated proxies.
+    pub fn path(mut self, path: Option<ObjectPath<'a>>) -> Self {
+        self.path = path;
+
+        self
+    }
+
+    /// Generate named structs for struct signatures, instead of tuples.
+    pub fn named_structs(mut self, named_structs: bool) -> Self {
+        self.named_structs = named_structs;
+
+        self
+    }
+
+    fn codegen_options(&self) -> Options {
+        Options::default().named_structs(self.named_structs)
+    }
+}
+
+/// A human-readable report of `changes`, listing the breaking changes first.
+pub fn compatibility_report(changes: &[Change]) -> String {
+    if changes.is_empty() {
+        return "No changes.\n".to_string();
     }
 
-    let formatted = match format_generated_code(&unformatted) {
+    let mut report = String::new();
+    for (compatibility, title) in [
+        (Compatibility::Breaking, "Breaking changes"),
+        (Compatibility::Additive, "Additive changes"),
+    ] {
+        let mut changes = changes
+            .iter()
+            .filter(|c| c.compatibility() == compatibility)
+            .peekable();
+        if changes.peek().is_none() {
+            continue;
+        }
+
+        if !report.is_empty() {
+            report.push('\n');
+        }
+        report.push_str(title);
+        report.push_str(":\n");
+        for change in changes {
+            report.push_str("  - ");
+            report.push_str(&change.to_string());
+            report.push('\n');
+        }
+    }
+
+    report
+}
+
+fn format_output(unformatted: String) -> String {
+    match format_generated_code(&unformatted) {
         Ok(formatted) => formatted,
         Err(e) => {
             eprintln!("Failed to format generated code: {e}");
             unformatted
         }
-    };
+    }
+}
 
-    Ok(formatted)
+/// The kind of code to generate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Proxy,
+    Server,
 }
 
 /// Write a doc header, listing the included Interfaces and how the
 /// code was generated.
 fn write_doc_header<W: std::fmt::Write>(
     w: &mut W,
+    mode: Mode,
     interfaces: &[Interface<'_>],
     standard_interfaces: &[Interface<'_>],
     input_src: &str,
     cargo_bin_name: &str,
     cargo_bin_version: &str,
 ) -> std::fmt::Result {
+    let (kind, kinds) = match mode {
+        Mode::Proxy => ("proxy", "proxies"),
+        Mode::Server => ("skeleton", "skeletons"),
+    };
     if let Some((first_iface, following_ifaces)) = interfaces.split_first() {
         if following_ifaces.is_empty() {
             writeln!(
                 w,
-                "//! # D-Bus interface proxy for: `{}`",
+                "//! # D-Bus interface {kind} for: `{}`",
                 first_iface.name()
             )?;
         } else {
             write!(
                 w,
-                "//! # D-Bus interface proxies for: `{}`",
+                "//! # D-Bus interface {kinds} for: `{}`",
                 first_iface.name()
             )?;
             for iface in following_ifaces {
@@ -83,6 +229,10 @@ fn write_doc_header<W: std::fmt::Write>(
         }
     }
 
+    let (section, section_url) = match mode {
+        Mode::Proxy => ("Writing a client proxy", "client.html"),
+        Mode::Server => ("Writing a service interface", "service.html"),
+    };
     write!(
         w,
         "//!
@@ -91,22 +241,36 @@ fn write_doc_header<W: std::fmt::Write>(
          //!
          //! You may prefer to adapt it, instead of using it verbatim.
          //!
-         //! More information can be found in the [Writing a client proxy] section of the zbus
+         //! More information can be found in the [{section}] section of the zbus
          //! documentation.
          //!
         ",
     )?;
 
     if !standard_interfaces.is_empty() {
-        write!(w,
-            "//! This type implements the [D-Bus standard interfaces], (`org.freedesktop.DBus.*`) for which the
-             //! following zbus API can be used:
-             //!
-            ")?;
-        for iface in standard_interfaces {
-            let idx = iface.name().rfind('.').unwrap() + 1;
-            let name = &iface.name()[idx..];
-            writeln!(w, "//! * [`zbus::fdo::{name}Proxy`]")?;
+        match mode {
+            Mode::Proxy => {
+                write!(w,
+                    "//! This type implements the [D-Bus standard interfaces], (`org.freedesktop.DBus.*`) for which the
+                     //! following zbus API can be used:
+                     //!
+                    ")?;
+                for iface in standard_interfaces {
+                    let idx = iface.name().rfind('.').unwrap() + 1;
+                    let name = &iface.name()[idx..];
+                    writeln!(w, "//! * [`zbus::fdo::{name}Proxy`]")?;
+                }
+            }
+            Mode::Server => {
+                write!(w,
+                    "//! This type implements the [D-Bus standard interfaces], (`org.freedesktop.DBus.*`). These are
+                     //! either provided by the zbus `ObjectServer` or available in the `zbus::fdo` module:
+                     //!
+                    ")?;
+                for iface in standard_interfaces {
+                    writeln!(w, "//! * `{}`", iface.name())?;
+                }
+            }
         }
         write!(
             w,
@@ -116,12 +280,16 @@ fn write_doc_header<W: std::fmt::Write>(
         )?;
     }
 
+    let import = match mode {
+        Mode::Proxy => "proxy",
+        Mode::Server => "interface",
+    };
     write!(
         w,
         "//!
-        //! [Writing a client proxy]: https://z-galaxy.github.io/zbus/client.html
+        //! [{section}]: https://z-galaxy.github.io/zbus/{section_url}
         //! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
-        use zbus::proxy;
+        use zbus::{import};
         "
     )?;
 
@@ -135,365 +303,122 @@ pub struct GenTrait<'i> {
     pub format: bool,
 }
 
-impl Display for GenTrait<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.format {
-            let mut unformatted = String::new();
-            self.write_interface(&mut unformatted)?;
-
-            let formatted = format_generated_code(&unformatted).unwrap_or(unformatted);
-
-            write!(f, "{formatted}")
-        } else {
-            self.write_interface(f)
+impl<'i> GenTrait<'i> {
+    /// Create a proxy trait generator for `interface`, without default service and path.
+    pub fn new(interface: &'i Interface<'i>) -> Self {
+        Self {
+            interface,
+            service: None,
+            path: None,
+            format: false,
         }
     }
-}
-
-impl GenTrait<'_> {
-    fn write_interface<W: Write>(&self, w: &mut W) -> std::fmt::Result {
-        let iface = self.interface;
-        let idx = iface.name().rfind('.').unwrap() + 1;
-        let name = &iface.name()[idx..];
 
-        write!(w, "#[proxy(interface = \"{}\"", iface.name())?;
-        if let Some(service) = self.service {
-            write!(w, ", default_service = \"{service}\"")?;
-        }
-        if let Some(path) = self.path {
-            write!(w, ", default_path = \"{path}\"")?;
-        }
-        if self.path.is_none() || self.service.is_none() {
-            write!(w, ", assume_defaults = true")?;
-        }
-        writeln!(w, ")]")?;
-        writeln!(w, "pub trait {name} {{")?;
-
-        let mut methods = iface.methods().to_vec();
-        methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
-        for m in &methods {
-            let (inputs, output) = inputs_output_from_args(m.args());
-            let name = to_identifier(&to_snakecase(m.name().as_str()));
-            writeln!(w)?;
-            writeln!(w, "    /// {} method", m.name())?;
-            if pascal_case(&name) != m.name().as_str() {
-                writeln!(w, "    #[zbus(name = \"{}\")]", m.name())?;
-            }
-            hide_clippy_lints(w, m)?;
-            writeln!(w, "    fn {name}({inputs}){output};")?;
-        }
-
-        let mut signals = iface.signals().to_vec();
-        signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
-        for signal in &signals {
-            let args = parse_signal_args(signal.args());
-            let name = to_identifier(&to_snakecase(signal.name().as_str()));
-            writeln!(w)?;
-            writeln!(w, "    /// {} signal", signal.name())?;
-            if pascal_case(&name) != signal.name().as_str() {
-                writeln!(w, "    #[zbus(signal, name = \"{}\")]", signal.name())?;
-            } else {
-                writeln!(w, "    #[zbus(signal)]")?;
-            }
-            writeln!(w, "    fn {name}({args}) -> zbus::Result<()>;",)?;
-        }
+    /// Set the default service of the proxy.
+    pub fn service(mut self, service: &'i BusName<'i>) -> Self {
+        self.service = Some(service);
 
-        let mut props = iface.properties().to_vec();
-        props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
-        for p in props {
-            let name = to_identifier(&to_snakecase(p.name().as_str()));
-            let fn_attribute = if pascal_case(&name) != p.name().as_str() {
-                format!("    #[zbus(property, name = \"{}\")]", p.name())
-            } else {
-                "    #[zbus(property)]".to_string()
-            };
+        self
+    }
 
-            writeln!(w)?;
-            writeln!(w, "    /// {} property", p.name())?;
-            if p.access().read() {
-                writeln!(w, "{fn_attribute}")?;
-                let output = to_rust_type(p.ty(), false, false);
-                hide_clippy_type_complexity_lint(w, p.ty())?;
-                writeln!(w, "    fn {name}(&self) -> zbus::Result<{output}>;",)?;
-            }
+    /// Set the default path of the proxy.
+    pub fn path(mut self, path: &'i ObjectPath<'i>) -> Self {
+        self.path = Some(path);
 
-            if p.access().write() {
-                writeln!(w, "{fn_attribute}")?;
-                let input = to_rust_type(p.ty(), true, true);
-                writeln!(
-                    w,
-                    "    fn set_{name}(&self, value: {input}) -> zbus::Result<()>;",
-                )?;
-            }
-        }
-        writeln!(w, "}}")
+        self
     }
-}
 
-fn hide_clippy_lints<W: Write>(write: &mut W, method: &zbus_xml::Method<'_>) -> std::fmt::Result {
-    // check for <https://rust-lang.github.io/rust-clippy/master/index.html#/too_many_arguments>
-    // triggers when a functions has at least 7 paramters
-    if method.args().len() >= 7 {
-        writeln!(write, "    #[allow(clippy::too_many_arguments)]")?;
-    }
+    /// Format the generated code with `rustfmt`.
+    pub fn format(mut self, format: bool) -> Self {
+        self.format = format;
 
-    // check for <https://rust-lang.github.io/rust-clippy/master/index.html#/type_complexity>
-    for arg in method.args() {
-        let signature = arg.ty();
-        hide_clippy_type_complexity_lint(write, signature)?;
+        self
     }
 
-    Ok(())
-}
-
-fn hide_clippy_type_complexity_lint<W: Write>(
-    write: &mut W,
-    signature: &Signature,
-) -> std::fmt::Result {
-    let complexity = estimate_type_complexity(signature);
-    if complexity >= 1700 {
-        writeln!(write, "    #[allow(clippy::type_complexity)]")?;
+    /// Write the proxy trait to `w`, generating the code as specified by `options`.
+    ///
+    /// The service and path of `options` are ignored.
+    pub fn write_with<W: Write>(&self, w: &mut W, options: &WriteOptions<'_>) -> std::fmt::Result {
+        write_generated(w, self.format, |w| {
+            codegen::write_proxy(
+                w,
+                self.interface,
+                self.service.map(|s| s.as_str()),
+                self.path.map(|p| p.as_str()),
+                &options.codegen_options(),
+            )
+        })
     }
-    Ok(())
 }
 
-fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
-    let mut inputs = vec!["&self".to_string()];
-    let mut output: Vec<OutputArg> = vec![];
-    let mut n = 0;
-    let mut gen_name = || {
-        n += 1;
-        format!("arg_{n}")
-    };
-
-    for a in args {
-        match a.direction() {
-            None | Some(ArgDirection::In) => {
-                let ty = to_rust_type(a.ty(), true, true);
-                let arg = if let Some(name) = a.name() {
-                    to_identifier(name)
-                } else {
-                    gen_name()
-                };
-                inputs.push(format!("{arg}: {ty}"));
-            }
-            Some(ArgDirection::Out) => {
-                let ty = to_rust_type(a.ty(), false, false);
-                let is_struct = matches!(a.ty().inner(), Signature::Structure(_));
-                output.push(OutputArg { ty, is_struct });
-            }
-        }
+impl Display for GenTrait<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_with(f, &WriteOptions::default())
     }
-
-    let output_str = match &output[..] {
-        [] => "()".to_string(),
-        [
-            OutputArg {
-                ty,
-                is_struct: true,
-            },
-        ] => {
-            // If there's a single output argument and it is a struct type, we need to wrap it
-            // in a tuple to distinguish it from multiple return values
-            format!("({ty},)")
-        }
-        [
-            OutputArg {
-                ty,
-                is_struct: false,
-            },
-        ] => ty.clone(),
-        multiple => {
-            let types = multiple
-                .iter()
-                .map(|arg| arg.ty.as_str())
-                .collect::<Vec<_>>();
-            format!("({})", types.join(", "))
-        }
-    };
-
-    (inputs.join(", "), format!(" -> zbus::Result<{output_str}>"))
 }
 
-#[derive(Debug)]
-struct OutputArg {
-    ty: String,
-    is_struct: bool,
+/// Generates a struct and an `#[interface]` implementation skeleton for a D-Bus interface.
+///
+/// All methods and property accessors have `todo!()` bodies.
+#[non_exhaustive]
+pub struct GenInterface<'i> {
+    pub interface: &'i Interface<'i>,
+    pub format: bool,
 }
 
-fn parse_signal_args(args: &[Arg]) -> String {
-    let mut inputs = vec!["&self".to_string()];
-    let mut n = 0;
-    let mut gen_name = || {
-        n += 1;
-        format!("arg_{n}")
-    };
-
-    for a in args {
-        let ty = to_rust_type(a.ty(), true, false);
-        let arg = if let Some(name) = a.name() {
-            to_identifier(name)
-        } else {
-            gen_name()
-        };
-        inputs.push(format!("{arg}: {ty}"));
+impl<'i> GenInterface<'i> {
+    /// Create an interface skeleton generator for `interface`.
+    pub fn new(interface: &'i Interface<'i>) -> Self {
+        Self {
+            interface,
+            format: false,
+        }
     }
 
-    inputs.join(", ")
-}
+    /// Format the generated code with `rustfmt`.
+    pub fn format(mut self, format: bool) -> Self {
+        self.format = format;
 
-fn to_rust_type(ty: &Signature, input: bool, as_ref: bool) -> String {
-    // can't haz recursive closure, yet
-    fn signature_to_rust_type(signature: &Signature, input: bool, as_ref: bool) -> String {
-        match signature {
-            Signature::Unit => "".into(),
-            Signature::U8 => "u8".into(),
-            Signature::Bool => "bool".into(),
-            Signature::I16 => "i16".into(),
-            Signature::U16 => "u16".into(),
-            Signature::I32 => "i32".into(),
-            Signature::U32 => "u32".into(),
-            Signature::I64 => "i64".into(),
-            Signature::U64 => "u64".into(),
-            Signature::F64 => "f64".into(),
-            #[cfg(unix)]
-            Signature::Fd if input => "zbus::zvariant::Fd<'_>".into(),
-            #[cfg(unix)]
-            Signature::Fd => "zbus::zvariant::OwnedFd".into(),
-            Signature::Str if input || as_ref => "&str".into(),
-            Signature::Str => "String".into(),
-            Signature::ObjectPath if input => {
-                if as_ref {
-                    "&zbus::zvariant::ObjectPath<'_>".into()
-                } else {
-                    "zbus::zvariant::ObjectPath<'_>".into()
-                }
-            }
-            Signature::ObjectPath => "zbus::zvariant::OwnedObjectPath".into(),
-            Signature::Signature if input => {
-                if as_ref {
-                    "&zbus::zvariant::Signature<'_>".into()
-                } else {
-                    "zbus::zvariant::Signature<'_>".into()
-                }
-            }
-            Signature::Signature => "zbus::zvariant::OwnedSignature".into(),
-            Signature::Variant if input => {
-                if as_ref {
-                    "&zbus::zvariant::Value<'_>".into()
-                } else {
-                    "zbus::zvariant::Value<'_>".into()
-                }
-            }
-            Signature::Variant => "zbus::zvariant::OwnedValue".into(),
-            Signature::Array(child) => {
-                let child_ty = signature_to_rust_type(child, input, as_ref);
-                if input && as_ref {
-                    format!("&[{child_ty}]")
-                } else {
-                    format!("Vec<{child_ty}>")
-                }
-            }
-            Signature::Dict { key, value } => {
-                let key_ty = signature_to_rust_type(key, input, as_ref);
-                let value_ty = signature_to_rust_type(value, input, as_ref);
-
-                format!("std::collections::HashMap<{key_ty}, {value_ty}>")
-            }
-            Signature::Structure(fields) => {
-                let fields = fields
-                    .iter()
-                    .map(|f| signature_to_rust_type(f, input, as_ref))
-                    .collect::<Vec<_>>();
-
-                if fields.len() > 1 {
-                    format!("{}({})", if as_ref { "&" } else { "" }, fields.join(", "))
-                } else {
-                    format!("{}({},)", if as_ref { "&" } else { "" }, fields[0])
-                }
-            }
-            #[allow(unreachable_patterns)]
-            _ => unreachable!("Unsupported signature: {}", signature),
-        }
+        self
     }
 
-    signature_to_rust_type(ty, input, as_ref)
-}
-
-static KWORDS: &[&str] = &[
-    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
-    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl",
-    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
-    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
-    "union", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
-];
-
-fn to_identifier(id: &str) -> String {
-    if KWORDS.contains(&id) {
-        format!("{id}_")
-    } else {
-        id.replace('-', "_")
+    /// Write the interface skeleton to `w`, generating the code as specified by `options`.
+    ///
+    /// The service and path of `options` are ignored.
+    pub fn write_with<W: Write>(&self, w: &mut W, options: &WriteOptions<'_>) -> std::fmt::Result {
+        write_generated(w, self.format, |w| {
+            codegen::write_interface(w, self.interface, &options.codegen_options())
+        })
     }
 }
 
-// This function is the same as zbus_macros::utils::pascal_case
-pub fn pascal_case(s: &str) -> String {
-    let mut pascal = String::new();
-    let mut capitalize = true;
-    for ch in s.chars() {
-        if ch == '_' {
-            capitalize = true;
-        } else if capitalize {
-            pascal.push(ch.to_ascii_uppercase());
-            capitalize = false;
-        } else {
-            pascal.push(ch);
-        }
+impl Display for GenInterface<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_with(f, &WriteOptions::default())
     }
-    pascal
 }
 
-fn estimate_type_complexity(signature: &Signature) -> u32 {
-    let mut score = 0;
-
-    match signature {
-        Signature::Unit => (),
-        Signature::U8
-        | Signature::Bool
-        | Signature::I16
-        | Signature::U16
-        | Signature::I32
-        | Signature::U32
-        | Signature::I64
-        | Signature::U64
-        | Signature::F64
-        | Signature::Str => score += 1,
-        #[cfg(unix)]
-        Signature::Fd => score += 10,
-        Signature::ObjectPath | Signature::Signature | Signature::Variant => score += 10,
-        Signature::Array(child) => score += 5 * estimate_type_complexity(child),
-        Signature::Dict { key, value } => {
-            score *= 10 + 50;
-            score += 5 * estimate_type_complexity(key);
-            score += 5 * estimate_type_complexity(value);
-        }
-        Signature::Structure(fields) => {
-            score += 50;
-            for field in fields.iter() {
-                score += 5 * estimate_type_complexity(field);
-            }
-        }
-        #[allow(unreachable_patterns)]
-        _ => unreachable!("Unsupported signature: {}", signature),
+/// Write the code generated by `generate` to `w`, formatted with `rustfmt` if `format` is set.
+fn write_generated<W, F, E>(w: &mut W, format: bool, generate: F) -> std::fmt::Result
+where
+    W: Write,
+    F: FnOnce(&mut String) -> Result<(), E>,
+{
+    let mut generated = String::new();
+    generate(&mut generated).map_err(|_| std::fmt::Error)?;
+    if format {
+        generated = format_generated_code(&generated).unwrap_or(generated);
     }
 
-    score
+    write!(w, "{generated}")
 }
 
 fn format_generated_code(generated_code: &str) -> std::io::Result<String> {
     use std::io::{Read, Write};
 
     let mut process = Command::new("rustfmt")
+        // `async fn` (used for signals in `#[interface]`) is not valid in the default 2015 edition.
+        .args(["--edition", "2021"])
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         // rustfmt may post warnings about features not being enabled on stable rust
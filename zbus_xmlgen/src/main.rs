@@ -15,7 +15,9 @@ use zbus::{
 };
 use zbus_xml::{Interface, Node};
 
-use zbus_xmlgen::write_interfaces;
+use zbus_xmlgen::{
+    WriteOptions, compatibility_report, write_interfaces_with, write_server_interfaces,
+};
 
 mod cli;
 
@@ -51,6 +53,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             let f = File::open(path)?;
             DBusInfo(Node::from_reader(f)?, None, None, input_src)
         }
+        cli::Command::Diff { old, new } => {
+            let old = Node::from_reader(File::open(old)?)?;
+            let new = Node::from_reader(File::open(new)?)?;
+            let changes = zbus_xml::diff::diff_nodes(&old, &new);
+            print!("{}", compatibility_report(&changes));
+            if changes.iter().any(|c| c.is_breaking()) {
+                std::process::exit(1);
+            }
+
+            return Ok(());
+        }
     };
 
     let fdo_iface_prefix = "org.freedesktop.DBus";
@@ -79,16 +92,30 @@ fn main() -> Result<(), Box<dyn Error>> {
         _ => OutputTarget::MultipleFiles,
     };
 
+    let options = WriteOptions::default()
+        .service(service)
+        .path(path)
+        .named_structs(args.named_structs);
     for interface in needed_ifaces {
-        let output = write_interfaces(
-            std::slice::from_ref(&interface),
-            &fdo_standard_ifaces,
-            service.clone(),
-            path.clone(),
-            &input_src,
-            env!("CARGO_BIN_NAME"),
-            env!("CARGO_PKG_VERSION"),
-        )?;
+        let output = if args.server {
+            write_server_interfaces(
+                std::slice::from_ref(&interface),
+                &fdo_standard_ifaces,
+                &options,
+                &input_src,
+                env!("CARGO_BIN_NAME"),
+                env!("CARGO_PKG_VERSION"),
+            )?
+        } else {
+            write_interfaces_with(
+                std::slice::from_ref(&interface),
+                &fdo_standard_ifaces,
+                &options,
+                &input_src,
+                env!("CARGO_BIN_NAME"),
+                env!("CARGO_PKG_VERSION"),
+            )?
+        };
 
         let interface_name = interface.name();
         match output_target {
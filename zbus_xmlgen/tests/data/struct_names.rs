@@ -0,0 +1,158 @@
+#[proxy(interface = "test.StructNames", assume_defaults = true)]
+pub trait StructNames {
+    /// AddPoints method
+    fn add_points(&self, points: &[Point], origin: &Point) -> zbus::Result<AddPointsResult>;
+
+    /// GetRecords method
+    fn get_records(
+        &self,
+    ) -> zbus::Result<(std::collections::HashMap<String, Records>, GetRecordsArg1)>;
+
+    /// ReturnsNestedStruct method
+    fn returns_nested_struct(&self) -> zbus::Result<ReturnsNestedStructResult>;
+
+    /// Moved signal
+    #[zbus(signal)]
+    fn moved(&self, new_position: Point) -> zbus::Result<()>;
+
+    /// Bounds property
+    #[zbus(property)]
+    fn bounds(&self) -> zbus::Result<Bounds>;
+    #[zbus(property)]
+    fn set_bounds(&self, value: Bounds) -> zbus::Result<()>;
+}
+
+/// D-Bus struct with signature `(ii)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct Point {
+    pub field_0: i32,
+    pub field_1: i32,
+}
+
+/// D-Bus struct with signature `(ss)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct AddPointsResult {
+    pub field_0: String,
+    pub field_1: String,
+}
+
+/// D-Bus struct with signature `(sx)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct Records {
+    pub field_0: String,
+    pub field_1: i64,
+}
+
+/// D-Bus struct with signature `(ub)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct GetRecordsArg1 {
+    pub field_0: u32,
+    pub field_1: bool,
+}
+
+/// D-Bus struct with signature `((ss)i)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct ReturnsNestedStructResult {
+    pub field_0: ReturnsNestedStructResultField0,
+    pub field_1: i32,
+}
+
+/// D-Bus struct with signature `(ss)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct ReturnsNestedStructResultField0 {
+    pub field_0: String,
+    pub field_1: String,
+}
+
+/// D-Bus struct with signature `((ii)(ii))`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct Bounds {
+    pub field_0: BoundsField0,
+    pub field_1: BoundsField1,
+}
+
+/// D-Bus struct with signature `(ii)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct BoundsField0 {
+    pub field_0: i32,
+    pub field_1: i32,
+}
+
+/// D-Bus struct with signature `(ii)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct BoundsField1 {
+    pub field_0: i32,
+    pub field_1: i32,
+}
@@ -0,0 +1,175 @@
+pub struct StructNames;
+
+#[interface(name = "test.StructNames")]
+impl StructNames {
+    /// AddPoints method
+    #[zbus(out_args("result"))]
+    fn add_points(&self, points: Vec<Point>, origin: Point) -> zbus::fdo::Result<AddPointsResult> {
+        todo!()
+    }
+
+    /// GetRecords method
+    fn get_records(
+        &self,
+    ) -> zbus::fdo::Result<(std::collections::HashMap<String, Records>, GetRecordsArg1)> {
+        todo!()
+    }
+
+    /// ReturnsNestedStruct method
+    #[zbus(out_args("result"))]
+    fn returns_nested_struct(&self) -> zbus::fdo::Result<ReturnsNestedStructResult> {
+        todo!()
+    }
+
+    /// Moved signal
+    #[zbus(signal)]
+    async fn moved(
+        emitter: &zbus::object_server::SignalEmitter<'_>,
+        new_position: Point,
+    ) -> zbus::Result<()>;
+
+    /// Bounds property
+    #[zbus(property)]
+    fn bounds(&self) -> zbus::fdo::Result<Bounds> {
+        todo!()
+    }
+    #[zbus(property)]
+    fn set_bounds(&mut self, value: Bounds) -> zbus::fdo::Result<()> {
+        todo!()
+    }
+}
+
+/// D-Bus struct with signature `(ii)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct Point {
+    pub field_0: i32,
+    pub field_1: i32,
+}
+
+/// D-Bus struct with signature `(ss)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct AddPointsResult {
+    pub field_0: String,
+    pub field_1: String,
+}
+
+/// D-Bus struct with signature `(sx)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct Records {
+    pub field_0: String,
+    pub field_1: i64,
+}
+
+/// D-Bus struct with signature `(ub)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct GetRecordsArg1 {
+    pub field_0: u32,
+    pub field_1: bool,
+}
+
+/// D-Bus struct with signature `((ss)i)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct ReturnsNestedStructResult {
+    pub field_0: ReturnsNestedStructResultField0,
+    pub field_1: i32,
+}
+
+/// D-Bus struct with signature `(ss)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct ReturnsNestedStructResultField0 {
+    pub field_0: String,
+    pub field_1: String,
+}
+
+/// D-Bus struct with signature `((ii)(ii))`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct Bounds {
+    pub field_0: BoundsField0,
+    pub field_1: BoundsField1,
+}
+
+/// D-Bus struct with signature `(ii)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct BoundsField0 {
+    pub field_0: i32,
+    pub field_1: i32,
+}
+
+/// D-Bus struct with signature `(ii)`.
+#[derive(
+    Clone,
+    Debug,
+    serde::Deserialize,
+    serde::Serialize,
+    zbus::zvariant::Type,
+    zbus::zvariant::Value,
+    zbus::zvariant::OwnedValue,
+)]
+pub struct BoundsField1 {
+    pub field_0: i32,
+    pub field_1: i32,
+}
@@ -0,0 +1,28 @@
+pub struct StructReturn;
+
+#[interface(name = "test.StructReturn")]
+impl StructReturn {
+    /// ReturnsNestedStruct method
+    #[zbus(out_args("result"))]
+    fn returns_nested_struct(&self) -> zbus::fdo::Result<(((String, String), i32),)> {
+        todo!()
+    }
+
+    /// ReturnsOneString method
+    #[zbus(out_args("result"))]
+    fn returns_one_string(&self) -> zbus::fdo::Result<String> {
+        todo!()
+    }
+
+    /// ReturnsStruct method
+    #[zbus(out_args("result"))]
+    fn returns_struct(&self) -> zbus::fdo::Result<((String, String),)> {
+        todo!()
+    }
+
+    /// ReturnsTwoStrings method
+    #[zbus(out_args("result1", "result2"))]
+    fn returns_two_strings(&self) -> zbus::fdo::Result<(String, String)> {
+        todo!()
+    }
+}
@@ -1,23 +1,54 @@
 use pretty_assertions::assert_eq;
 use std::{env, error::Error, io::Write, path::Path};
 
-use zbus_xml::Node;
-use zbus_xmlgen::GenTrait;
+use zbus_xml::{Interface, Node};
+use zbus_xmlgen::{GenInterface, GenTrait, WriteOptions};
+
+fn proxy(interface: &Interface<'_>) -> String {
+    GenTrait {
+        interface,
+        path: None,
+        service: None,
+        format: true,
+    }
+    .to_string()
+}
+
+fn proxy_named_structs(interface: &Interface<'_>) -> String {
+    let mut generated = String::new();
+    GenTrait::new(interface)
+        .format(true)
+        .write_with(&mut generated, &WriteOptions::default().named_structs(true))
+        .unwrap();
+
+    generated
+}
+
+fn server(interface: &Interface<'_>) -> String {
+    GenInterface::new(interface).format(true).to_string()
+}
+
+fn server_named_structs(interface: &Interface<'_>) -> String {
+    let mut generated = String::new();
+    GenInterface::new(interface)
+        .format(true)
+        .write_with(&mut generated, &WriteOptions::default().named_structs(true))
+        .unwrap();
+
+    generated
+}
 
 macro_rules! gen_diff {
-    ($infile:literal, $outfile:literal) => {{
+    ($infile:literal, $outfile:literal) => {
+        gen_diff!($infile, $outfile, proxy)
+    };
+    ($infile:literal, $outfile:literal, $gen:ident) => {{
         let input = include_str!(concat!("data/", $infile));
         let expected = include_str!(concat!("data/", $outfile));
         #[cfg(windows)]
         let expected = expected.replace("\r\n", "\n");
         let node = Node::from_reader(input.as_bytes())?;
-        let r#gen = GenTrait {
-            interface: &node.interfaces()[0],
-            path: None,
-            service: None,
-            format: true,
-        }
-        .to_string();
+        let r#gen = $gen(&node.interfaces()[0]);
 
         if env::var("TEST_OVERWRITE").is_ok() {
             let path = Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -47,3 +78,169 @@ fn sample_object0() -> Result<(), Box<dyn Error>> {
 fn struct_return() -> Result<(), Box<dyn Error>> {
     gen_diff!("struct_return.xml", "struct_return.rs")
 }
+
+#[test]
+fn sample_object0_server() -> Result<(), Box<dyn Error>> {
+    gen_diff!("sample_object0.xml", "sample_object0_server.rs", server)
+}
+
+#[test]
+fn struct_return_server() -> Result<(), Box<dyn Error>> {
+    gen_diff!("struct_return.xml", "struct_return_server.rs", server)
+}
+
+#[test]
+fn struct_names() -> Result<(), Box<dyn Error>> {
+    gen_diff!("struct_names.xml", "struct_names.rs", proxy_named_structs)
+}
+
+#[test]
+fn struct_names_server() -> Result<(), Box<dyn Error>> {
+    gen_diff!(
+        "struct_names.xml",
+        "struct_names_server.rs",
+        server_named_structs
+    )
+}
+
+#[test]
+fn struct_names_server_introspection() -> Result<(), Box<dyn Error>> {
+    use zbus::object_server::Interface as _;
+
+    let mut xml = String::from("<node>");
+    generated_servers::struct_names::StructNames.introspect_to_writer(&mut xml, 0);
+    xml.push_str("</node>");
+    let generated = Node::from_reader(xml.as_bytes())?;
+    let original = Node::from_reader(include_str!("data/struct_names.xml").as_bytes())?;
+
+    assert_eq!(
+        members(&generated.interfaces()[0]),
+        members(&original.interfaces()[0])
+    );
+    Ok(())
+}
+
+#[test]
+fn compatibility_report() -> Result<(), Box<dyn Error>> {
+    let old = Node::from_reader(include_str!("data/struct_return.xml").as_bytes())?;
+    let new = include_str!("data/struct_return.xml")
+        .replace(r#"type="((ss)i)""#, r#"type="((ss)u)""#)
+        .replace(
+            "  </interface>",
+            r#"    <property name="Count" type="u" access="read"/>
+  </interface>"#,
+        );
+    let new = Node::from_reader(new.as_bytes())?;
+
+    assert_eq!(
+        zbus_xmlgen::compatibility_report(&zbus_xml::diff::diff_nodes(&old, &new)),
+        "Breaking changes:
+  - test.StructReturn: output arguments of method `ReturnsNestedStruct` changed from `(((ss)i))` to `(((ss)u))`
+
+Additive changes:
+  - test.StructReturn: property `Count` added
+"
+    );
+    assert_eq!(
+        zbus_xmlgen::compatibility_report(&zbus_xml::diff::diff_nodes(&old, &old)),
+        "No changes.\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn sample_object0_server_introspection() -> Result<(), Box<dyn Error>> {
+    use zbus::object_server::Interface as _;
+
+    let mut xml = String::from("<node>");
+    generated_servers::sample_object0::SampleInterface0.introspect_to_writer(&mut xml, 0);
+    xml.push_str("</node>");
+    let generated = Node::from_reader(xml.as_bytes())?;
+    let original = Node::from_reader(include_str!("data/sample_object0.xml").as_bytes())?;
+
+    assert_eq!(
+        members(&generated.interfaces()[0]),
+        members(&original.interfaces()[0])
+    );
+    Ok(())
+}
+
+/// A summary of the members of `interface`, to compare interfaces regardless of annotations and
+/// default argument directions.
+fn members(interface: &Interface<'_>) -> Vec<String> {
+    let signatures = |args: &[zbus_xml::Arg], direction| {
+        args.iter()
+            .filter(|a| a.direction().unwrap_or(zbus_xml::ArgDirection::In) == direction)
+            .map(|a| a.ty().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut members: Vec<_> = interface
+        .methods()
+        .iter()
+        .map(|m| {
+            format!(
+                "method {}({}) -> ({})",
+                m.name(),
+                signatures(m.args(), zbus_xml::ArgDirection::In),
+                signatures(m.args(), zbus_xml::ArgDirection::Out),
+            )
+        })
+        .chain(interface.signals().iter().map(|s| {
+            let args: Vec<_> = s.args().iter().map(|a| a.ty().to_string()).collect();
+            format!("signal {}({})", s.name(), args.join(", "))
+        }))
+        .chain(interface.properties().iter().map(|p| {
+            format!(
+                "property {}: {} {:?}",
+                p.name(),
+                p.ty().to_string(),
+                p.access()
+            )
+        }))
+        .collect();
+    members.sort();
+
+    members
+}
+
+// Ensure the generated skeletons actually build.
+#[allow(
+    dead_code,
+    unused_variables,
+    clippy::disallowed_names,
+    clippy::type_complexity
+)]
+mod generated_servers {
+    use zbus::interface;
+
+    pub mod sample_object0 {
+        use super::*;
+
+        include!("data/sample_object0_server.rs");
+    }
+
+    mod struct_return {
+        use super::*;
+
+        include!("data/struct_return_server.rs");
+    }
+
+    pub mod struct_names {
+        use super::*;
+
+        include!("data/struct_names_server.rs");
+    }
+}
+
+// Ensure the generated proxies with named structs actually build.
+mod generated_proxies {
+    use zbus::proxy;
+
+    mod struct_names {
+        use super::*;
+
+        include!("data/struct_names.rs");
+    }
+}